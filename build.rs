@@ -0,0 +1,17 @@
+use std::process::Command;
+
+fn main() {
+    // embed the current commit so that it can be shown in the About panel and diagnostic reports
+    let hash = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_owned())
+        .unwrap_or_else(|| "unknown".to_owned());
+
+    println!("cargo:rustc-env=DECKOCR_GIT_HASH={hash}");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+}
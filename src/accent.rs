@@ -0,0 +1,160 @@
+//! Accent colour of the OCR window's selection highlight and loading spinner, either picked by the
+//! user or taken from the desktop's theme so the overlay blends in (eg. in Gaming Mode).
+
+use std::{process::Command, sync::OnceLock};
+
+use eframe::egui::{self, Color32};
+use serde::{Deserialize, Serialize};
+
+/// Accent colour of the Steam Deck's Gaming Mode, which can't be changed.
+const STEAM_ACCENT: [u8; 3] = [26, 159, 255];
+
+/// GNOME's named accent colours, as returned by `gsettings`.
+const GNOME_ACCENTS: &[(&str, [u8; 3])] = &[
+    ("blue", [53, 132, 228]),
+    ("teal", [33, 144, 164]),
+    ("green", [58, 148, 74]),
+    ("yellow", [200, 136, 0]),
+    ("orange", [237, 91, 0]),
+    ("red", [230, 45, 66]),
+    ("pink", [213, 97, 153]),
+    ("purple", [145, 65, 172]),
+    ("slate", [111, 131, 150]),
+];
+
+/// The system's accent colour, detected once.
+static SYSTEM_ACCENT: OnceLock<Option<[u8; 3]>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum AccentSource {
+    /// No accent colour, the highlights are white.
+    #[default]
+    None,
+    /// The accent colour of Steam's Gaming Mode or the desktop's theme, if it can be detected.
+    System,
+    /// A colour picked by the user.
+    Custom,
+}
+
+impl AccentSource {
+    pub const ALL: &'static [Self] = &[Self::None, Self::System, Self::Custom];
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::None => "None",
+            Self::System => "System",
+            Self::Custom => "Custom",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccentConfig {
+    pub source: AccentSource,
+    /// The colour used when `source` is `Custom`.
+    pub custom: [u8; 3],
+}
+
+impl Default for AccentConfig {
+    fn default() -> Self {
+        Self {
+            source: AccentSource::None,
+            custom: STEAM_ACCENT,
+        }
+    }
+}
+
+impl AccentConfig {
+    /// The accent colour, or `None` if highlights should stay white.
+    pub fn colour(&self) -> Option<Color32> {
+        let [r, g, b] = match self.source {
+            AccentSource::None => return None,
+            AccentSource::System => (*SYSTEM_ACCENT.get_or_init(detect_system_accent))?,
+            AccentSource::Custom => self.custom,
+        };
+        Some(Color32::from_rgb(r, g, b))
+    }
+
+    /// Show the UI for choosing the accent colour.
+    pub fn show_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Accent Colour:");
+            egui::ComboBox::from_id_salt("Accent Colour ComboBox")
+                .selected_text(self.source.name())
+                .show_ui(ui, |ui| {
+                    for source in AccentSource::ALL {
+                        ui.selectable_value(&mut self.source, *source, source.name());
+                    }
+                })
+                .response
+                .on_hover_text("Colour of the selected word's highlight and of the loading spinner.\nSystem uses Steam's accent colour in Gaming Mode, or the desktop's\n(KDE or GNOME) otherwise.");
+
+            match self.source {
+                AccentSource::None => {}
+                AccentSource::System => match self.colour() {
+                    Some(colour) => {
+                        let (_, rect) = ui.allocate_space(egui::vec2(16.0, 16.0));
+                        ui.painter()
+                            .rect_filled(rect, egui::CornerRadius::same(2), colour);
+                    }
+                    None => {
+                        ui.label("(not detected)");
+                    }
+                },
+                AccentSource::Custom => {
+                    ui.color_edit_button_srgb(&mut self.custom);
+                }
+            }
+        });
+    }
+}
+
+/// The accent colour of Steam's Gaming Mode, or of the desktop's theme.
+fn detect_system_accent() -> Option<[u8; 3]> {
+    let accent = steam_accent().or_else(kde_accent).or_else(gnome_accent);
+
+    match accent {
+        Some([r, g, b]) => log::info!("Detected system accent colour: #{r:02x}{g:02x}{b:02x}"),
+        None => log::info!("Could not detect a system accent colour"),
+    }
+
+    accent
+}
+
+fn steam_accent() -> Option<[u8; 3]> {
+    // set by Steam when running in Gaming Mode. `SteamDeck` isn't checked since it is also set
+    // in Desktop Mode, where the desktop's accent applies
+    std::env::var_os("SteamGamepadUI").map(|_| STEAM_ACCENT)
+}
+
+fn kde_accent() -> Option<[u8; 3]> {
+    let mut path = dirs::config_dir()?;
+    path.push("kdeglobals");
+    let kdeglobals = std::fs::read_to_string(path).ok()?;
+
+    let value = kdeglobals
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("AccentColor="))?;
+    let mut components = value.split(',').map(|c| c.trim().parse::<u8>().ok());
+
+    Some([
+        components.next()??,
+        components.next()??,
+        components.next()??,
+    ])
+}
+
+fn gnome_accent() -> Option<[u8; 3]> {
+    let output = Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.interface", "accent-color"])
+        .output()
+        .ok()?;
+    let name = String::from_utf8_lossy(&output.stdout);
+    let name = name.trim().trim_matches('\'');
+
+    GNOME_ACCENTS
+        .iter()
+        .find(|(accent, _)| *accent == name)
+        .map(|(_, colour)| *colour)
+}
@@ -0,0 +1,72 @@
+//! Opt-in benchmark mode, appending the time taken by every stage of each capture to a JSON lines
+//! file in the data directory, so releases and services can be compared with each other.
+
+use std::{collections::BTreeMap, fs::OpenOptions, io::Write, time::Duration};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::{config::AppConfig, data, stats};
+
+/// Name of the benchmark log, in the data directory.
+const BENCHMARK_FILE: &str = "benchmark.jsonl";
+
+/// A line of the benchmark log, written once a capture is shown to the user.
+#[derive(Serialize)]
+struct Entry {
+    /// When the capture was shown, in seconds since the Unix epoch.
+    timestamp: u64,
+    version: &'static str,
+    capture_service: String,
+    ocr_service: String,
+    dictionary_service: String,
+    srs_service: String,
+    characters: usize,
+    /// Time taken by each stage, in milliseconds, keyed by their name in snake case.
+    timings_ms: BTreeMap<String, u128>,
+}
+
+/// Append the timings of a capture to the benchmark log. `stages` are the time taken by the
+/// capture itself and the OCR window's loading stages, and `first_paint` the time between the
+/// window being opened and the text being shown.
+pub fn write_entry(
+    config: &AppConfig,
+    characters: usize,
+    stages: &[(&'static str, Duration)],
+    first_paint: Duration,
+) -> Result<()> {
+    let mut timings_ms = BTreeMap::new();
+
+    for (stage, duration) in stages {
+        let name = stage.to_lowercase().replace(['-', ' '], "_");
+        timings_ms.insert(name, duration.as_millis());
+    }
+    timings_ms.insert("first_paint".to_owned(), first_paint.as_millis());
+
+    let entry = Entry {
+        timestamp: stats::now(),
+        version: env!("CARGO_PKG_VERSION"),
+        capture_service: config.capture_service.name().to_owned(),
+        ocr_service: config.ocr_service.name().to_owned(),
+        dictionary_service: config.dictionary_service.name().to_owned(),
+        srs_service: config.srs_service.name().to_owned(),
+        characters,
+        timings_ms,
+    };
+
+    let mut path = data::data_dir()?;
+    std::fs::create_dir_all(&path)
+        .with_context(|| format!("Could not create data directory: `{}`", path.display()))?;
+    path.push(BENCHMARK_FILE);
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Could not open benchmark log: `{}`", path.display()))?;
+    let line = serde_json::to_string(&entry).context("Could not serialise benchmark entry")?;
+    writeln!(file, "{line}")
+        .with_context(|| format!("Could not write to benchmark log: `{}`", path.display()))?;
+
+    Ok(())
+}
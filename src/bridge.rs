@@ -0,0 +1,206 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+
+/// Game the reading time of text sent from the browser is attributed to.
+pub const BRIDGE_GAME: &str = "Browser";
+/// Largest request body accepted by the bridge, in bytes.
+const MAX_BODY_LEN: usize = 1024 * 1024;
+/// Origins of browser extensions, the only web pages allowed to send text to the bridge.
+const EXTENSION_ORIGINS: &[&str] = &["chrome-extension://", "moz-extension://"];
+
+/// Body of a request sent to the bridge.
+#[derive(Deserialize)]
+struct TextRequest {
+    text: String,
+}
+
+/// A small HTTP server on localhost, accepting text sent by a browser extension (eg. the text
+/// selected in a web page or ttsu reader) so it can be read in the OCR window without capturing
+/// the screen.
+///
+/// Text is sent as `POST /text` with a JSON body like `{"text": "..."}` and a `Content-Type` of
+/// `application/json`, so that browsers always send a preflight request first. Only browser
+/// extensions (and local programs, which send no `Origin`) are allowed, so that web pages the user
+/// visits can't open the OCR window.
+pub struct BridgeServer {
+    port: u16,
+    running: Arc<AtomicBool>,
+    /// Text received from the browser which hasn't been opened yet.
+    received: Arc<Mutex<Option<String>>>,
+}
+
+impl BridgeServer {
+    /// Start listening on localhost on the given port.
+    pub fn start(port: u16) -> Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+            .with_context(|| format!("Bridge: Failed to listen on port {port}"))?;
+        listener
+            .set_nonblocking(true)
+            .context("Bridge: Failed to set listener to non-blocking mode")?;
+
+        let running = Arc::new(AtomicBool::new(true));
+        let received = Arc::new(Mutex::new(None));
+
+        {
+            let running = Arc::clone(&running);
+            let received = Arc::clone(&received);
+            thread::spawn(move || {
+                while running.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            let received = Arc::clone(&received);
+                            thread::spawn(move || {
+                                if let Err(e) = handle_connection(stream, &received) {
+                                    log::warn!("Bridge: Failed to handle request: {e:#}");
+                                }
+                            });
+                        }
+                        Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                            thread::sleep(Duration::from_millis(100));
+                        }
+                        Err(e) => log::error!("Bridge: Failed to accept connection: {e}"),
+                    }
+                }
+            });
+        }
+
+        log::info!("Bridge: Listening on port {port}");
+
+        Ok(Self {
+            port,
+            running,
+            received,
+        })
+    }
+
+    /// The port this server is listening on.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Take the text received since the last call, if any. Only the latest text is kept.
+    pub fn take_text(&self) -> Option<String> {
+        self.received.lock().unwrap().take()
+    }
+}
+
+impl Drop for BridgeServer {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Split text received from the browser into paragraphs, one per non-empty line.
+pub fn paragraphs(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+fn handle_connection(stream: TcpStream, received: &Mutex<Option<String>>) -> Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut reader = BufReader::new(&stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+
+    let mut content_length = 0;
+    let mut content_type = String::new();
+    let mut origin = None;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().context("Invalid Content-Length")?;
+            } else if name.eq_ignore_ascii_case("content-type") {
+                content_type = value.trim().to_lowercase();
+            } else if name.eq_ignore_ascii_case("origin") {
+                origin = Some(value.trim().to_owned());
+            }
+        }
+    }
+
+    let origin = origin.as_deref();
+    if origin.is_some_and(|origin| !EXTENSION_ORIGINS.iter().any(|o| origin.starts_with(o))) {
+        respond(&stream, "403 Forbidden", None, "")?;
+        return Err(anyhow!("Rejected request from `{}`", origin.unwrap()));
+    }
+
+    match (method, path) {
+        // CORS preflight, sent by browsers before posting JSON
+        ("OPTIONS", _) => respond(&stream, "204 No Content", origin, ""),
+        ("POST", "/text") => {
+            // anything else could be sent by a web page without a preflight request
+            if content_type.split(';').next() != Some("application/json") {
+                return respond(&stream, "415 Unsupported Media Type", origin, "");
+            }
+
+            if content_length > MAX_BODY_LEN {
+                respond(&stream, "413 Payload Too Large", origin, "")?;
+                return Err(anyhow!(
+                    "Request body of {content_length} bytes is too large"
+                ));
+            }
+
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body)?;
+
+            let request: TextRequest = match serde_json::from_slice(&body) {
+                Ok(request) => request,
+                Err(e) => {
+                    respond(&stream, "400 Bad Request", origin, "")?;
+                    return Err(e).context("Could not parse request body");
+                }
+            };
+
+            if paragraphs(&request.text).is_empty() {
+                return respond(&stream, "400 Bad Request", origin, "");
+            }
+
+            log::info!(
+                "Bridge: Received {} characters from the browser",
+                request.text.chars().count()
+            );
+            *received.lock().unwrap() = Some(request.text);
+            respond(&stream, "200 OK", origin, "")
+        }
+        _ => respond(&stream, "404 Not Found", origin, ""),
+    }
+}
+
+/// Send a response. CORS headers are only sent for allowed `origin`s.
+fn respond(mut stream: &TcpStream, status: &str, origin: Option<&str>, body: &str) -> Result<()> {
+    let cors = match origin {
+        Some(origin) => format!("Access-Control-Allow-Origin: {origin}\r\nVary: Origin\r\nAccess-Control-Allow-Methods: POST, OPTIONS\r\nAccess-Control-Allow-Headers: Content-Type\r\n"),
+        None => String::new(),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n{cors}Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    Ok(())
+}
@@ -0,0 +1,198 @@
+use eframe::egui;
+use global_hotkey::hotkey;
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+
+use crate::services::ocr::ParagraphOrder;
+
+/// Share of pixels which may differ between two frames still considered identical, so that
+/// eg. a blinking cursor doesn't prevent the text from ever settling.
+const SETTLED_TOLERANCE: f32 = 0.001;
+
+/// What part of the screen should be captured.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CaptureTarget {
+    /// The whole primary monitor.
+    PrimaryMonitor,
+    /// The whole monitor with the given name.
+    Monitor { name: String },
+    /// The first window whose title contains the given text.
+    Window { title: String },
+    /// A region of the primary monitor, in pixels.
+    Region {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+}
+
+impl CaptureTarget {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::PrimaryMonitor => "Primary Monitor",
+            Self::Monitor { .. } => "Monitor",
+            Self::Window { .. } => "Window",
+            Self::Region { .. } => "Region",
+        }
+    }
+
+    /// Show the UI for choosing the target and its parameters.
+    pub fn show_ui(&mut self, ui: &mut egui::Ui, id_salt: impl std::hash::Hash) {
+        let targets = [
+            Self::PrimaryMonitor,
+            Self::Monitor {
+                name: String::new(),
+            },
+            Self::Window {
+                title: String::new(),
+            },
+            Self::Region {
+                x: 0,
+                y: 0,
+                width: 1280,
+                height: 200,
+            },
+        ];
+
+        ui.horizontal(|ui| {
+            ui.label("Capture:");
+            egui::ComboBox::from_id_salt(id_salt)
+                .selected_text(self.name())
+                .show_ui(ui, |ui| {
+                    for target in targets {
+                        let selected =
+                            std::mem::discriminant(self) == std::mem::discriminant(&target);
+                        if ui.selectable_label(selected, target.name()).clicked() && !selected {
+                            *self = target;
+                        }
+                    }
+                });
+        });
+
+        match self {
+            Self::PrimaryMonitor => {}
+            Self::Monitor { name } => {
+                ui.horizontal(|ui| {
+                    ui.label("Monitor Name:");
+                    ui.text_edit_singleline(name);
+                });
+            }
+            Self::Window { title } => {
+                ui.horizontal(|ui| {
+                    ui.label("Window Title Contains:");
+                    ui.text_edit_singleline(title);
+                });
+            }
+            Self::Region {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                ui.horizontal(|ui| {
+                    ui.label("Position:");
+                    ui.add(egui::DragValue::new(x));
+                    ui.label("×");
+                    ui.add(egui::DragValue::new(y));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Size:");
+                    ui.add(egui::DragValue::new(width).range(1..=u32::MAX));
+                    ui.label("×");
+                    ui.add(egui::DragValue::new(height).range(1..=u32::MAX));
+                });
+            }
+        }
+    }
+}
+
+/// A capture target bound to its own hotkey.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapturePreset {
+    /// Identifies the preset, so that its hotkey keeps triggering it when other presets are
+    /// removed. 0 until `AppConfig::assign_preset_ids` gives it one.
+    #[serde(default)]
+    pub id: u64,
+    /// Name shown to the user.
+    pub name: String,
+    /// Modifiers for the preset's hotkey. Details: https://w3c.github.io/uievents-key/#keys-modifier
+    pub hotkey_modifiers: hotkey::Modifiers,
+    /// Keycode for the preset's hotkey. Details: https://w3c.github.io/uievents-code/
+    pub hotkey_keycode: hotkey::Code,
+    /// What the preset captures.
+    pub target: CaptureTarget,
+    /// Order paragraphs should be read in for this preset, if it differs from the default one.
+    #[serde(default)]
+    pub paragraph_order: Option<ParagraphOrder>,
+    /// Preprocessing applied to this preset's captures, if it differs from the default one.
+    #[serde(default)]
+    pub preprocessing: Option<Preprocessing>,
+    /// Name of the jpdb account used while reading this preset's captures, if it differs from the
+    /// selected one, eg. to keep a separate deck per game.
+    #[serde(default)]
+    pub jpdb_account: Option<String>,
+}
+
+impl CapturePreset {
+    pub fn hotkey(&self) -> hotkey::HotKey {
+        hotkey::HotKey::new(Some(self.hotkey_modifiers), self.hotkey_keycode)
+    }
+}
+
+/// Adjustments made to a capture before it is sent to the OCR service, eg. so that light text on
+/// a dark background is read more reliably. The screenshot shown in the OCR window is unchanged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preprocessing {
+    /// Whether colours are removed.
+    pub grayscale: bool,
+    /// Whether colours are inverted.
+    pub invert: bool,
+}
+
+impl Preprocessing {
+    /// Apply the preprocessing to a capture, if there is any to apply.
+    pub fn apply(&self, image: RgbaImage) -> RgbaImage {
+        if !self.grayscale && !self.invert {
+            return image;
+        }
+
+        let mut image = image;
+        for pixel in image.pixels_mut() {
+            if self.grayscale {
+                let [r, g, b, _] = pixel.0;
+                let luma = (0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32) as u8;
+                pixel.0[..3].fill(luma);
+            }
+            if self.invert {
+                for channel in &mut pixel.0[..3] {
+                    *channel = 255 - *channel;
+                }
+            }
+        }
+
+        image
+    }
+
+    /// Show the UI for choosing the preprocessing steps.
+    pub fn show_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.grayscale, "Grayscale");
+            ui.checkbox(&mut self.invert, "Invert Colours")
+                .on_hover_text("Helps some OCR models read light text on a dark background.");
+        });
+    }
+}
+
+/// Whether two frames taken one after the other are close enough to consider the text on screen
+/// settled, eg. because a typewriter animation finished.
+pub fn frames_match(a: &RgbaImage, b: &RgbaImage) -> bool {
+    if a.dimensions() != b.dimensions() {
+        return false;
+    }
+
+    let different = a.pixels().zip(b.pixels()).filter(|(a, b)| a != b).count();
+
+    different as f32 <= (a.width() * a.height()) as f32 * SETTLED_TOLERANCE
+}
@@ -0,0 +1,260 @@
+use std::{
+    io::{Read, Write},
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream, UdpSocket},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+
+use crate::{
+    gui::ocr_window::{OcrWindow, State},
+    services::{srs, Services},
+    word::Word,
+};
+
+const COMPANION_PAGE: &str = include_str!("../assets/companion.html");
+
+/// The session currently published by the companion server, along with a version number which is
+/// bumped every time it changes.
+#[derive(Default)]
+struct Published {
+    version: u64,
+    session: String,
+}
+
+#[derive(Default)]
+struct Shared {
+    published: Mutex<Published>,
+    changed: Condvar,
+}
+
+/// When the published OCR window was created, its selected word and the card states generation.
+type PublishedKey = (Instant, (usize, usize), u64);
+
+/// A small HTTP server serving the current OCR session as a live web page, so that it can be
+/// read from another device on the local network.
+pub struct CompanionServer {
+    port: u16,
+    running: Arc<AtomicBool>,
+    shared: Arc<Shared>,
+    /// URL at which this server can likely be reached, found when it was started.
+    url: String,
+    /// Identifies the last session, selection and card states generation we published, to avoid
+    /// re-serialising every frame.
+    last_published: Option<Option<PublishedKey>>,
+}
+
+impl CompanionServer {
+    /// Start listening on all interfaces on the given port.
+    pub fn start(port: u16) -> Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, port))
+            .with_context(|| format!("Companion: Failed to listen on port {port}"))?;
+        listener
+            .set_nonblocking(true)
+            .context("Companion: Failed to set listener to non-blocking mode")?;
+
+        let running = Arc::new(AtomicBool::new(true));
+        let shared = Arc::new(Shared::default());
+
+        {
+            let running = Arc::clone(&running);
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || {
+                while running.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            let running = Arc::clone(&running);
+                            let shared = Arc::clone(&shared);
+                            thread::spawn(move || {
+                                if let Err(e) = handle_connection(stream, &running, &shared) {
+                                    log::debug!("Companion: Connection closed: {e:?}");
+                                }
+                            });
+                        }
+                        Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                            thread::sleep(Duration::from_millis(100));
+                        }
+                        Err(e) => log::error!("Companion: Failed to accept connection: {e}"),
+                    }
+                }
+            });
+        }
+
+        log::info!("Companion: Listening on port {port}");
+
+        Ok(Self {
+            port,
+            running,
+            shared,
+            url: local_url(port),
+            last_published: None,
+        })
+    }
+
+    /// The port this server is listening on.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Publish the state of the given OCR window to all connected clients, if it changed.
+    pub fn update(&mut self, ocr_window: Option<&OcrWindow>, services: &Services) {
+        let ready_state = ocr_window.and_then(|window| match &window.state {
+            State::Ready(state) => Some((window, state)),
+            _ => None,
+        });
+
+        // card states change when words are mined or refreshed, even if the selection doesn't
+        let key = ready_state.map(|(window, state)| {
+            (
+                window.created_at,
+                state.selected_word,
+                srs::card_states_generation(),
+            )
+        });
+
+        if self.last_published == Some(key) {
+            return;
+        }
+        self.last_published = Some(key);
+
+        let session = match ready_state {
+            None => Value::Null,
+            Some((_, state)) => json!({
+                "paragraphs": state
+                    .words
+                    .iter()
+                    .map(|paragraph| paragraph
+                        .iter()
+                        .map(|word| word_to_json(word, services))
+                        .collect::<Vec<_>>())
+                    .collect::<Vec<_>>(),
+                "selected": [state.selected_word.0, state.selected_word.1],
+            }),
+        };
+
+        let mut published = self.shared.published.lock().unwrap();
+        published.version += 1;
+        published.session = session.to_string();
+        self.shared.changed.notify_all();
+    }
+
+    /// A URL at which this server can likely be reached from other devices on the local network.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+/// A URL at which a server listening on `port` can likely be reached from other devices on the
+/// local network.
+fn local_url(port: u16) -> String {
+    // connecting a UDP socket doesn't send anything, but lets us find which interface is used
+    // for outbound traffic
+    let ip = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+        .and_then(|socket| {
+            socket.connect((Ipv4Addr::new(192, 168, 0, 1), 80))?;
+            socket.local_addr()
+        })
+        .map(|addr| addr.ip())
+        .unwrap_or(Ipv4Addr::LOCALHOST.into());
+
+    format!("http://{}", SocketAddr::new(ip, port))
+}
+
+impl Drop for CompanionServer {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        // wake up websocket threads so they notice we're shutting down
+        self.shared.changed.notify_all();
+    }
+}
+
+fn word_to_json(word: &Word, services: &Services) -> Value {
+    let [r, g, b] = services.srs.card_state(word).colour;
+
+    json!({
+        "text": word
+            .text
+            .0
+            .iter()
+            .map(|fragment| json!([fragment.text, fragment.ruby]))
+            .collect::<Vec<_>>(),
+        "colour": format!("#{r:02x}{g:02x}{b:02x}"),
+        "card_state": services.srs.card_state(word).name,
+        "definition": word.definition.as_ref().map(|definition| json!({
+            "spelling": definition.spelling,
+            "reading": definition.reading,
+            "frequency": definition.frequency,
+            "meanings": definition.meanings,
+        })),
+    })
+}
+
+fn handle_connection(stream: TcpStream, running: &AtomicBool, shared: &Shared) -> Result<()> {
+    stream.set_nonblocking(false)?;
+
+    let mut buf = [0; 1024];
+    let len = stream.peek(&mut buf)?;
+    let request = String::from_utf8_lossy(&buf[..len]);
+
+    if request.starts_with("GET /ws ") {
+        serve_websocket(stream, running, shared)
+    } else {
+        serve_page(stream)
+    }
+}
+
+fn serve_page(mut stream: TcpStream) -> Result<()> {
+    // consume the request, we serve the same page no matter what was asked for
+    let mut buf = [0; 4096];
+    let _ = stream.read(&mut buf)?;
+
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        COMPANION_PAGE.len(),
+        COMPANION_PAGE
+    )?;
+
+    Ok(())
+}
+
+fn serve_websocket(stream: TcpStream, running: &AtomicBool, shared: &Shared) -> Result<()> {
+    let mut socket = tungstenite::accept(stream).context("Websocket handshake failed")?;
+
+    let mut last_version = None;
+
+    while running.load(Ordering::Relaxed) {
+        let session = {
+            let published = shared.published.lock().unwrap();
+            let (published, _) = shared
+                .changed
+                .wait_timeout_while(published, Duration::from_secs(5), |published| {
+                    Some(published.version) == last_version && running.load(Ordering::Relaxed)
+                })
+                .unwrap();
+
+            if Some(published.version) == last_version {
+                None
+            } else {
+                last_version = Some(published.version);
+                Some(published.session.clone())
+            }
+        };
+
+        match session {
+            Some(session) => socket.send(tungstenite::Message::text(session))?,
+            // keep the connection alive, and find out early if the client went away
+            None => socket.send(tungstenite::Message::Ping(Default::default()))?,
+        }
+    }
+
+    socket.close(None)?;
+
+    Ok(())
+}
@@ -1,16 +1,53 @@
-use std::fs::File;
+use std::{fs::File, path::PathBuf};
 
 use anyhow::{anyhow, Context, Result};
 use eframe::egui::{self};
 use global_hotkey::hotkey;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use crate::services::{
-    dictionary::{jpdb_dictionary::JpdbDictionary, DictionaryService},
-    ocr::{owocr::Owocr, OcrService},
-    srs::{jpdb_srs::JpdbSrs, SrsService},
+use crate::{
+    accent::AccentConfig,
+    capture::{CapturePreset, CaptureTarget, Preprocessing},
+    discord::DiscordConfig,
+    hooks::HookConfig,
+    services::{
+        capture::{
+            clipboard::Clipboard,
+            command::{Command, CommandConfig},
+            image_file::{ImageFile, ImageFileConfig},
+            portal::Portal,
+            screen::Screen,
+            CaptureService,
+        },
+        dictionary::{
+            jpdb_dictionary::{JpdbDictionary, JpdbDictionaryConfig},
+            DictionaryService,
+        },
+        jpdb,
+        ocr::{
+            owocr::{Owocr, OwocrConfig},
+            OcrService, ParagraphOrder,
+        },
+        srs::{
+            jpdb_srs::{JpdbSrs, JpdbSrsConfig},
+            SrsService,
+        },
+    },
+    summary::WeeklySummaryConfig,
+    word::{DefinitionSuppression, PartOfSpeechFilter},
 };
 
+/// Explains the paragraph order settings, which only apply with some OCR services.
+const PARAGRAPH_ORDER_HOVER_TEXT: &str = "How paragraphs are sorted, when the OCR service returns their position.\nOnly OCR services returning the position of the text support this: owocr\ndoesn't, and keeps the order it reads the text in.";
+
+/// Path to deckocr's configuration directory.
+pub fn config_dir() -> Result<PathBuf> {
+    let mut config_dir =
+        dirs::config_dir().ok_or_else(|| anyhow!("Could not find suitable config diractory"))?;
+    config_dir.push(env!("CARGO_PKG_NAME"));
+    Ok(config_dir)
+}
+
 /// Represents a configuration file.
 pub trait Config: Serialize + DeserializeOwned + Default {
     /// Relative path to the configuration file, assuming `./` is the deckocr configuration directory.
@@ -21,9 +58,7 @@ pub trait Config: Serialize + DeserializeOwned + Default {
 
     /// Load a configuration file, or create a default configuration struct if the file does not exist.
     fn load() -> Result<Self> {
-        let mut config_path = dirs::config_dir()
-            .ok_or_else(|| anyhow!("Could not find suitable config diractory"))?;
-        config_path.push(env!("CARGO_PKG_NAME"));
+        let mut config_path = config_dir()?;
         config_path.push(Self::path());
 
         if !config_path.exists() {
@@ -50,9 +85,7 @@ pub trait Config: Serialize + DeserializeOwned + Default {
 
     /// Save a configuration file.
     fn save(&self) -> Result<()> {
-        let mut config_path = dirs::config_dir()
-            .ok_or_else(|| anyhow!("Could not find suitable config diractory"))?;
-        config_path.push(env!("CARGO_PKG_NAME"));
+        let mut config_path = config_dir()?;
         config_path.push(Self::path());
 
         let mut config_dir = config_path.clone();
@@ -84,18 +117,40 @@ pub trait Config: Serialize + DeserializeOwned + Default {
 
 /// `deckocr`'s main configuration file.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppConfig {
     /// Modifiers for the OCR hotkey. Details: https://w3c.github.io/uievents-key/#keys-modifier
     pub hotkey_modifiers: hotkey::Modifiers,
     /// Keycode for the OCR hotkey. Details: https://w3c.github.io/uievents-code/
     pub hotkey_keycode: hotkey::Code,
+    /// Modifiers for the hotkey capturing a page to be stitched together with the next OCR capture.
+    pub stitch_hotkey_modifiers: hotkey::Modifiers,
+    /// Keycode for the hotkey capturing a page to be stitched together with the next OCR capture.
+    pub stitch_hotkey_keycode: hotkey::Code,
+    /// Whether holding the OCR hotkey (instead of tapping it) lets the user select the region to read.
+    pub hold_for_region: bool,
+    /// How long the OCR hotkey needs to be held to select a region, in milliseconds.
+    pub hold_threshold_ms: u64,
+    /// Additional capture targets, each bound to their own hotkey.
+    pub capture_presets: Vec<CapturePreset>,
 
+    /// The capture service selected by the user.
+    pub capture_service: CaptureServiceList,
     /// The OCR service selected by the user.
     pub ocr_service: OcrServiceList,
     /// The dictionary service selected by the user.
     pub dictionary_service: DictionaryServiceList,
     /// The SRS service selected by the user.
     pub srs_service: SrsServiceList,
+    /// Order paragraphs are read in, when the OCR service returns their bounds.
+    pub paragraph_order: ParagraphOrder,
+    /// Preprocessing applied to captures before they are sent to the OCR service.
+    pub preprocessing: Preprocessing,
+    /// Paragraphs with fewer characters than this are merged into their neighbours (or dropped)
+    /// before being parsed. 0 to keep every paragraph as is.
+    pub min_paragraph_chars: usize,
+    /// Whether short paragraphs should be merged into their neighbours instead of being dropped.
+    pub merge_short_paragraphs: bool,
 
     /// The UI scaling for the whole app. Passed to `egui::Context::set_zoom_factor`.
     pub zoom_factor: f32,
@@ -107,6 +162,78 @@ pub struct AppConfig {
     pub window_height: u32,
     /// How dim should the screenshot shown in the background of the OCR window be.
     pub background_dimming: u8,
+    /// Whether the OCR window should be drawn in black and white, without the screenshot in its
+    /// background, eg. for e-ink displays. Card states are shown with their underline style
+    /// instead of colours.
+    pub monochrome: bool,
+    /// Colour of the selection highlight and loading spinner in the OCR window.
+    pub accent: AccentConfig,
+    /// Whether Tab should move the keyboard focus between the OCR window's text, meanings and bottom
+    /// bar actions, shown with focus rings, for users reading with a keyboard only.
+    pub keyboard_focus_mode: bool,
+    /// Delay between pressing a capture hotkey and taking the screenshot, in milliseconds. 0 to capture immediately.
+    pub capture_delay_ms: u64,
+    /// Whether captures taken with a hotkey should wait until the screen stops changing, eg. until
+    /// a typewriter animation finishes.
+    pub settled_capture: bool,
+    /// Interval between the frames compared to each other when waiting for the screen to settle, in milliseconds.
+    pub settled_capture_interval_ms: u64,
+    /// How many meanings are shown for the selected word before the rest is hidden behind an
+    /// "expand" action. 0 to always show every meaning.
+    pub max_meanings: usize,
+    /// How often the card states of the words in the OCR window are reloaded, to show changes made
+    /// elsewhere (eg. reviews done on another device), in seconds. 0 to never reload them.
+    pub card_state_refresh_secs: u64,
+    /// Whether the monolingual dictionary's meanings are shown instead of the dictionary service's
+    /// when the OCR window opens, if there are any.
+    pub monolingual_by_default: bool,
+    /// How many paragraphs are shown at once in the OCR window, the rest being split into pages
+    /// flipped with L1 and R1. 0 to show every paragraph on a single page.
+    pub paragraphs_per_page: usize,
+    /// Path to a font used for characters the built-in font can't render. Empty if unused.
+    pub fallback_font: String,
+
+    /// Time spent on each word in auto-scroll mode, in milliseconds.
+    pub auto_scroll_pace_ms: u64,
+    /// Whether auto-scroll mode should advance through paragraphs instead of words. Each paragraph
+    /// is shown for `auto_scroll_pace_ms` per word it contains.
+    pub auto_scroll_paragraphs: bool,
+    /// Whether auto-scroll mode should pause when it reaches an unknown word.
+    pub auto_scroll_pause_on_unknown: bool,
+    /// Parts of speech skipped over when skipping irrelevant words, and left out of quick reviews.
+    pub part_of_speech_filter: PartOfSpeechFilter,
+    /// Kana-only words shown as plain text, without their definitions.
+    pub definition_suppression: DefinitionSuppression,
+
+    /// Whether the current OCR session should be served as a web page on the local network.
+    pub companion_enabled: bool,
+    /// Port the companion web page is served on.
+    pub companion_port: u16,
+    /// Whether text sent by a browser extension should be opened in the OCR window.
+    pub bridge_enabled: bool,
+    /// Port on localhost the browser extension sends text to.
+    pub bridge_port: u16,
+
+    /// How many times services should attempt an HTTP request before giving up on transient errors.
+    pub network_retry_attempts: u32,
+    /// Delay before retrying a failed HTTP request, in milliseconds. Doubled after every attempt.
+    pub network_retry_base_delay_ms: u64,
+    /// Whether services should be sent a warm-up request when they are started.
+    pub warm_up_services: bool,
+    /// How long each loading stage is expected to take before a hint is shown.
+    pub latency_budgets: LatencyBudgets,
+
+    /// User scripts run at different stages of the pipeline.
+    pub hooks: HookConfig,
+    /// Optional Discord webhook integration.
+    pub discord: DiscordConfig,
+    /// Optional weekly digest of the user's mining activity.
+    pub weekly_summary: WeeklySummaryConfig,
+
+    /// Debug option: record the payloads sent to services, and don't send requests with side effects.
+    pub dry_run: bool,
+    /// Debug option: append the time taken by each stage of every capture to a log in the data directory.
+    pub benchmark_mode: bool,
 }
 
 impl Default for AppConfig {
@@ -114,16 +241,172 @@ impl Default for AppConfig {
         Self {
             hotkey_modifiers: hotkey::Modifiers::ALT,
             hotkey_keycode: hotkey::Code::F12,
+            stitch_hotkey_modifiers: hotkey::Modifiers::ALT,
+            stitch_hotkey_keycode: hotkey::Code::F11,
+            hold_for_region: false,
+            hold_threshold_ms: 400,
+            capture_presets: Vec::new(),
 
+            capture_service: CaptureServiceList::Screen,
             ocr_service: OcrServiceList::Owocr,
             dictionary_service: DictionaryServiceList::Jpdb,
             srs_service: SrsServiceList::Jpdb,
+            paragraph_order: ParagraphOrder::TopToBottom,
+            preprocessing: Preprocessing::default(),
+            min_paragraph_chars: 0,
+            merge_short_paragraphs: true,
 
             zoom_factor: 1.0,
             fullscreen: true,
             window_width: 1280,
             window_height: 720,
             background_dimming: 204,
+            monochrome: false,
+            accent: AccentConfig::default(),
+            keyboard_focus_mode: false,
+            capture_delay_ms: 0,
+            settled_capture: false,
+            settled_capture_interval_ms: 150,
+            max_meanings: 3,
+            card_state_refresh_secs: 60,
+            monolingual_by_default: false,
+            paragraphs_per_page: 50,
+            fallback_font: String::new(),
+
+            auto_scroll_pace_ms: 800,
+            auto_scroll_paragraphs: false,
+            auto_scroll_pause_on_unknown: true,
+            part_of_speech_filter: PartOfSpeechFilter::default(),
+            definition_suppression: DefinitionSuppression::default(),
+
+            companion_enabled: false,
+            companion_port: 7332,
+            bridge_enabled: false,
+            bridge_port: 7333,
+
+            network_retry_attempts: 3,
+            network_retry_base_delay_ms: 500,
+            warm_up_services: false,
+            latency_budgets: LatencyBudgets::default(),
+
+            hooks: HookConfig::default(),
+            discord: DiscordConfig::default(),
+            weekly_summary: WeeklySummaryConfig::default(),
+
+            dry_run: false,
+            benchmark_mode: false,
+        }
+    }
+}
+
+impl AppConfig {
+    /// Give an id to the capture presets which don't have one yet (or share one with another
+    /// preset), eg. because they were written by an older version of deckocr.
+    pub fn assign_preset_ids(&mut self) {
+        let mut seen = Vec::new();
+        for idx in 0..self.capture_presets.len() {
+            let id = self.capture_presets[idx].id;
+            if id == 0 || seen.contains(&id) {
+                self.capture_presets[idx].id = self.next_preset_id();
+            }
+            seen.push(self.capture_presets[idx].id);
+        }
+    }
+
+    /// The capture preset with the given id, if it wasn't removed.
+    pub fn capture_preset(&self, id: u64) -> Option<&CapturePreset> {
+        self.capture_presets.iter().find(|preset| preset.id == id)
+    }
+
+    /// The capture preset with the given id, if it wasn't removed.
+    pub fn capture_preset_mut(&mut self, id: u64) -> Option<&mut CapturePreset> {
+        self.capture_presets
+            .iter_mut()
+            .find(|preset| preset.id == id)
+    }
+
+    /// Id for a new capture preset.
+    fn next_preset_id(&self) -> u64 {
+        let max = self.capture_presets.iter().map(|preset| preset.id).max();
+        max.unwrap_or(0) + 1
+    }
+
+    /// Hotkey for a new capture preset, which no other hotkey uses yet.
+    fn free_preset_hotkey(&self) -> hotkey::HotKey {
+        let taken: Vec<_> = [
+            hotkey::HotKey::new(Some(self.hotkey_modifiers), self.hotkey_keycode),
+            hotkey::HotKey::new(
+                Some(self.stitch_hotkey_modifiers),
+                self.stitch_hotkey_keycode,
+            ),
+        ]
+        .into_iter()
+        .chain(self.capture_presets.iter().map(CapturePreset::hotkey))
+        .collect();
+
+        let keys = [
+            hotkey::Code::F1,
+            hotkey::Code::F2,
+            hotkey::Code::F3,
+            hotkey::Code::F4,
+            hotkey::Code::F5,
+            hotkey::Code::F6,
+            hotkey::Code::F7,
+            hotkey::Code::F8,
+            hotkey::Code::F9,
+            hotkey::Code::F10,
+            hotkey::Code::F11,
+            hotkey::Code::F12,
+        ];
+        let modifiers = [
+            hotkey::Modifiers::ALT,
+            hotkey::Modifiers::CONTROL | hotkey::Modifiers::ALT,
+            hotkey::Modifiers::ALT | hotkey::Modifiers::SHIFT,
+        ];
+
+        modifiers
+            .into_iter()
+            .flat_map(|mods| keys.map(|key| hotkey::HotKey::new(Some(mods), key)))
+            .find(|hotkey| !taken.contains(hotkey))
+            // every candidate is taken, the user will have to pick another one
+            .unwrap_or_else(|| hotkey::HotKey::new(Some(hotkey::Modifiers::ALT), hotkey::Code::F1))
+    }
+}
+
+/// Text field for editing a hotkey, eg. `ALT+F1`. The hotkey is changed once the field loses
+/// focus, if the text is a valid hotkey.
+fn hotkey_edit(
+    ui: &mut egui::Ui,
+    id_salt: impl std::hash::Hash,
+    modifiers: &mut hotkey::Modifiers,
+    keycode: &mut hotkey::Code,
+) {
+    let id = ui.make_persistent_id(id_salt);
+    let current = hotkey::HotKey::new(Some(*modifiers), *keycode)
+        .to_string()
+        .to_uppercase();
+    let mut text = ui
+        .data_mut(|data| data.get_temp::<String>(id.with("text")))
+        .unwrap_or_else(|| current.clone());
+
+    let response = ui.add(egui::TextEdit::singleline(&mut text).id(id));
+    let parsed = text.parse::<hotkey::HotKey>();
+
+    if response.has_focus() {
+        ui.data_mut(|data| data.insert_temp(id.with("text"), text));
+    } else {
+        ui.data_mut(|data| data.remove::<String>(id.with("text")));
+    }
+
+    match parsed {
+        Ok(hotkey) if response.lost_focus() => {
+            *modifiers = hotkey.mods;
+            *keycode = hotkey.key;
+        }
+        Ok(_) => {}
+        Err(e) => {
+            ui.colored_label(ui.visuals().error_fg_color, "Invalid hotkey")
+                .on_hover_text(e.to_string());
         }
     }
 }
@@ -138,17 +421,25 @@ impl Config for AppConfig {
 
         // TODO: let the user set the hotkey from the config panel directly
         ui.add_enabled_ui(false, |ui| {
-            let mut hotkey = global_hotkey::hotkey::HotKey::new(
-                Some(self.hotkey_modifiers),
-                self.hotkey_keycode,
-            )
-            .to_string()
-            .to_uppercase();
+            let hotkeys = [
+                ("OCR Hotkey: ", self.hotkey_modifiers, self.hotkey_keycode),
+                (
+                    "Stitch Page Hotkey: ",
+                    self.stitch_hotkey_modifiers,
+                    self.stitch_hotkey_keycode,
+                ),
+            ];
 
-            ui.horizontal(|ui| {
-                ui.label("OCR Hotkey: ");
-                ui.text_edit_singleline(&mut hotkey);
-            });
+            for (label, modifiers, keycode) in hotkeys {
+                let mut hotkey = global_hotkey::hotkey::HotKey::new(Some(modifiers), keycode)
+                    .to_string()
+                    .to_uppercase();
+
+                ui.horizontal(|ui| {
+                    ui.label(label);
+                    ui.text_edit_singleline(&mut hotkey);
+                });
+            }
         });
 
         let mut config_path = dirs::config_dir().unwrap();
@@ -168,8 +459,128 @@ impl Config for AppConfig {
             );
         });
 
+        ui.horizontal(|ui| {
+            ui.label("Hold OCR Hotkey to Select Region:");
+            ui.add(egui::Checkbox::without_text(&mut self.hold_for_region))
+                .on_hover_text("Tap the OCR hotkey to capture the whole screen, or hold it and\nrelease it to drag a rectangle around the text to read.");
+            ui.add_enabled(
+                self.hold_for_region,
+                egui::DragValue::new(&mut self.hold_threshold_ms)
+                    .range(100..=2000)
+                    .speed(10)
+                    .prefix("held for ")
+                    .suffix("ms"),
+            );
+        });
+
+        ui.collapsing("Capture Presets", |ui| {
+            ui.label("Each preset captures its own target when its hotkey is pressed.");
+
+            let mut remove = None;
+
+            for (idx, preset) in self.capture_presets.iter_mut().enumerate() {
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut preset.name);
+                    if ui.button("Remove").clicked() {
+                        remove = Some(idx);
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Hotkey:");
+                    hotkey_edit(
+                        ui,
+                        ("capture preset hotkey", preset.id),
+                        &mut preset.hotkey_modifiers,
+                        &mut preset.hotkey_keycode,
+                    );
+                });
+                preset.target.show_ui(ui, ("capture preset target", idx));
+                ui.horizontal(|ui| {
+                    ui.label("Paragraph Order:");
+                    egui::ComboBox::from_id_salt(("capture preset paragraph order", idx))
+                        .selected_text(
+                            preset
+                                .paragraph_order
+                                .map(|order| order.name())
+                                .unwrap_or("Default"),
+                        )
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut preset.paragraph_order, None, "Default");
+                            for order in ParagraphOrder::ALL {
+                                ui.selectable_value(
+                                    &mut preset.paragraph_order,
+                                    Some(*order),
+                                    order.name(),
+                                );
+                            }
+                        });
+                })
+                .response
+                .on_hover_text(PARAGRAPH_ORDER_HOVER_TEXT);
+                ui.horizontal(|ui| {
+                    let mut custom = preset.preprocessing.is_some();
+                    if ui.checkbox(&mut custom, "Custom Preprocessing").changed() {
+                        preset.preprocessing = custom.then_some(self.preprocessing);
+                    }
+                    if let Some(preprocessing) = &mut preset.preprocessing {
+                        preprocessing.show_ui(ui);
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("jpdb Account:");
+                    egui::ComboBox::from_id_salt(("capture preset jpdb account", idx))
+                        .selected_text(preset.jpdb_account.as_deref().unwrap_or("Default"))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut preset.jpdb_account, None, "Default");
+                            for name in jpdb::account_names() {
+                                ui.selectable_value(
+                                    &mut preset.jpdb_account,
+                                    Some(name.clone()),
+                                    name,
+                                );
+                            }
+                        });
+                })
+                .response
+                .on_hover_text(
+                    "Account used by the jpdb services while reading this preset's captures,
+if it differs from the selected one.",
+                );
+            }
+
+            if let Some(idx) = remove {
+                self.capture_presets.remove(idx);
+            }
+
+            ui.separator();
+            if ui.button("Add Preset").clicked() {
+                let n = self.capture_presets.len() + 1;
+                let hotkey = self.free_preset_hotkey();
+                self.capture_presets.push(CapturePreset {
+                    id: self.next_preset_id(),
+                    name: format!("Preset {n}"),
+                    hotkey_modifiers: hotkey.mods,
+                    hotkey_keycode: hotkey.key,
+                    target: CaptureTarget::PrimaryMonitor,
+                    paragraph_order: None,
+                    preprocessing: None,
+                    jpdb_account: None,
+                });
+            }
+        });
+
         ui.add_space(spacing);
 
+        egui::ComboBox::from_label("Capture Service")
+            .selected_text(self.capture_service.name())
+            .show_ui(ui, |ui| {
+                for service in CaptureServiceList::ALL {
+                    ui.selectable_value(&mut self.capture_service, *service, service.name());
+                }
+            });
+
         egui::ComboBox::from_label("OCR Service")
             .selected_text(self.ocr_service.name())
             .show_ui(ui, |ui| {
@@ -194,6 +605,56 @@ impl Config for AppConfig {
                 }
             });
 
+        ui.horizontal(|ui| {
+            ui.label("Paragraph Order:");
+            egui::ComboBox::from_id_salt("Paragraph Order ComboBox")
+                .selected_text(self.paragraph_order.name())
+                .show_ui(ui, |ui| {
+                    for order in ParagraphOrder::ALL {
+                        ui.selectable_value(&mut self.paragraph_order, *order, order.name());
+                    }
+                });
+        })
+        .response
+        .on_hover_text(format!(
+            "{PARAGRAPH_ORDER_HOVER_TEXT}\nCapture presets can override this."
+        ));
+
+        ui.horizontal(|ui| {
+            ui.label("Preprocessing:");
+            self.preprocessing.show_ui(ui);
+        })
+        .response
+        .on_hover_text("Applied to captures before they are sent to the OCR service.\nCapture presets can override this.");
+
+        ui.horizontal(|ui| {
+            ui.label("Minimum Paragraph Length:");
+            ui.add(
+                egui::DragValue::new(&mut self.min_paragraph_chars)
+                    .range(0..=10)
+                    .custom_formatter(|n, _| match n as usize {
+                        0 => "Off".to_owned(),
+                        n => format!("{n} chars"),
+                    }),
+            )
+            .on_hover_text("Paragraphs shorter than this, eg. stray UI glyphs or particles split off\nby the OCR, are cleaned up before being looked up in the dictionary.");
+            ui.add_enabled_ui(self.min_paragraph_chars > 0, |ui| {
+                egui::ComboBox::from_id_salt("Short Paragraphs ComboBox")
+                    .selected_text(match self.merge_short_paragraphs {
+                        true => "Merge into Neighbours",
+                        false => "Drop",
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            &mut self.merge_short_paragraphs,
+                            true,
+                            "Merge into Neighbours",
+                        );
+                        ui.selectable_value(&mut self.merge_short_paragraphs, false, "Drop");
+                    });
+            });
+        });
+
         ui.add_space(spacing);
 
         ui.horizontal(|ui| {
@@ -242,6 +703,282 @@ impl Config for AppConfig {
                     }),
             );
         });
+
+        ui.horizontal(|ui| {
+            ui.label("Capture Delay:");
+            let mut enabled = self.capture_delay_ms > 0;
+            if ui.add(egui::Checkbox::without_text(&mut enabled)).changed() {
+                self.capture_delay_ms = if enabled { 1000 } else { 0 };
+            }
+            if enabled {
+                ui.add(
+                    egui::DragValue::new(&mut self.capture_delay_ms)
+                        .range(500..=3000)
+                        .speed(10)
+                        .suffix("ms"),
+                )
+                .on_hover_text("Wait before taking the screenshot after a capture hotkey is pressed,\nshowing a countdown, eg. to let a textbox finish its animation.");
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Wait for Text to Settle:");
+            ui.add(egui::Checkbox::without_text(&mut self.settled_capture))
+                .on_hover_text("Compare frames taken a short interval apart, and only capture once\nthey match, so text which is still being animated isn't captured.");
+            ui.add_enabled(
+                self.settled_capture,
+                egui::DragValue::new(&mut self.settled_capture_interval_ms)
+                    .range(50..=1000)
+                    .speed(5)
+                    .suffix("ms apart"),
+            );
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Meanings Shown:");
+            ui.add(
+                egui::DragValue::new(&mut self.max_meanings)
+                    .range(0..=20)
+                    .custom_formatter(|n, _| match n as usize {
+                        0 => "All".to_owned(),
+                        n => n.to_string(),
+                    }),
+            )
+            .on_hover_text(
+                "Meanings past this number are hidden until Y is pressed in the OCR window.",
+            );
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Refresh Card States Every:");
+            ui.add(
+                egui::DragValue::new(&mut self.card_state_refresh_secs)
+                    .range(0..=3600)
+                    .custom_formatter(|n, _| match n as u64 {
+                        0 => "Never".to_owned(),
+                        n => format!("{n}s"),
+                    }),
+            )
+            .on_hover_text("While the OCR window is open, card states are reloaded so that reviews\ndone on another device are shown, with a notification of what changed.");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Show Monolingual Meanings First:");
+            ui.add(egui::Checkbox::without_text(&mut self.monolingual_by_default))
+                .on_hover_text("Requires a monolingual dictionary, set in the dictionary section.\nR3 (or D) switches between the two in the OCR window.");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Paragraphs per Page:");
+            ui.add(
+                egui::DragValue::new(&mut self.paragraphs_per_page)
+                    .range(0..=500)
+                    .custom_formatter(|n, _| match n as usize {
+                        0 => "All".to_owned(),
+                        n => n.to_string(),
+                    }),
+            )
+            .on_hover_text("Large captures (eg. whole web pages) are split into pages,\nflipped with L1 / R1 or Page Up / Page Down, to keep the OCR window responsive.");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Monochrome:");
+            ui.add(egui::Checkbox::without_text(&mut self.monochrome))
+                .on_hover_text("High contrast black and white OCR window without a background image,\neg. for e-ink displays. Card states are told apart by their\nunderline style, set in the SRS service's configuration, instead of colours.");
+        });
+
+        ui.add_enabled_ui(!self.monochrome, |ui| self.accent.show_ui(ui));
+
+        ui.horizontal(|ui| {
+            ui.label("Keyboard Focus Mode:");
+            ui.add(egui::Checkbox::without_text(&mut self.keyboard_focus_mode))
+                .on_hover_text("Tab and Shift+Tab move the focus between the text, the meanings and the\nbottom bar actions in the OCR window. Arrow keys move within them, Enter activates.");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Fallback Font:");
+            ui.text_edit_singleline(&mut self.fallback_font)
+                .on_hover_text("Path to a .ttf or .otf font, used for characters the built-in font\ncan't render. Applied after restarting deckocr.");
+        });
+
+        ui.add_space(spacing);
+
+        ui.horizontal(|ui| {
+            ui.label("Auto-Scroll Pace:");
+            ui.add(
+                egui::DragValue::new(&mut self.auto_scroll_pace_ms)
+                    .range(100..=10000)
+                    .speed(10)
+                    .suffix("ms per word"),
+            )
+            .on_hover_text("Toggle auto-scroll in the OCR window with Space or L3.");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Auto-Scroll by Paragraph:");
+            ui.add(egui::Checkbox::without_text(
+                &mut self.auto_scroll_paragraphs,
+            ));
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Auto-Scroll Pauses on Unknown Words:");
+            ui.add(egui::Checkbox::without_text(
+                &mut self.auto_scroll_pause_on_unknown,
+            ))
+            .on_hover_text("Press Space or L3 again to resume.");
+        });
+
+        ui.add_space(spacing);
+
+        ui.horizontal(|ui| {
+            ui.label("Skip Over:").on_hover_text(
+                "Parts of speech treated as irrelevant when navigating with R2 held,
+auto-scrolling or reviewing. Requires a dictionary which reports parts of speech.",
+            );
+            let filter = &mut self.part_of_speech_filter;
+            ui.checkbox(&mut filter.skip_particles, "Particles");
+            ui.checkbox(&mut filter.skip_auxiliaries, "Auxiliaries");
+            ui.checkbox(&mut filter.skip_interjections, "Interjections");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Hide Definitions of Kana-Only:").on_hover_text(
+                "These words are shown as plain text in the OCR window, and can't be selected.",
+            );
+            let suppression = &mut self.definition_suppression;
+            ui.checkbox(&mut suppression.kana_only_particles, "Particles");
+            ui.label("Words up to Rank:");
+            ui.add(
+                egui::DragValue::new(&mut suppression.kana_only_max_frequency_rank)
+                    .range(0..=10000)
+                    .speed(10)
+                    .custom_formatter(|n, _| match n as u64 {
+                        0 => "Off".to_owned(),
+                        n => n.to_string(),
+                    }),
+            );
+        });
+
+        ui.add_space(spacing);
+
+        ui.horizontal(|ui| {
+            ui.label("Companion Web Page:");
+            ui.add(egui::Checkbox::without_text(&mut self.companion_enabled))
+                .on_hover_text("Serve the current OCR session as a web page, so it can be read from\nanother device on the same network.");
+        });
+
+        ui.add_enabled_ui(self.companion_enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Companion Port:");
+                ui.add(egui::DragValue::new(&mut self.companion_port).range(1024..=u16::MAX));
+            });
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Browser Bridge:");
+            ui.add(egui::Checkbox::without_text(&mut self.bridge_enabled))
+                .on_hover_text("Accept text from a browser extension (eg. selected in ttsu reader) and\nopen it in the OCR window without capturing the screen. The extension\nsends it as `POST /text` with a JSON body like `{\"text\": \"...\"}`.");
+        });
+
+        ui.add_enabled_ui(self.bridge_enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Bridge Port:");
+                ui.add(egui::DragValue::new(&mut self.bridge_port).range(1024..=u16::MAX));
+            });
+        });
+
+        ui.add_space(spacing);
+
+        ui.horizontal(|ui| {
+            ui.label("Network Retries:");
+            ui.add(egui::DragValue::new(&mut self.network_retry_attempts).range(1..=10))
+                .on_hover_text("Total number of attempts for requests failing because of\ntimeouts or server errors. Applied when services are reloaded.");
+            ui.label("attempts, starting at");
+            ui.add(
+                egui::DragValue::new(&mut self.network_retry_base_delay_ms)
+                    .range(0..=10000)
+                    .speed(10)
+                    .suffix("ms"),
+            );
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Warm Up Services on Start:");
+            ui.add(egui::Checkbox::without_text(&mut self.warm_up_services))
+                .on_hover_text("Send a small request to the OCR and dictionary services when they\nare started, so the first capture doesn't have to wait for them\nto load models or establish connections.");
+        });
+
+        ui.add_space(spacing);
+
+        ui.collapsing("Latency Budgets", |ui| self.latency_budgets.show_ui(ui))
+            .header_response
+            .on_hover_text("A hint is shown in the OCR window when a loading stage\ntakes longer than expected, eg. because a service isn't running.");
+        ui.collapsing("Hook Scripts", |ui| self.hooks.show_ui(ui));
+        ui.collapsing("Discord", |ui| self.discord.show_ui(ui));
+        ui.collapsing("Weekly Summary", |ui| self.weekly_summary.show_ui(ui));
+
+        ui.add_space(spacing);
+
+        ui.horizontal(|ui| {
+            ui.label("Dry Run:");
+            ui.add(egui::Checkbox::without_text(&mut self.dry_run))
+                .on_hover_text("Record the exact payloads sent to services, without sending\nrequests which would modify anything (eg. adding words to a deck).\nRead-only requests (eg. parsing text, loading card states) are still\nsent, as the OCR window can't show anything without them.");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Benchmark Mode:");
+            ui.add(egui::Checkbox::without_text(&mut self.benchmark_mode))
+                .on_hover_text("Append the time taken by each stage of every capture (capture, encode,\nOCR, dictionary, SRS, first paint) to `benchmark.jsonl` in the data directory.");
+        });
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum CaptureServiceList {
+    Screen,
+    ImageFile,
+    Clipboard,
+    Command,
+    Portal,
+}
+
+impl CaptureServiceList {
+    pub const ALL: &'static [Self] = &[
+        Self::Screen,
+        Self::ImageFile,
+        Self::Clipboard,
+        Self::Command,
+        Self::Portal,
+    ];
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Screen => "Screen",
+            Self::ImageFile => "Image File",
+            Self::Clipboard => "Clipboard",
+            Self::Command => "Command",
+            Self::Portal => "Screenshot Portal",
+        }
+    }
+
+    /// Path to the service's configuration file, relative to the configuration directory, if it has one.
+    pub fn config_path(&self) -> Option<&'static str> {
+        match self {
+            Self::Screen | Self::Clipboard | Self::Portal => None,
+            Self::ImageFile => Some(ImageFileConfig::path()),
+            Self::Command => Some(CommandConfig::path()),
+        }
+    }
+
+    pub fn create_service(&self) -> Box<dyn CaptureService> {
+        match self {
+            Self::Screen => Box::new(Screen),
+            Self::ImageFile => Box::new(ImageFile::default()),
+            Self::Clipboard => Box::new(Clipboard),
+            Self::Command => Box::new(Command::default()),
+            Self::Portal => Box::new(Portal),
+        }
     }
 }
 
@@ -259,6 +996,13 @@ impl OcrServiceList {
         }
     }
 
+    /// Path to the service's configuration file, relative to the configuration directory.
+    pub fn config_path(&self) -> &'static str {
+        match self {
+            Self::Owocr => OwocrConfig::path(),
+        }
+    }
+
     pub fn create_service(&self) -> Box<dyn OcrService> {
         match self {
             Self::Owocr => Box::new(Owocr::default()),
@@ -280,6 +1024,13 @@ impl DictionaryServiceList {
         }
     }
 
+    /// Path to the service's configuration file, relative to the configuration directory.
+    pub fn config_path(&self) -> &'static str {
+        match self {
+            Self::Jpdb => JpdbDictionaryConfig::path(),
+        }
+    }
+
     pub fn create_service(&self) -> Box<dyn DictionaryService> {
         match self {
             Self::Jpdb => Box::new(JpdbDictionary::default()),
@@ -301,9 +1052,63 @@ impl SrsServiceList {
         }
     }
 
+    /// Path to the service's configuration file, relative to the configuration directory.
+    pub fn config_path(&self) -> &'static str {
+        match self {
+            Self::Jpdb => JpdbSrsConfig::path(),
+        }
+    }
+
     pub fn create_service(&self) -> Box<dyn SrsService> {
         match self {
             Self::Jpdb => Box::new(JpdbSrs::default()),
         }
     }
 }
+
+/// How long each loading stage of the OCR window is expected to take, in milliseconds. A hint is
+/// shown while loading when a stage takes longer than this. 0 to never show one for that stage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LatencyBudgets {
+    pub ocr_ms: u64,
+    pub post_ocr_hook_ms: u64,
+    pub dictionary_ms: u64,
+    pub srs_ms: u64,
+}
+
+impl Default for LatencyBudgets {
+    fn default() -> Self {
+        Self {
+            ocr_ms: 5000,
+            post_ocr_hook_ms: 2000,
+            dictionary_ms: 3000,
+            srs_ms: 3000,
+        }
+    }
+}
+
+impl LatencyBudgets {
+    /// Show the UI for configuring the budgets.
+    pub fn show_ui(&mut self, ui: &mut egui::Ui) {
+        for (label, budget) in [
+            ("OCR:", &mut self.ocr_ms),
+            ("Post-OCR Hook:", &mut self.post_ocr_hook_ms),
+            ("Dictionary:", &mut self.dictionary_ms),
+            ("SRS:", &mut self.srs_ms),
+        ] {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                ui.add(
+                    egui::DragValue::new(budget)
+                        .range(0..=60000)
+                        .speed(50)
+                        .custom_formatter(|n, _| match n as u64 {
+                            0 => "Off".to_owned(),
+                            n => format!("{n}ms"),
+                        }),
+                );
+            });
+        }
+    }
+}
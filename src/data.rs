@@ -0,0 +1,57 @@
+use std::{fs::File, path::PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Path to deckocr's data directory, where files which aren't configuration (eg. the pin board) are stored.
+pub fn data_dir() -> Result<PathBuf> {
+    let mut data_dir =
+        dirs::data_dir().ok_or_else(|| anyhow!("Could not find suitable data directory"))?;
+    data_dir.push(env!("CARGO_PKG_NAME"));
+    Ok(data_dir)
+}
+
+/// Represents a file holding data generated while using deckocr. Works like `Config`, but lives
+/// in the data directory instead of the configuration directory.
+pub trait DataFile: Serialize + DeserializeOwned + Default {
+    /// Relative path to the data file, assuming `./` is the deckocr data directory.
+    fn path() -> &'static str;
+
+    /// Load a data file, or create a default struct if the file does not exist.
+    fn load() -> Result<Self> {
+        let mut data_path = data_dir()?;
+        data_path.push(Self::path());
+
+        if !data_path.exists() {
+            Ok(Self::default())
+        } else {
+            let file = File::open(&data_path)
+                .with_context(|| format!("Could not open data file: `{}`", data_path.display()))?;
+
+            let data = serde_json::from_reader(file)
+                .with_context(|| format!("Could not read data file: `{}`", data_path.display()))?;
+
+            Ok(data)
+        }
+    }
+
+    /// Save a data file.
+    fn save(&self) -> Result<()> {
+        let mut data_path = data_dir()?;
+        data_path.push(Self::path());
+
+        let mut data_dir = data_path.clone();
+        data_dir.pop();
+        std::fs::create_dir_all(&data_dir).with_context(|| {
+            format!("Could not create data directory: `{}`", data_dir.display())
+        })?;
+
+        let file = File::create(&data_path)
+            .with_context(|| format!("Could not write to data file: `{}`", data_path.display()))?;
+
+        serde_json::to_writer(file, self)
+            .with_context(|| format!("Could not serialise data file: `{}`", data_path.display()))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use std::{path::Path, process::Command};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    config::{self, AppConfig},
+    data,
+    services::health::{self, ServiceKind},
+};
+
+/// Version of deckocr, along with the commit it was built from.
+pub fn version() -> String {
+    format!(
+        "{} ({})",
+        env!("CARGO_PKG_VERSION"),
+        env!("DECKOCR_GIT_HASH")
+    )
+}
+
+/// The kind of graphical session deckocr is running in, eg. `X11`, `Wayland` or `gamescope`.
+pub fn session_type() -> String {
+    let var = |name| std::env::var(name).ok().filter(|v| !v.is_empty());
+
+    if cfg!(not(target_os = "linux")) {
+        return std::env::consts::OS.to_owned();
+    }
+
+    // gamescope (eg. Steam Deck game mode) runs games under Xwayland, so check it first
+    if var("GAMESCOPE_WAYLAND_DISPLAY").is_some() {
+        return "gamescope".to_owned();
+    }
+
+    match var("XDG_SESSION_TYPE").as_deref() {
+        Some("wayland") => "Wayland".to_owned(),
+        Some("x11") => "X11".to_owned(),
+        Some(other) => other.to_owned(),
+        None if var("WAYLAND_DISPLAY").is_some() => "Wayland".to_owned(),
+        None if var("DISPLAY").is_some() => "X11".to_owned(),
+        None => "unknown".to_owned(),
+    }
+}
+
+/// Open a folder in the system's file manager.
+pub fn open_folder(path: &Path) -> Result<()> {
+    std::fs::create_dir_all(path)
+        .with_context(|| format!("Could not create folder `{}`", path.display()))?;
+
+    let program = match std::env::consts::OS {
+        "windows" => "explorer",
+        "macos" => "open",
+        _ => "xdg-open",
+    };
+
+    Command::new(program).arg(path).spawn().with_context(|| {
+        format!(
+            "Could not open folder `{}` with `{program}`",
+            path.display()
+        )
+    })?;
+
+    Ok(())
+}
+
+/// A plain text report describing deckocr's environment and configuration, to be attached to bug
+/// reports. Doesn't contain API keys or other secrets.
+pub fn report(config: &AppConfig) -> String {
+    let path_or_error = |path: Result<std::path::PathBuf>| match path {
+        Ok(path) => path.display().to_string(),
+        Err(e) => format!("<{e}>"),
+    };
+
+    let services = [
+        (
+            "Capture",
+            config.capture_service.name(),
+            config.capture_service.config_path(),
+            ServiceKind::Capture,
+        ),
+        (
+            "OCR",
+            config.ocr_service.name(),
+            Some(config.ocr_service.config_path()),
+            ServiceKind::Ocr,
+        ),
+        (
+            "Dictionary",
+            config.dictionary_service.name(),
+            Some(config.dictionary_service.config_path()),
+            ServiceKind::Dictionary,
+        ),
+        (
+            "SRS",
+            config.srs_service.name(),
+            Some(config.srs_service.config_path()),
+            ServiceKind::Srs,
+        ),
+    ];
+
+    let mut report = format!(
+        "deckocr {}\nOS: {} ({})\nSession: {}\nConfig directory: {}\nData directory: {}\n",
+        version(),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        session_type(),
+        path_or_error(config::config_dir()),
+        path_or_error(data::data_dir()),
+    );
+
+    for (kind, name, path, service) in services {
+        let health = health::get(service);
+        report.push_str(&format!(
+            "{kind} service: {name} (config: {}, status: {:?})\n",
+            path.unwrap_or("none"),
+            health.status()
+        ));
+        if let Some((at, error)) = &health.last_error {
+            report.push_str(&format!(
+                "  last error ({}s ago): {error}\n",
+                at.elapsed().as_secs()
+            ));
+        }
+    }
+
+    report.push_str(&format!(
+        "Fullscreen: {}, zoom: {}, dry run: {}\n",
+        config.fullscreen, config.zoom_factor, config.dry_run
+    ));
+
+    report
+}
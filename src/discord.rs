@@ -0,0 +1,120 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    services::http,
+    stats::{self, Stats},
+    word::Word,
+};
+
+/// Maximum number of words listed in a daily summary.
+const SUMMARY_MAX_WORDS: usize = 20;
+
+/// Optional integration posting mining events to a Discord webhook. Off by default.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiscordConfig {
+    /// URL of the Discord webhook. Empty if the integration is disabled.
+    pub webhook_url: String,
+    /// Whether a message should be posted every time a word is mined.
+    pub post_mined_words: bool,
+    /// Whether a summary of the previous day's mining should be posted once a day.
+    pub post_daily_summary: bool,
+}
+
+impl DiscordConfig {
+    fn is_enabled(&self) -> bool {
+        !self.webhook_url.trim().is_empty()
+    }
+
+    /// Post a message about a word which was just mined, if enabled. Errors are logged.
+    pub fn word_mined(&self, word: &Word, sentence: &str) {
+        let Some(definition) = &word.definition else {
+            return;
+        };
+
+        if !self.is_enabled() || !self.post_mined_words {
+            return;
+        }
+
+        let content = format!(
+            "Mined **{}** ({}): {}\n> {}",
+            definition.spelling,
+            definition.reading,
+            definition.meanings.join("; "),
+            sentence
+        );
+
+        self.post(content);
+    }
+
+    /// Post a summary of the previous day's mining, if enabled and it wasn't posted yet today.
+    pub fn post_daily_summary(&self, stats: &mut Stats) {
+        if !self.is_enabled() || !self.post_daily_summary {
+            return;
+        }
+
+        let today = stats::today();
+        if stats.last_summary_day.is_some_and(|day| day >= today) {
+            return;
+        }
+        stats.last_summary_day = Some(today);
+
+        let yesterday = today - 1;
+        let words: Vec<_> = stats.mined_on(yesterday).collect();
+        if words.is_empty() {
+            return;
+        }
+
+        let mut content = format!(
+            "Mined {} word(s) on {}:",
+            words.len(),
+            stats::format_day(yesterday)
+        );
+        for word in words.iter().take(SUMMARY_MAX_WORDS) {
+            content.push_str(&format!("\n・{} ({})", word.spelling, word.reading));
+        }
+        if words.len() > SUMMARY_MAX_WORDS {
+            content.push_str(&format!("\n…and {} more", words.len() - SUMMARY_MAX_WORDS));
+        }
+
+        self.post(content);
+    }
+
+    /// Post a summary generated elsewhere (eg. the weekly summary), if enabled.
+    pub fn post_summary(&self, content: String) {
+        if self.is_enabled() {
+            self.post(content);
+        }
+    }
+
+    /// Post a message to the webhook in the background.
+    fn post(&self, content: String) {
+        let url = self.webhook_url.trim().to_owned();
+
+        std::thread::spawn(move || {
+            let body = json!({ "content": content, "username": env!("CARGO_PKG_NAME") });
+            if let Err(e) = http::post_webhook(&url, &body) {
+                log::warn!("Failed to post to Discord webhook: {e:#}");
+            }
+        });
+    }
+
+    /// Show the UI for configuring the integration.
+    pub fn show_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Webhook URL:");
+            ui.add(egui::TextEdit::singleline(&mut self.webhook_url).password(true))
+                .on_hover_text("Server Settings > Integrations > Webhooks > Copy Webhook URL.\nLeave empty to disable.");
+        });
+
+        ui.add_enabled_ui(self.is_enabled(), |ui| {
+            ui.checkbox(&mut self.post_mined_words, "Post Mined Words");
+            ui.checkbox(&mut self.post_daily_summary, "Post Daily Summary")
+                .on_hover_text(
+                    "Posted the first time deckocr is used each day, for the previous day (UTC).",
+                );
+        });
+    }
+}
@@ -0,0 +1,144 @@
+use std::{ops::RangeInclusive, sync::Arc};
+
+use ab_glyph::{Font, FontRef};
+use anyhow::{Context, Result};
+use eframe::{
+    egui,
+    epaint::text::{FontInsert, FontPriority, InsertFontFamily},
+};
+
+use crate::config::AppConfig;
+
+/// The font used for Japanese text.
+pub const MAIN_FONT: &[u8] = include_bytes!("../assets/fonts/MPLUS1-Regular.ttf");
+
+/// The 2965 level 1 kanji of JIS X 0208. This isn't the Jōyō list: a few Jōyō kanji (eg. 嗅, 遡, 璽)
+/// are level 2 kanji, and about a thousand level 1 kanji aren't Jōyō kanji.
+const JIS_LEVEL1_KANJI: &str = include_str!("../assets/charsets/jis_level1_kanji.txt");
+
+/// Symbols commonly found in Japanese games which aren't part of the ranges checked below.
+const COMMON_SYMBOLS: &str = "…‥※→←↑↓★☆♪♡♥○●◎■□△▲▽▼◆◇";
+
+/// Add deckocr's fonts to the given context: the main font, then the user's fallback font if one is set.
+pub fn install_fonts(ctx: &egui::Context, config: &AppConfig) -> Result<()> {
+    // FIXME: some characters aren't being rendered properly with this font. Users can set a fallback
+    // font in the meantime, and check which characters are affected from the configuration window.
+    ctx.add_font(FontInsert::new(
+        "M+",
+        egui::FontData::from_static(MAIN_FONT),
+        vec![InsertFontFamily {
+            family: egui::FontFamily::Proportional,
+            priority: FontPriority::Highest,
+        }],
+    ));
+
+    if let Some(data) = load_fallback_font(config)? {
+        ctx.add_font(FontInsert::new(
+            "Fallback",
+            egui::FontData::from_owned(data),
+            vec![InsertFontFamily {
+                family: egui::FontFamily::Proportional,
+                priority: FontPriority::Lowest,
+            }],
+        ));
+    }
+
+    Ok(())
+}
+
+/// Read the fallback font set by the user, if any.
+fn load_fallback_font(config: &AppConfig) -> Result<Option<Vec<u8>>> {
+    if config.fallback_font.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let data = std::fs::read(config.fallback_font.trim())
+        .with_context(|| format!("Could not read fallback font `{}`", config.fallback_font))?;
+
+    // make sure the font can actually be parsed, as egui panics on invalid fonts
+    FontRef::try_from_slice(&data)
+        .with_context(|| format!("Could not parse fallback font `{}`", config.fallback_font))?;
+
+    Ok(Some(data))
+}
+
+/// How well a set of characters is covered by the configured fonts.
+pub struct Coverage {
+    /// Name of the set of characters.
+    pub name: &'static str,
+    /// Number of characters in the set.
+    pub total: usize,
+    /// Characters which no font has a glyph for, and which would be rendered as tofu.
+    pub missing: Vec<char>,
+}
+
+impl Coverage {
+    /// The missing characters, grouped into ranges of consecutive code points.
+    pub fn missing_ranges(&self) -> Vec<RangeInclusive<char>> {
+        let mut ranges: Vec<RangeInclusive<char>> = Vec::new();
+
+        for &c in &self.missing {
+            match ranges.last_mut() {
+                Some(range) if *range.end() as u32 + 1 == c as u32 => {
+                    *range = *range.start()..=c;
+                }
+                _ => ranges.push(c..=c),
+            }
+        }
+
+        ranges
+    }
+}
+
+/// Check which commonly used characters (kana, JIS level 1 kanji and symbols) can't be rendered
+/// with the fonts configured in `config`, including egui's default fonts.
+pub fn check_coverage(config: &AppConfig) -> Result<Vec<Coverage>> {
+    let mut font_data: Vec<Arc<egui::FontData>> = egui::FontDefinitions::default()
+        .font_data
+        .into_values()
+        .collect();
+    font_data.push(Arc::new(egui::FontData::from_static(MAIN_FONT)));
+    if let Some(data) = load_fallback_font(config)? {
+        font_data.push(Arc::new(egui::FontData::from_owned(data)));
+    }
+
+    let fonts: Vec<FontRef> = font_data
+        .iter()
+        .filter_map(|data| FontRef::try_from_slice_and_index(&data.font, data.index).ok())
+        .collect();
+
+    let ranges = |ranges: &[RangeInclusive<char>]| -> Vec<char> {
+        ranges.iter().cloned().flatten().collect()
+    };
+
+    let sets = [
+        (
+            "Hiragana",
+            ranges(&['\u{3041}'..='\u{3096}', '\u{309B}'..='\u{309E}']),
+        ),
+        (
+            "Katakana",
+            ranges(&['\u{30A1}'..='\u{30FA}', '\u{30FC}'..='\u{30FE}']),
+        ),
+        ("Half-width Katakana", ranges(&['\u{FF66}'..='\u{FF9F}'])),
+        (
+            "Kanji (JIS Level 1)",
+            JIS_LEVEL1_KANJI.trim().chars().collect(),
+        ),
+        ("CJK Punctuation", ranges(&['\u{3000}'..='\u{303F}'])),
+        ("Full-width Forms", ranges(&['\u{FF01}'..='\u{FF5E}'])),
+        ("Common Symbols", COMMON_SYMBOLS.chars().collect()),
+    ];
+
+    Ok(sets
+        .into_iter()
+        .map(|(name, chars)| Coverage {
+            name,
+            total: chars.len(),
+            missing: chars
+                .into_iter()
+                .filter(|&c| fonts.iter().all(|font| font.glyph_id(c).0 == 0))
+                .collect(),
+        })
+        .collect())
+}
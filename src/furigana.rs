@@ -0,0 +1,176 @@
+//! Furigana generated from a word's dictionary reading, for dictionaries which don't return any
+//! (eg. local dictionaries without alignment data), by aligning the reading with the kana written
+//! between the word's kanji.
+
+use crate::word::{TextFragment, TextWithRuby, Word};
+
+/// Whether the character is written with furigana above it.
+fn is_kanji(c: char) -> bool {
+    matches!(c, '\u{4e00}'..='\u{9fff}' | '\u{3400}'..='\u{4dbf}' | '々' | '〆' | 'ヶ')
+}
+
+/// The character, with katakana converted to hiragana so that readings can be compared.
+fn to_hiragana(c: char) -> char {
+    match c {
+        'ァ'..='ヶ' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// Split text into runs of kanji and runs of other characters, along with whether they are kanji.
+fn runs(text: &str) -> Vec<(bool, String)> {
+    let mut runs: Vec<(bool, String)> = Vec::new();
+    for c in text.chars() {
+        match runs.last_mut() {
+            Some((kanji, run)) if *kanji == is_kanji(c) => run.push(c),
+            _ => runs.push((is_kanji(c), c.to_string())),
+        }
+    }
+    runs
+}
+
+/// Find the reading of each kanji run of `runs` in `reading`, by matching the other runs with it.
+fn align(runs: &[(bool, String)], reading: &[char]) -> Option<Vec<String>> {
+    let Some(((kanji, run), rest)) = runs.split_first() else {
+        return reading.is_empty().then(Vec::new);
+    };
+
+    if !kanji {
+        let len = run.chars().count();
+        let matches = reading.len() >= len
+            && run
+                .chars()
+                .zip(reading)
+                .all(|(a, b)| to_hiragana(a) == to_hiragana(*b));
+        return match matches {
+            true => align(rest, &reading[len..]),
+            false => None,
+        };
+    }
+
+    // try the shortest readings first
+    (1..=reading.len()).find_map(|len| {
+        let mut readings = align(rest, &reading[len..])?;
+        readings.insert(0, reading[..len].iter().collect());
+        Some(readings)
+    })
+}
+
+/// Furigana for `text`, an occurrence of the word `spelling` read as `reading`. The text can be
+/// conjugated (eg. 食べた for 食べる), as long as it contains the same kanji.
+pub fn generate(text: &str, spelling: &str, reading: &str) -> Option<TextWithRuby> {
+    let reading: Vec<char> = reading.chars().collect();
+    let spelling_runs = runs(spelling);
+    let readings = align(&spelling_runs, &reading)?;
+
+    let text_runs = runs(text);
+    let text_kanji = text_runs.iter().filter(|(kanji, _)| *kanji);
+    let spelling_kanji = spelling_runs.iter().filter(|(kanji, _)| *kanji);
+    if !text_kanji
+        .map(|(_, run)| run)
+        .eq(spelling_kanji.map(|(_, run)| run))
+    {
+        return None;
+    }
+
+    let mut readings = readings.into_iter();
+    Some(
+        text_runs
+            .into_iter()
+            .map(|(kanji, run)| TextFragment {
+                text: run,
+                ruby: match kanji {
+                    true => readings.next(),
+                    false => None,
+                },
+            })
+            .collect(),
+    )
+}
+
+/// Generate furigana for the words containing kanji which have a definition, but no furigana.
+pub fn fill_missing(words: &mut [Vec<Word>]) {
+    for word in words.iter_mut().flatten() {
+        let Some(definition) = &word.definition else {
+            continue;
+        };
+        if word.text.0.iter().any(|fragment| fragment.ruby.is_some()) {
+            continue;
+        }
+
+        let text = word.text.plain_text();
+        if !text.chars().any(is_kanji) {
+            continue;
+        }
+
+        if let Some(furigana) = generate(&text, &definition.spelling, &definition.reading) {
+            word.text = furigana;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aligned(spelling: &str, reading: &str) -> Option<Vec<String>> {
+        let reading: Vec<char> = reading.chars().collect();
+        align(&runs(spelling), &reading)
+    }
+
+    fn furigana(
+        text: &str,
+        spelling: &str,
+        reading: &str,
+    ) -> Option<Vec<(String, Option<String>)>> {
+        generate(text, spelling, reading).map(|text| {
+            text.0
+                .into_iter()
+                .map(|fragment| (fragment.text, fragment.ruby))
+                .collect()
+        })
+    }
+
+    #[test]
+    fn kanji_only() {
+        assert_eq!(aligned("日本", "にほん"), Some(vec!["にほん".to_owned()]));
+    }
+
+    #[test]
+    fn kana_between_kanji() {
+        assert_eq!(
+            aligned("取り消す", "とりけす"),
+            Some(vec!["と".to_owned(), "け".to_owned()])
+        );
+    }
+
+    #[test]
+    fn katakana_matches_hiragana() {
+        assert_eq!(
+            aligned("カタ仮名", "かたかな"),
+            Some(vec!["かな".to_owned()])
+        );
+    }
+
+    #[test]
+    fn mismatched_reading() {
+        assert_eq!(aligned("食べる", "たべた"), None);
+        assert_eq!(aligned("食べる", "たべるよ"), None);
+    }
+
+    #[test]
+    fn conjugated_text() {
+        assert_eq!(
+            furigana("食べた", "食べる", "たべる"),
+            Some(vec![
+                ("食".to_owned(), Some("た".to_owned())),
+                ("べた".to_owned(), None),
+            ])
+        );
+    }
+
+    #[test]
+    fn different_kanji() {
+        assert_eq!(furigana("喰べた", "食べる", "たべる"), None);
+    }
+}
@@ -1,3 +1,10 @@
+pub mod about;
 pub mod config_window;
+pub mod countdown;
+pub mod hotkey_conflict;
+pub mod lookup;
 pub mod ocr_window;
+pub mod pin_board;
 pub mod popups;
+pub mod region_select;
+pub mod statistics;
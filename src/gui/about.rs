@@ -0,0 +1,82 @@
+use eframe::egui;
+
+use crate::{config, data, diagnostics, EframeApp};
+
+/// Shows deckocr's version, where its files are stored and the environment it runs in.
+pub fn about_ui(app: &mut EframeApp, ui: &mut egui::Ui) {
+    egui::Grid::new("about").num_columns(2).show(ui, |ui| {
+        ui.label("Version:");
+        ui.label(diagnostics::version());
+        ui.end_row();
+
+        ui.label("OS:");
+        ui.label(format!(
+            "{} ({}), {} session",
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            diagnostics::session_type()
+        ));
+        ui.end_row();
+
+        for (label, dir) in [
+            ("Configuration:", config::config_dir()),
+            ("Data:", data::data_dir()),
+        ] {
+            ui.label(label);
+            match dir {
+                Ok(dir) => {
+                    ui.horizontal(|ui| {
+                        ui.label(dir.display().to_string());
+                        if ui.small_button("Open").clicked() {
+                            if let Err(e) = diagnostics::open_folder(&dir) {
+                                app.popups.error(e);
+                            }
+                        }
+                    });
+                }
+                Err(e) => {
+                    ui.label(format!("{e}"));
+                }
+            }
+            ui.end_row();
+        }
+
+        for (label, name, path) in [
+            (
+                "Capture Service:",
+                app.config.capture_service.name(),
+                app.config.capture_service.config_path(),
+            ),
+            (
+                "OCR Service:",
+                app.config.ocr_service.name(),
+                Some(app.config.ocr_service.config_path()),
+            ),
+            (
+                "Dictionary Service:",
+                app.config.dictionary_service.name(),
+                Some(app.config.dictionary_service.config_path()),
+            ),
+            (
+                "SRS Service:",
+                app.config.srs_service.name(),
+                Some(app.config.srs_service.config_path()),
+            ),
+        ] {
+            ui.label(label);
+            match path {
+                Some(path) => ui.label(format!("{name} ({path})")),
+                None => ui.label(name),
+            };
+            ui.end_row();
+        }
+    });
+
+    if ui
+        .button("Copy Diagnostic Report")
+        .on_hover_text("Copy information about your setup to the clipboard, to be included\nin bug reports. API keys are not included.")
+        .clicked()
+    {
+        ui.ctx().copy_text(diagnostics::report(&app.config));
+    }
+}
@@ -1,6 +1,18 @@
 use eframe::egui;
 
-use crate::{config::Config, services::Services, EframeApp};
+use crate::{
+    config::{Config, DictionaryServiceList, SrsServiceList},
+    fonts,
+    gui::{about::about_ui, lookup::lookup_ui, pin_board::pin_board_ui, statistics::statistics_ui},
+    safe_mode::SafeMode,
+    services::{
+        health::{self, HealthStatus, ServiceKind},
+        http, jpdb, Services,
+    },
+    EframeApp,
+};
+
+const CAPTURE_NOW_HOVER_TEXT: &str = "Capture the primary monitor without using the hotkey.";
 
 /// The main configuration window, shown when deckocr is first started.
 pub fn show_config_window(app: &mut EframeApp, ctx: &egui::Context) {
@@ -20,58 +32,327 @@ pub fn show_config_window(app: &mut EframeApp, ctx: &egui::Context) {
                                 .strong(),
                         );
 
+                        if let Some(safe_mode) = app.safe_mode {
+                            safe_mode_ui(app, ui, safe_mode);
+                            ui.separator();
+                        }
+
                         app.config.show_ui(ui);
 
+                        ui.collapsing("Font Coverage", |ui| font_coverage_ui(app, ui));
+
+                        if !app.stitch_buffer.is_empty() {
+                            stitch_buffer_ui(app, ui);
+                        }
+
+                        if let Some(companion) = &app.companion {
+                            let url = companion.url();
+                            ui.horizontal(|ui| {
+                                ui.label("Companion page:");
+                                ui.hyperlink(url);
+                            });
+                        }
+
                         ui.separator();
 
+                        service_header(
+                            ui,
+                            format!("Capture: {}", app.config.capture_service.name()),
+                            ServiceKind::Capture,
+                            |ui| app.services.capture.show_config_ui(ui),
+                        );
+
+                        ui.separator();
+
+                        service_header(
+                            ui,
+                            format!("OCR: {}", app.config.ocr_service.name()),
+                            ServiceKind::Ocr,
+                            |ui| app.services.ocr.show_config_ui(ui),
+                        );
+
+                        ui.separator();
+
+                        service_header(
+                            ui,
+                            format!("Dictionary: {}", app.config.dictionary_service.name()),
+                            ServiceKind::Dictionary,
+                            |ui| {
+                                app.services.dictionary.show_config_ui(ui);
+                                ui.collapsing("Monolingual Dictionary", |ui| {
+                                    app.services.monolingual.show_config_ui(ui)
+                                });
+                            },
+                        );
+
+                        ui.separator();
+
+                        service_header(
+                            ui,
+                            format!("SRS: {}", app.config.srs_service.name()),
+                            ServiceKind::Srs,
+                            |ui| app.services.srs.show_config_ui(ui),
+                        );
+
+                        ui.separator();
+
+                        if matches!(app.config.dictionary_service, DictionaryServiceList::Jpdb)
+                            || matches!(app.config.srs_service, SrsServiceList::Jpdb)
+                        {
+                            egui::CollapsingHeader::new(
+                                egui::RichText::new("jpdb Account").size(header_size),
+                            )
+                            .id_salt("jpdb account")
+                            .default_open(jpdb::api_key().is_err())
+                            .show_unindented(ui, jpdb::accounts_ui);
+
+                            ui.separator();
+                        }
+
                         egui::CollapsingHeader::new(
-                            egui::RichText::new(format!("OCR: {}", app.config.ocr_service.name()))
-                                .size(header_size),
+                            egui::RichText::new("Lookup").size(header_size),
                         )
-                        .default_open(true)
-                        .show_unindented(ui, |ui| {
-                            app.services.ocr.show_config_ui(ui);
-                        });
+                        .id_salt("lookup")
+                        .show_unindented(ui, |ui| lookup_ui(app, ui));
 
                         ui.separator();
 
                         egui::CollapsingHeader::new(
                             egui::RichText::new(format!(
-                                "Dictionary: {}",
-                                app.config.dictionary_service.name()
+                                "Pin Board ({})",
+                                app.pin_board.pins.len()
                             ))
                             .size(header_size),
                         )
-                        .default_open(true)
-                        .show_unindented(ui, |ui| {
-                            app.services.dictionary.show_config_ui(ui);
-                        });
+                        .id_salt("pin board")
+                        .default_open(!app.pin_board.pins.is_empty())
+                        .show_unindented(ui, |ui| pin_board_ui(app, ui));
 
                         ui.separator();
 
                         egui::CollapsingHeader::new(
-                            egui::RichText::new(format!("SRS: {}", app.config.srs_service.name()))
-                                .size(header_size),
+                            egui::RichText::new("Statistics").size(header_size),
                         )
-                        .default_open(true)
-                        .show_unindented(ui, |ui| {
-                            app.services.srs.show_config_ui(ui);
-                        });
+                        .id_salt("statistics")
+                        .show_unindented(ui, |ui| statistics_ui(app, ui));
+
+                        ui.separator();
+
+                        egui::CollapsingHeader::new(egui::RichText::new("About").size(header_size))
+                            .id_salt("about")
+                            .show_unindented(ui, |ui| about_ui(app, ui));
+
+                        if app.config.dry_run {
+                            ui.separator();
+
+                            egui::CollapsingHeader::new(
+                                egui::RichText::new("Dry Run Log").size(header_size),
+                            )
+                            .default_open(true)
+                            .show_unindented(ui, dry_run_log_ui);
+                        }
                     });
                 });
 
                 strip.empty();
 
-                strip.cell(|ui| {
-                    ui.centered_and_justified(|ui| {
-                        if ui.button("Reload Services").clicked() {
-                            match Services::new(&app.config) {
-                                Ok(services) => app.services = services,
-                                Err(e) => app.popups.error(e),
-                            }
-                        }
-                    });
+                strip.strip(|builder| {
+                    builder
+                        .sizes(egui_extras::Size::remainder(), 2)
+                        .horizontal(|mut strip| {
+                            strip.cell(|ui| {
+                                ui.centered_and_justified(|ui| {
+                                    if ui
+                                        .button("Capture Now")
+                                        .on_hover_text(CAPTURE_NOW_HOVER_TEXT)
+                                        .clicked()
+                                    {
+                                        if let Err(e) = app.capture_now() {
+                                            app.popups.error(e);
+                                        }
+                                    }
+                                });
+                            });
+
+                            strip.cell(|ui| {
+                                ui.centered_and_justified(|ui| {
+                                    if ui.button("Reload Services").clicked() {
+                                        match Services::new(&app.config) {
+                                            Ok(services) => {
+                                                let mut old_services =
+                                                    std::mem::replace(&mut app.services, services);
+                                                for e in old_services.terminate() {
+                                                    app.popups.error(e);
+                                                }
+                                                if app.config.warm_up_services {
+                                                    app.warm_up_jobs = app.services.warm_up();
+                                                }
+                                            }
+                                            Err(e) => app.popups.error(e),
+                                        }
+                                    }
+                                });
+                            });
+                        });
                 });
             });
     });
 }
+
+/// Shows how many pages were captured with the stitch hotkey, and lets the user discard them.
+fn stitch_buffer_ui(app: &mut EframeApp, ui: &mut egui::Ui) {
+    ui.horizontal(|ui| {
+        ui.label(format!(
+            "{} page(s) captured, waiting to be stitched with the next OCR capture.",
+            app.stitch_buffer.len()
+        ));
+        if ui.button("Clear").clicked() {
+            app.stitch_buffer = Default::default();
+        }
+    });
+}
+
+/// Explains what safe mode changes, and lets the user save the configuration they fixed.
+fn safe_mode_ui(app: &mut EframeApp, ui: &mut egui::Ui, safe_mode: SafeMode) {
+    ui.colored_label(ui.visuals().warn_fg_color, safe_mode.message());
+    ui.label("The default configuration is shown, services are disabled and hotkeys aren't registered. Nothing is saved on exit: press Save Configuration to replace your configuration with the one below, then restart deckocr.");
+
+    if ui.button("Save Configuration").clicked() {
+        match app.config.save() {
+            Ok(()) => log::info!("Saved configuration from safe mode"),
+            Err(e) => app.popups.error(e.context("Failed to save configuration")),
+        }
+    }
+}
+
+/// Shows a service's configuration under a header coloured according to the service's health.
+fn service_header(
+    ui: &mut egui::Ui,
+    title: String,
+    service: ServiceKind,
+    add_contents: impl FnOnce(&mut egui::Ui),
+) {
+    let header_size = 24.0;
+    let health = health::get(service);
+    let status = health.status();
+
+    let mut text = egui::RichText::new(&title).size(header_size);
+    if let Some([r, g, b]) = status.colour() {
+        text = text.color(egui::Color32::from_rgb(r, g, b));
+    }
+
+    let response = egui::CollapsingHeader::new(text)
+        .id_salt(("service header", title))
+        .default_open(true)
+        .show_unindented(ui, add_contents);
+
+    let status_text = match status {
+        HealthStatus::Unknown => "Not used yet.",
+        HealthStatus::Healthy => "Working.",
+        HealthStatus::Degraded => "Working, but failed recently.",
+        HealthStatus::Failing => "The last request failed.",
+    };
+
+    response.header_response.on_hover_ui(|ui| {
+        ui.label(status_text);
+        if let Some((at, error)) = &health.last_error {
+            ui.label(format!(
+                "Last error ({}s ago): {error}",
+                at.elapsed().as_secs()
+            ));
+        }
+    });
+}
+
+/// Shows which common characters can't be rendered with the configured fonts.
+fn font_coverage_ui(app: &mut EframeApp, ui: &mut egui::Ui) {
+    ui.label(
+        "Check whether the configured fonts can render kana, JIS level 1 kanji and common symbols.",
+    );
+
+    if ui.button("Check Fonts").clicked() {
+        match fonts::check_coverage(&app.config) {
+            Ok(coverage) => app.font_coverage = Some(coverage),
+            Err(e) => app.popups.error(e),
+        }
+    }
+
+    let Some(coverage) = &app.font_coverage else {
+        return;
+    };
+
+    for set in coverage {
+        let covered = set.total - set.missing.len();
+
+        if set.missing.is_empty() {
+            ui.label(format!("✔ {}: {covered}/{}", set.name, set.total));
+            continue;
+        }
+
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            format!("⚠ {}: {covered}/{}", set.name, set.total),
+        );
+
+        let ranges = set
+            .missing_ranges()
+            .into_iter()
+            .map(|range| match range.start() == range.end() {
+                true => format!("{} (U+{:04X})", range.start(), *range.start() as u32),
+                false => format!(
+                    "{}–{} (U+{:04X}–U+{:04X})",
+                    range.start(),
+                    range.end(),
+                    *range.start() as u32,
+                    *range.end() as u32
+                ),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        ui.label(egui::RichText::new(format!("Missing: {ranges}")).weak());
+    }
+
+    if coverage.iter().any(|set| !set.missing.is_empty()) {
+        ui.label("Missing characters will be rendered as boxes (tofu). Setting a fallback font which covers them (eg. Noto Sans CJK JP) will fix this.");
+    }
+}
+
+/// Shows the requests recorded in dry-run mode, most recent first.
+fn dry_run_log_ui(ui: &mut egui::Ui) {
+    ui.label("Read-only requests (eg. parsing text, loading card states) are still sent. Requests which would modify anything (eg. adding words to a deck) are not, and words aren't counted as mined.");
+
+    let log = http::dry_run_log();
+
+    if log.is_empty() {
+        ui.label("No requests recorded yet.");
+        return;
+    }
+
+    if ui.button("Clear").clicked() {
+        http::clear_dry_run_log();
+    }
+
+    for (idx, request) in log.iter().enumerate().rev() {
+        let age = request.timestamp.elapsed().unwrap_or_default().as_secs();
+        let status = match request.sent {
+            true => "sent",
+            false => "NOT SENT",
+        };
+
+        let text = format!(
+            "POST {}\nAuthorization: Bearer <redacted>\n\n{}",
+            request.url,
+            serde_json::to_string_pretty(&request.body).unwrap_or_default()
+        );
+
+        egui::CollapsingHeader::new(format!("{} ({status}, {age}s ago)", request.url))
+            .id_salt(("dry run request", idx))
+            .show(ui, |ui| {
+                if ui.button("Copy").clicked() {
+                    ui.ctx().copy_text(text.clone());
+                }
+                ui.label(egui::RichText::new(text).monospace());
+            });
+    }
+}
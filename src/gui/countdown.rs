@@ -0,0 +1,133 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use eframe::egui::{self, Color32};
+use image::RgbaImage;
+
+use crate::{
+    capture::{self, CaptureTarget},
+    services::{ocr::ParagraphOrder, ServiceJob, Services},
+};
+
+/// The countdown is hidden this long before the capture, so it doesn't end up in the screenshot.
+const HIDE_BEFORE_CAPTURE: Duration = Duration::from_millis(200);
+/// How long to wait for the text to settle before capturing anyway, eg. if the background is animated.
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A capture which was requested with a hotkey, and will be taken once the OCR service is ready,
+/// its countdown ran out and, if enabled, the text on screen stopped changing.
+pub struct PendingCapture {
+    pub target: CaptureTarget,
+    pub paragraph_order: ParagraphOrder,
+    /// Checks that the OCR service can be reached, until it finished.
+    readiness: Option<ServiceJob<Result<()>>>,
+    /// When the countdown runs out.
+    countdown_until: Instant,
+    /// When the next frame should be taken.
+    next_frame_at: Instant,
+    /// Interval between frames compared to each other, if waiting for the text to settle.
+    settle_interval: Option<Duration>,
+    /// The last frame taken with the time it took, and the time after which it is used even if
+    /// the text didn't settle.
+    last_frame: Option<(RgbaImage, Duration, Instant)>,
+}
+
+impl PendingCapture {
+    pub fn new(
+        target: CaptureTarget,
+        paragraph_order: ParagraphOrder,
+        readiness: ServiceJob<Result<()>>,
+        delay_ms: u64,
+        settle_interval: Option<Duration>,
+    ) -> Self {
+        let countdown_until = Instant::now() + Duration::from_millis(delay_ms);
+
+        Self {
+            target,
+            paragraph_order,
+            readiness: Some(readiness),
+            countdown_until,
+            next_frame_at: countdown_until,
+            settle_interval,
+            last_frame: None,
+        }
+    }
+
+    /// Take a frame if it is time to. Returns the screenshot to be used once it is ready, along
+    /// with the time it took.
+    pub fn poll(&mut self, services: &mut Services) -> Result<Option<(RgbaImage, Duration)>> {
+        if let Some(readiness) = &mut self.readiness {
+            match readiness.try_wait()? {
+                None => return Ok(None),
+                Some(result) => {
+                    result?;
+                    self.readiness = None;
+                }
+            }
+        }
+
+        let now = Instant::now();
+        if now < self.next_frame_at {
+            return Ok(None);
+        }
+
+        let (frame, capture_time) = services.take_screenshot(&self.target)?;
+
+        let Some(interval) = self.settle_interval else {
+            return Ok(Some((frame, capture_time)));
+        };
+
+        match self.last_frame.take() {
+            Some((previous, _, give_up_at)) => {
+                if capture::frames_match(&previous, &frame) || now >= give_up_at {
+                    return Ok(Some((frame, capture_time)));
+                }
+                self.last_frame = Some((frame, capture_time, give_up_at));
+            }
+            None => self.last_frame = Some((frame, capture_time, now + SETTLE_TIMEOUT)),
+        }
+
+        self.next_frame_at = now + interval;
+        Ok(None)
+    }
+
+    /// Show the time remaining in the countdown in a small window on top of everything else.
+    pub fn show_countdown(&self, ctx: &egui::Context) {
+        let remaining = self
+            .countdown_until
+            .saturating_duration_since(Instant::now());
+        ctx.request_repaint_after(Duration::from_millis(50));
+
+        if remaining <= HIDE_BEFORE_CAPTURE {
+            return;
+        }
+
+        ctx.show_viewport_immediate(
+            egui::ViewportId(egui::Id::new("countdown_viewport")),
+            egui::ViewportBuilder {
+                title: Some("Capture Countdown".to_owned()),
+                inner_size: Some(egui::vec2(120.0, 80.0)),
+                decorations: Some(false),
+                resizable: Some(false),
+                transparent: Some(true),
+                window_level: Some(egui::WindowLevel::AlwaysOnTop),
+                mouse_passthrough: Some(true),
+                active: Some(false),
+                ..Default::default()
+            },
+            |ctx, _| {
+                egui::CentralPanel::default()
+                    .frame(egui::Frame::new().fill(Color32::from_black_alpha(192)))
+                    .show(ctx, |ui| {
+                        ui.centered_and_justified(|ui| {
+                            ui.label(
+                                egui::RichText::new(format!("{:.1}", remaining.as_secs_f32()))
+                                    .size(40.0)
+                                    .color(Color32::WHITE),
+                            );
+                        });
+                    });
+            },
+        );
+    }
+}
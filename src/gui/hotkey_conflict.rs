@@ -0,0 +1,149 @@
+use eframe::egui::{self, vec2};
+use global_hotkey::{
+    hotkey::{Code, HotKey, Modifiers},
+    GlobalHotKeyManager,
+};
+
+use crate::HotkeyAction;
+
+/// Maximum number of alternative hotkeys suggested to the user.
+const MAX_SUGGESTIONS: usize = 4;
+
+/// A hotkey which could not be registered, most likely because another application (or another
+/// instance of deckocr) already registered it.
+pub struct HotkeyConflict {
+    pub hotkey: HotKey,
+    pub action: HotkeyAction,
+    /// Hotkeys which were successfully registered and unregistered, and are therefore likely to be free.
+    suggestions: Vec<HotKey>,
+    error: String,
+    first_frame: bool,
+}
+
+/// What the user chose to do about a `HotkeyConflict`.
+pub enum Resolution {
+    /// Use another hotkey instead.
+    UseHotkey(HotKey),
+    /// Keep going without this hotkey.
+    Ignore,
+}
+
+impl HotkeyConflict {
+    /// Create a new `HotkeyConflict`, looking for free hotkeys to suggest instead. Hotkeys in
+    /// `taken` are never suggested.
+    pub fn new(
+        hotkey: HotKey,
+        action: HotkeyAction,
+        error: &anyhow::Error,
+        manager: &GlobalHotKeyManager,
+        taken: &[HotKey],
+    ) -> Self {
+        let modifiers = [
+            hotkey.mods,
+            Modifiers::ALT,
+            Modifiers::CONTROL | Modifiers::ALT,
+            Modifiers::ALT | Modifiers::SHIFT,
+            Modifiers::CONTROL | Modifiers::SHIFT,
+        ];
+        let keys = [
+            hotkey.key,
+            Code::F12,
+            Code::F11,
+            Code::F10,
+            Code::F9,
+            Code::F8,
+            Code::F7,
+        ];
+
+        let mut suggestions: Vec<HotKey> = Vec::new();
+
+        'outer: for mods in modifiers {
+            for key in keys {
+                let candidate = HotKey::new(Some(mods), key);
+
+                if candidate == hotkey
+                    || taken.contains(&candidate)
+                    || suggestions.contains(&candidate)
+                {
+                    continue;
+                }
+
+                if manager.register(candidate).is_ok() {
+                    let _ = manager.unregister(candidate);
+                    suggestions.push(candidate);
+                }
+
+                if suggestions.len() >= MAX_SUGGESTIONS {
+                    break 'outer;
+                }
+            }
+        }
+
+        Self {
+            hotkey,
+            action,
+            suggestions,
+            error: format!("{error:#}"),
+            first_frame: true,
+        }
+    }
+
+    /// Show the conflict dialog. Returns `Some` once the user has made a choice.
+    pub fn show(&mut self, ctx: &egui::Context, action_name: &str) -> Option<Resolution> {
+        let mut resolution = None;
+
+        ctx.show_viewport_immediate(
+            egui::ViewportId(egui::Id::new(("hotkey conflict", self.hotkey.id))),
+            egui::ViewportBuilder {
+                title: Some("Hotkey Conflict".to_owned()),
+                inner_size: Some(vec2(480.0, 320.0)),
+                ..Default::default()
+            },
+            |ctx, _| {
+                if self.first_frame {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+                    self.first_frame = false;
+                }
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    ui.label(format!(
+                        "The hotkey {} ({action_name}) could not be registered. Another application, or another instance of deckocr, is probably using it already.",
+                        self.hotkey.to_string().to_uppercase()
+                    ));
+                    ui.label(egui::RichText::new(&self.error).weak());
+
+                    ui.add_space(8.0);
+
+                    if self.suggestions.is_empty() {
+                        ui.label("No free alternative hotkey could be found.");
+                    } else {
+                        ui.label("Use one of these hotkeys instead:");
+                        for suggestion in &self.suggestions {
+                            if ui.button(suggestion.to_string().to_uppercase()).clicked() {
+                                resolution = Some(Resolution::UseHotkey(*suggestion));
+                            }
+                        }
+                    }
+
+                    ui.add_space(8.0);
+
+                    if ui.button("Continue without this hotkey").clicked() {
+                        resolution = Some(Resolution::Ignore);
+                    }
+                    ui.label(
+                        egui::RichText::new(
+                            "You can still capture manually with the \"Capture Now\" button in the main window.",
+                        )
+                        .weak(),
+                    );
+                });
+
+                if ctx.input(|input| input.viewport().close_requested()) {
+                    resolution = Some(Resolution::Ignore);
+                }
+            },
+        );
+
+        resolution
+    }
+}
@@ -0,0 +1,192 @@
+use anyhow::{Context, Result};
+use eframe::egui::{self, Color32};
+
+use crate::{
+    services::{dictionary::DictionaryServiceJob, ServiceJob, Services},
+    word::Word,
+    EframeApp,
+};
+
+/// Text typed in the main window's lookup panel, and the words it was parsed into.
+#[derive(Default)]
+pub struct Lookup {
+    pub input: String,
+    state: LookupState,
+}
+
+#[derive(Default)]
+enum LookupState {
+    #[default]
+    Empty,
+    LoadingDictionary(DictionaryServiceJob),
+    LoadingSrs {
+        words: Vec<Vec<Word>>,
+        job: ServiceJob<Result<()>>,
+    },
+    Ready {
+        words: Vec<Vec<Word>>,
+        selected: Option<(usize, usize)>,
+    },
+}
+
+impl Lookup {
+    fn is_loading(&self) -> bool {
+        matches!(
+            self.state,
+            LookupState::LoadingDictionary(_) | LookupState::LoadingSrs { .. }
+        )
+    }
+
+    /// Start looking up the input with the dictionary service.
+    fn start(&mut self, services: &mut Services) {
+        let paragraphs = self
+            .input
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_owned)
+            .collect();
+
+        self.state = LookupState::LoadingDictionary(services.dictionary.parse(paragraphs));
+    }
+
+    /// Move on to the next state if the current job is finished.
+    fn poll(&mut self, services: &mut Services) -> Result<()> {
+        match &mut self.state {
+            LookupState::LoadingDictionary(job) => {
+                if let Some(words) = job
+                    .try_wait()?
+                    .transpose()
+                    .context("Dictionary ServiceJob returned an error")?
+                {
+                    let job = services
+                        .srs
+                        .load_card_states(words.iter().flatten().cloned().collect());
+                    self.state = LookupState::LoadingSrs { words, job };
+                }
+            }
+            LookupState::LoadingSrs { words, job } => {
+                if job
+                    .try_wait()?
+                    .transpose()
+                    .context("SRS ServiceJob returned an error")?
+                    .is_some()
+                {
+                    self.state = LookupState::Ready {
+                        words: std::mem::take(words),
+                        selected: None,
+                    };
+                }
+            }
+            LookupState::Empty | LookupState::Ready { .. } => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// Shows a text box whose contents can be looked up with the configured dictionary and SRS
+/// services, without capturing the screen.
+pub fn lookup_ui(app: &mut EframeApp, ui: &mut egui::Ui) {
+    let lookup = &mut app.lookup;
+
+    if let Err(e) = lookup.poll(&mut app.services) {
+        lookup.state = LookupState::Empty;
+        app.popups.error(e);
+    }
+
+    ui.add(
+        egui::TextEdit::multiline(&mut lookup.input)
+            .hint_text("Type or paste Japanese text here.")
+            .desired_rows(3)
+            .desired_width(f32::INFINITY),
+    );
+
+    ui.horizontal(|ui| {
+        let can_look_up = !lookup.is_loading() && !lookup.input.trim().is_empty();
+        if ui
+            .add_enabled(can_look_up, egui::Button::new("Look Up"))
+            .clicked()
+        {
+            lookup.start(&mut app.services);
+        }
+
+        if lookup.is_loading() {
+            ui.spinner();
+            ui.ctx().request_repaint();
+        }
+    });
+
+    let LookupState::Ready { words, selected } = &mut lookup.state else {
+        return;
+    };
+
+    ui.separator();
+
+    for (i, paragraph) in words.iter().enumerate() {
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing.x = 0.0;
+
+            for (j, word) in paragraph.iter().enumerate() {
+                let mut text = egui::RichText::new(word.text.plain_text()).size(20.0);
+                if word.definition.is_none() {
+                    ui.label(text);
+                    continue;
+                }
+
+                let [r, g, b] = app.services.srs.card_state(word).colour;
+                text = text.color(Color32::from_rgb(r, g, b));
+                if *selected == Some((i, j)) {
+                    text = text.underline();
+                }
+
+                if ui
+                    .add(egui::Label::new(text).sense(egui::Sense::click()))
+                    .clicked()
+                {
+                    *selected = Some((i, j));
+                }
+            }
+        });
+    }
+
+    let Some((i, j)) = *selected else {
+        ui.label(egui::RichText::new("Click a word to show its definition.").weak());
+        return;
+    };
+
+    let word = &words[i][j];
+    let Some(definition) = &word.definition else {
+        return;
+    };
+
+    ui.separator();
+
+    let card_state = app.services.srs.card_state(word);
+    let [r, g, b] = card_state.colour;
+
+    ui.horizontal(|ui| {
+        ui.label(
+            egui::RichText::new(format!("{}【{}】", definition.spelling, definition.reading))
+                .size(24.0),
+        );
+        ui.colored_label(Color32::from_rgb(r, g, b), &card_state.name);
+
+        let sentence: String = words[i].iter().map(|word| word.text.plain_text()).collect();
+        let label = match app.pin_board.is_pinned(definition) {
+            true => "Unpin",
+            false => "Pin",
+        };
+        if ui
+            .button(label)
+            .on_hover_text("Pinned words can be added to your deck from the pin board.")
+            .clicked()
+        {
+            app.pin_board.toggle(word, sentence);
+        }
+    });
+
+    for meaning in &definition.meanings {
+        ui.label(format!("・{meaning}"));
+    }
+}
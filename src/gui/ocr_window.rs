@@ -1,18 +1,30 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    ops::Range,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use eframe::egui::{self, vec2, Color32, CornerRadius, Pos2, Rect, TextureHandle};
 use egui_extras::Size;
 use gilrs::Gilrs;
 use image::RgbaImage;
 
 use crate::{
+    benchmark,
     config::AppConfig,
+    furigana,
+    mining::{MiningJob, MiningStatus},
+    numbers,
+    pins::PinBoard,
     services::{
         dictionary::DictionaryServiceJob,
-        ocr::{OcrResponse, OcrServiceJob},
-        ServiceJob, Services,
+        ocr::{filter_short_paragraphs, OcrServiceJob},
+        ServiceJob, Services, PENDING_JOBS_TIMEOUT,
     },
+    share_card, stats,
+    stitching::StitchBuffer,
     word::Word,
     Popups, WINDOW_TITLE,
 };
@@ -20,18 +32,63 @@ use crate::{
 mod input_state;
 use input_state::*;
 
+mod focus;
+use focus::*;
+
+mod card_state_watcher;
+use card_state_watcher::*;
+
+mod definition_images;
+use definition_images::*;
+
+mod difficulty;
+use difficulty::*;
+
+mod quick_review;
+use quick_review::*;
+
 mod text_with_ruby_widget;
 use text_with_ruby_widget::*;
 
 /// The OCR window, shown when the user presses the OCR hotkey.
 pub struct OcrWindow {
     pub close_requested: bool,
+    /// When this window was created. Also serves to tell OCR sessions apart.
+    pub created_at: Instant,
+    /// Game (capture preset) the time spent in this window is attributed to in the statistics.
+    pub game: String,
 
     pub texture: TextureHandle,
+    /// The captured screenshot, used for share cards. A blank image if the text wasn't captured
+    /// from the screen.
+    pub screenshot: RgbaImage,
+    /// Whether the text was captured from the screen, rather than eg. sent from the browser.
+    pub is_capture: bool,
+    /// Bounds of the recognised text in the screenshot, if the OCR service returned them.
+    pub text_bounds: Option<Rect>,
     pub config: AppConfig,
     pub gilrs: Gilrs,
 
     pub state: State,
+    /// Words added to the user's deck during this session which haven't been handled by the app yet,
+    /// along with their sentence.
+    pub mined: Vec<(Word, String)>,
+    /// Words graded during a quick review which haven't been handled by the app yet, and whether they were passed.
+    pub reviewed: Vec<(Word, bool)>,
+    /// Number of characters in the capture once it has loaded, if it hasn't been handled by the app yet.
+    pub characters_read: Option<usize>,
+    /// Pages captured previously, which should be stitched together with this window's capture.
+    pub stitch_buffer: StitchBuffer,
+
+    /// When the current loading stage started.
+    pub stage_started_at: Instant,
+    /// Name and duration of the loading stages which have already completed, along with the time
+    /// taken by the screenshot and by the OCR service to encode it.
+    pub stage_timings: Vec<(&'static str, Duration)>,
+    /// Whether the timings of this capture were written to the benchmark log.
+    pub benchmark_written: bool,
+    /// Images of the definitions shown so far, kept for the following windows.
+    pub definition_images: DefinitionImages,
 
     pub frame_count: u32,
 }
@@ -40,6 +97,8 @@ pub struct OcrWindow {
 pub enum State {
     /// Waiting on the OCR service.
     LoadingOcr(OcrServiceJob),
+    /// Waiting on the user's post-OCR hook script.
+    TransformingText(ServiceJob<Result<Vec<String>>>),
     /// Waiting on the dictionary service.
     LoadingDictionary(DictionaryServiceJob),
     /// Waiting on the SRS service.
@@ -48,17 +107,67 @@ pub enum State {
         job: ServiceJob<Result<()>>,
     },
     /// The UI is ready to be shown.
-    Ready(ReadyState),
+    Ready(Box<ReadyState>),
 }
 
 impl State {
     /// Whether we are still waiting on data from services.
     pub fn is_loading(&self) -> bool {
         match self {
-            Self::LoadingOcr(_) | Self::LoadingDictionary(_) | Self::LoadingSrs { .. } => true,
+            Self::LoadingOcr(_)
+            | Self::TransformingText(_)
+            | Self::LoadingDictionary(_)
+            | Self::LoadingSrs { .. } => true,
             Self::Ready(_) => false,
         }
     }
+
+    /// Name of the loading stage, if still loading.
+    pub fn stage_name(&self) -> Option<&'static str> {
+        match self {
+            Self::LoadingOcr(_) => Some("OCR"),
+            Self::TransformingText(_) => Some("Post-OCR Hook"),
+            Self::LoadingDictionary(_) => Some("Dictionary"),
+            Self::LoadingSrs { .. } => Some("SRS"),
+            Self::Ready(_) => None,
+        }
+    }
+
+    /// How long the loading stage is expected to take, if still loading and a budget is set.
+    fn budget(&self, config: &AppConfig) -> Option<Duration> {
+        let budgets = &config.latency_budgets;
+        let ms = match self {
+            Self::LoadingOcr(_) => budgets.ocr_ms,
+            Self::TransformingText(_) => budgets.post_ocr_hook_ms,
+            Self::LoadingDictionary(_) => budgets.dictionary_ms,
+            Self::LoadingSrs { .. } => budgets.srs_ms,
+            Self::Ready(_) => 0,
+        };
+        (ms > 0).then(|| Duration::from_millis(ms))
+    }
+
+    /// Hint shown when the loading stage takes longer than its budget.
+    fn slow_stage_hint(&self, config: &AppConfig) -> Option<String> {
+        match self {
+            Self::LoadingOcr(_) => Some(format!(
+                "OCR is taking longer than usual — is {} running?",
+                config.ocr_service.name()
+            )),
+            Self::TransformingText(_) => Some(
+                "The post-OCR hook is taking longer than usual — is the script waiting on something?"
+                    .to_owned(),
+            ),
+            Self::LoadingDictionary(_) => Some(format!(
+                "The dictionary is taking longer than usual — can {} be reached?",
+                config.dictionary_service.name()
+            )),
+            Self::LoadingSrs { .. } => Some(format!(
+                "Loading card states is taking longer than usual — can {} be reached?",
+                config.srs_service.name()
+            )),
+            Self::Ready(_) => None,
+        }
+    }
 }
 
 /// The OCR window's state, after all the data has been loaded.
@@ -67,16 +176,48 @@ pub struct ReadyState {
 
     /// List of paragraphs, represented as lists of words with definitions.
     pub words: Vec<Vec<Word>>,
-    /// How the words are laid out on the screen (used for finding the closest word when moving up or down).
+    /// How the words of the current page are laid out on the screen (used for finding the closest
+    /// word when moving up or down).
     pub word_rects: HashMap<(usize, usize), Rect>,
 
     /// Index of the word currently selected by the user.
     pub selected_word: (usize, usize),
+    /// Horizontal position the selection is kept closest to while moving up or down, so it doesn't
+    /// drift sideways over several lines. Reset when the selection moves any other way.
+    pub preferred_x: Option<f32>,
+    /// Index of the word last selected in each paragraph, restored when moving back into it.
+    pub paragraph_memory: HashMap<usize, usize>,
     /// Whether we should scroll to the currently selected word on this frame.
     pub scroll_to_current_word_requested: bool,
+    /// Word whose meanings are all shown, even if there are more than `max_meanings`.
+    pub expanded_word: Option<(usize, usize)>,
+    /// Whether the monolingual dictionary's meanings are shown instead of the dictionary service's.
+    pub monolingual: bool,
+
+    /// Hands-free reading mode, if enabled.
+    pub auto_scroll: Option<AutoScroll>,
+
+    /// Flashcard review of this capture's unknown words, if one is in progress.
+    pub quick_review: Option<QuickReview>,
+
+    /// Job created when the user adds a new word to their deck, along with the word and its sentence.
+    pub add_to_deck_job: Option<MiningJob>,
+    /// Job rendering and saving a share card, returning the card and where it was saved.
+    pub share_card_job: Option<ServiceJob<Result<(RgbaImage, PathBuf)>>>,
+    /// When the user asked to close the window while jobs were still pending, if they did.
+    pub closing: Option<Instant>,
+    /// Part of the window with the keyboard focus, if keyboard focus mode is enabled.
+    pub focus: FocusArea,
+    /// Reloads the card states every now and then, to show changes made elsewhere.
+    pub card_state_watcher: CardStateWatcher,
+}
 
-    /// Job created when the user adds a new word to their deck.
-    pub add_to_deck_job: Option<ServiceJob<Result<()>>>,
+/// State of the hands-free reading mode, which advances the selection at a steady pace.
+pub struct AutoScroll {
+    /// When the selection was last moved.
+    pub last_step: Instant,
+    /// Whether auto-scroll stopped on an unknown word, and is waiting for the user to resume it.
+    pub paused: bool,
 }
 
 impl ReadyState {
@@ -85,46 +226,384 @@ impl ReadyState {
         &self.words[self.selected_word.0][self.selected_word.1]
     }
 
+    /// Returns the text of the paragraph containing the currently selected word.
+    pub fn selected_sentence(&self) -> String {
+        self.words[self.selected_word.0]
+            .iter()
+            .map(|word| word.text.plain_text())
+            .collect()
+    }
+
+    /// Returns how many times the given word's definition appears in the text.
+    pub fn occurrences(&self, word: &Word) -> u32 {
+        let Some(definition) = &word.definition else {
+            return 0;
+        };
+
+        self.words
+            .iter()
+            .flatten()
+            .filter_map(|word| word.definition.as_ref())
+            .filter(|other| {
+                other.spelling == definition.spelling && other.reading == definition.reading
+            })
+            .count() as u32
+    }
+
+    /// Returns the reading of the selected word if it is a number followed by a counter. Numbers and
+    /// counters are often split into separate words, so the next word is also tried.
+    pub fn counter_reading(&self) -> Option<String> {
+        let (paragraph, idx) = self.selected_word;
+        let text = self.selected_word().text.plain_text();
+
+        numbers::counter_reading(&text).or_else(|| {
+            let next = self.words[paragraph].get(idx + 1)?;
+            numbers::counter_reading(&(text + &next.text.plain_text()))
+        })
+    }
+
+    /// Returns the index of the page containing the selected word.
+    pub fn page(&self, paragraphs_per_page: usize) -> usize {
+        match paragraphs_per_page {
+            0 => 0,
+            n => self.selected_word.0 / n,
+        }
+    }
+
+    /// Returns the number of pages the text is split into.
+    pub fn page_count(&self, paragraphs_per_page: usize) -> usize {
+        match paragraphs_per_page {
+            0 => 1,
+            n => self.words.len().div_ceil(n).max(1),
+        }
+    }
+
+    /// Returns the indices of the paragraphs on the page containing the selected word.
+    pub fn page_paragraphs(&self, paragraphs_per_page: usize) -> Range<usize> {
+        match paragraphs_per_page {
+            0 => 0..self.words.len(),
+            n => {
+                let start = self.page(n) * n;
+                start..usize::min(start + n, self.words.len())
+            }
+        }
+    }
+
+    /// Move the selection to the first word with a definition on the page `delta` pages away, if
+    /// there is one.
+    fn flip_page(&mut self, delta: i32, paragraphs_per_page: usize) {
+        let page = self.page(paragraphs_per_page) as i32 + delta;
+        if page < 0 || page as usize >= self.page_count(paragraphs_per_page) {
+            return;
+        }
+
+        let first_paragraph = page as usize * paragraphs_per_page;
+        self.selected_word = (first_paragraph..self.words.len())
+            .take(paragraphs_per_page)
+            .find_map(|paragraph_idx| {
+                self.words[paragraph_idx]
+                    .iter()
+                    .position(|word| word.definition.is_some())
+                    .map(|word_idx| (paragraph_idx, word_idx))
+            })
+            .unwrap_or((first_paragraph, 0));
+    }
+
+    /// Returns whether the selected word has more meanings than `max_meanings`, and they are hidden.
+    pub fn meanings_truncated(&self, max_meanings: usize) -> bool {
+        let Some(definition) = &self.selected_word().definition else {
+            return false;
+        };
+
+        max_meanings > 0
+            && definition.glosses(self.monolingual).len() > max_meanings
+            && self.expanded_word != Some(self.selected_word)
+    }
+
+    /// Returns whether the selected word has monolingual meanings, so that dictionaries can be switched.
+    pub fn can_toggle_dictionary(&self) -> bool {
+        self.selected_word()
+            .definition
+            .as_ref()
+            .is_some_and(|definition| !definition.monolingual_meanings.is_empty())
+    }
+
+    /// Returns whether `action` is currently shown in the bottom bar.
+    pub fn is_action_shown(
+        &self,
+        action: FocusAction,
+        max_meanings: usize,
+        paragraphs_per_page: usize,
+    ) -> bool {
+        match action {
+            FocusAction::More => self.meanings_truncated(max_meanings),
+            FocusAction::PreviousPage | FocusAction::NextPage => {
+                self.page_count(paragraphs_per_page) > 1
+            }
+            FocusAction::Dictionary => self.can_toggle_dictionary(),
+            _ => true,
+        }
+    }
+
+    /// Move the keyboard focus between the window's areas with Tab, and within the meanings and
+    /// bottom bar with the arrow keys. Enter activates the focused element.
+    fn handle_focus_input(
+        &mut self,
+        ctx: &egui::Context,
+        max_meanings: usize,
+        paragraphs_per_page: usize,
+    ) {
+        let shown: Vec<bool> = FocusAction::ALL
+            .iter()
+            .map(|action| self.is_action_shown(*action, max_meanings, paragraphs_per_page))
+            .collect();
+        let input = &mut self.input_state;
+
+        if input.next_focus.was_pressed() {
+            self.focus = self.focus.next();
+        }
+        if input.previous_focus.was_pressed() {
+            self.focus = self.focus.previous();
+        }
+
+        match &mut self.focus {
+            FocusArea::Words => {}
+            FocusArea::Meanings(idx) => {
+                let Some(definition) =
+                    &self.words[self.selected_word.0][self.selected_word.1].definition
+                else {
+                    return;
+                };
+                let glosses = definition.glosses(self.monolingual);
+
+                let truncated = self.expanded_word != Some(self.selected_word)
+                    && max_meanings > 0
+                    && glosses.len() > max_meanings;
+                let count = match truncated {
+                    true => max_meanings + 1,
+                    false => glosses.len(),
+                };
+
+                if input.up.was_pressed_with_retrigger() {
+                    *idx = idx.saturating_sub(1);
+                }
+                if input.down.was_pressed_with_retrigger() {
+                    *idx += 1;
+                }
+                *idx = (*idx).min(count.saturating_sub(1));
+
+                if input.add_to_deck.was_pressed() {
+                    if truncated && *idx == max_meanings {
+                        input.expand.press();
+                    } else if let Some(meaning) = glosses.get(*idx) {
+                        ctx.copy_text(meaning.clone());
+                    }
+                }
+            }
+            FocusArea::Actions(idx) => {
+                // eg. the "more" action is only shown when the meanings are truncated
+                let is_shown = |idx: usize| shown[idx];
+
+                if input.left.was_pressed_with_retrigger() {
+                    if let Some(previous) = (0..*idx).rev().find(|idx| is_shown(*idx)) {
+                        *idx = previous;
+                    }
+                }
+                if input.right.was_pressed_with_retrigger() {
+                    if let Some(next) =
+                        (*idx + 1..FocusAction::ALL.len()).find(|idx| is_shown(*idx))
+                    {
+                        *idx = next;
+                    }
+                }
+                if !is_shown(*idx) {
+                    *idx = (0..*idx).rev().find(|idx| is_shown(*idx)).unwrap_or(0);
+                }
+
+                if input.add_to_deck.was_pressed() {
+                    FocusAction::ALL[*idx].key(input).press();
+                }
+            }
+        }
+    }
+
     /// Returns a mutable reference to the currently selected word.
     pub fn selected_word_mut(&mut self) -> &mut Word {
         &mut self.words[self.selected_word.0][self.selected_word.1]
     }
 }
 
+const BACKGROUND_TEXTURE_OPTIONS: egui::TextureOptions = egui::TextureOptions {
+    magnification: egui::TextureFilter::Linear,
+    minification: egui::TextureFilter::Linear,
+    wrap_mode: egui::TextureWrapMode::ClampToEdge,
+    mipmap_mode: None,
+};
+
+/// The part of a screenshot within `bounds` (with some margin around it), or the whole screenshot
+/// if they aren't known.
+fn crop(screenshot: &RgbaImage, bounds: Option<Rect>) -> RgbaImage {
+    let margin = 16.0;
+
+    let Some(bounds) = bounds else {
+        return screenshot.clone();
+    };
+    let bounds = bounds.expand(margin).intersect(Rect::from_min_size(
+        Pos2::ZERO,
+        vec2(screenshot.width() as f32, screenshot.height() as f32),
+    ));
+    if !bounds.is_positive() {
+        return screenshot.clone();
+    }
+
+    image::imageops::crop_imm(
+        screenshot,
+        bounds.min.x as u32,
+        bounds.min.y as u32,
+        bounds.width() as u32,
+        bounds.height() as u32,
+    )
+    .to_image()
+}
+
+/// Upload a screenshot to a new texture.
+fn load_background_texture(ctx: &egui::Context, image: &RgbaImage) -> TextureHandle {
+    let color_image = egui::ColorImage::from_rgba_unmultiplied(
+        [image.width() as usize, image.height() as usize],
+        image.as_flat_samples().as_slice(),
+    );
+
+    ctx.load_texture(
+        "ocr window background",
+        color_image,
+        BACKGROUND_TEXTURE_OPTIONS,
+    )
+}
+
+/// Update a texture showing `previous` so that it shows `image` instead, only uploading the region
+/// which changed between the two. Returns `None` if the screenshots don't have the same size.
+fn update_background_texture(
+    mut texture: TextureHandle,
+    previous: &RgbaImage,
+    image: &RgbaImage,
+) -> Option<TextureHandle> {
+    if previous.dimensions() != image.dimensions() {
+        return None;
+    }
+
+    // bounding box of the changed pixels
+    let mut min = (u32::MAX, u32::MAX);
+    let mut max = (0, 0);
+    for (x, y, pixel) in image.enumerate_pixels() {
+        if previous.get_pixel(x, y) != pixel {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+    }
+
+    // nothing changed, no need to upload anything
+    if min.0 > max.0 {
+        return Some(texture);
+    }
+
+    let region =
+        image::imageops::crop_imm(image, min.0, min.1, max.0 - min.0 + 1, max.1 - min.1 + 1)
+            .to_image();
+    let color_image = egui::ColorImage::from_rgba_unmultiplied(
+        [region.width() as usize, region.height() as usize],
+        region.as_flat_samples().as_slice(),
+    );
+    texture.set_partial(
+        [min.0 as usize, min.1 as usize],
+        color_image,
+        BACKGROUND_TEXTURE_OPTIONS,
+    );
+
+    Some(texture)
+}
+
+/// Whether the word is marked as relevant by the SRS service, and not filtered out by its part of speech.
+fn is_relevant(word: &Word, config: &AppConfig, services: &Services) -> bool {
+    services.srs.card_state(word).is_relevant && !config.part_of_speech_filter.skips(word)
+}
+
 impl OcrWindow {
-    /// Create a new `OcrWindow` and start querying data from services.
+    /// Create a new `OcrWindow` and start querying data from services. `capture_time` is the time
+    /// the screenshot took, recorded in the benchmark log. If `previous` is the window this one
+    /// replaces, its background texture is reused when possible.
     pub fn new(
         ctx: &egui::Context,
         config: AppConfig,
         image: RgbaImage,
+        capture_time: Duration,
+        stitch_buffer: StitchBuffer,
         services: &mut Services,
+        previous: Option<OcrWindow>,
     ) -> Self {
-        let color_image = egui::ColorImage::from_rgba_unmultiplied(
-            [image.width() as usize, image.height() as usize],
-            image.as_flat_samples().as_slice(),
-        );
+        let state = State::LoadingOcr(services.ocr.ocr(config.preprocessing.apply(image.clone())));
+        let mut window = Self::with_state(ctx, config, image, stitch_buffer, state, previous);
+        window.stage_timings.push(("Capture", capture_time));
+        window
+    }
 
-        let texture = ctx.load_texture(
-            "ocr window background",
-            color_image,
-            egui::TextureOptions {
-                magnification: egui::TextureFilter::Linear,
-                minification: egui::TextureFilter::Linear,
-                wrap_mode: egui::TextureWrapMode::ClampToEdge,
-                mipmap_mode: None,
-            },
-        );
+    /// Create a new `OcrWindow` for text which doesn't need to be recognised (eg. text sent from
+    /// the browser), shown over a blank background.
+    pub fn from_text(
+        ctx: &egui::Context,
+        config: AppConfig,
+        paragraphs: Vec<String>,
+        previous: Option<OcrWindow>,
+    ) -> Self {
+        let image = RgbaImage::from_pixel(1, 1, image::Rgba([0, 0, 0, 255]));
+        let state = State::TransformingText(config.hooks.post_ocr(paragraphs));
+        let mut window =
+            Self::with_state(ctx, config, image, StitchBuffer::default(), state, previous);
+        window.is_capture = false;
+        window
+    }
 
-        let state = State::LoadingOcr(services.ocr.ocr(image));
+    fn with_state(
+        ctx: &egui::Context,
+        config: AppConfig,
+        image: RgbaImage,
+        stitch_buffer: StitchBuffer,
+        state: State,
+        previous: Option<OcrWindow>,
+    ) -> Self {
+        let (texture, definition_images) = match previous {
+            Some(previous) => (
+                update_background_texture(previous.texture, &previous.screenshot, &image)
+                    .unwrap_or_else(|| load_background_texture(ctx, &image)),
+                previous.definition_images,
+            ),
+            None => (
+                load_background_texture(ctx, &image),
+                DefinitionImages::default(),
+            ),
+        };
 
         Self {
             close_requested: false,
+            created_at: Instant::now(),
+            game: stats::UNKNOWN_GAME.to_owned(),
 
             texture,
+            screenshot: image,
+            is_capture: true,
+            text_bounds: None,
             config,
             gilrs: Gilrs::new().unwrap(),
 
             state,
+            mined: Vec::new(),
+            reviewed: Vec::new(),
+            characters_read: None,
+            stitch_buffer,
+
+            stage_started_at: Instant::now(),
+            stage_timings: Vec::new(),
+            benchmark_written: false,
+            definition_images,
 
             frame_count: 0,
         }
@@ -132,8 +611,12 @@ impl OcrWindow {
 
     /// Manages the `OcrWindow`'s state while it is still loading.
     pub fn manage_loading(&mut self, services: &mut Services) -> Result<()> {
+        let stage = self.state.stage_name();
+
         match &mut self.state {
             State::Ready(_) => {}
+            // wait for previously captured pages before moving on, so they can be stitched together
+            State::LoadingOcr(_) if !self.stitch_buffer.poll(self.config.paragraph_order)? => {}
             State::LoadingOcr(job) => match job
                 .try_wait()
                 .unwrap()
@@ -141,8 +624,28 @@ impl OcrWindow {
                 .context("OCR ServiceJob returned an error")?
             {
                 None => {}
-                Some(OcrResponse::WithRects(_)) => unimplemented!(),
-                Some(OcrResponse::WithoutRects(text)) => {
+                Some((response, encode_time)) => {
+                    self.stage_timings
+                        .extend(encode_time.map(|duration| ("Encode", duration)));
+                    self.text_bounds = response.text_bounds();
+                    let text = response.into_paragraphs(self.config.paragraph_order);
+                    let text = std::mem::take(&mut self.stitch_buffer).stitch(text);
+                    let text = filter_short_paragraphs(
+                        text,
+                        self.config.min_paragraph_chars,
+                        self.config.merge_short_paragraphs,
+                    );
+                    self.state = State::TransformingText(self.config.hooks.post_ocr(text));
+                }
+            },
+            State::TransformingText(job) => match job
+                .try_wait()
+                .unwrap()
+                .transpose()
+                .context("Post-OCR hook returned an error")?
+            {
+                None => {}
+                Some(text) => {
                     self.state = State::LoadingDictionary(services.dictionary.parse(text));
                 }
             },
@@ -153,7 +656,10 @@ impl OcrWindow {
                 .context("Dictionary ServiceJob returned an error")?
             {
                 None => {}
-                Some(words) => {
+                Some(mut words) => {
+                    furigana::fill_missing(&mut words);
+                    self.config.definition_suppression.apply(&mut words);
+                    services.monolingual.annotate(&mut words);
                     self.state = State::LoadingSrs {
                         job: services
                             .srs
@@ -181,21 +687,94 @@ impl OcrWindow {
                         }
                     }
 
-                    self.state = State::Ready(ReadyState {
+                    self.characters_read = Some(
+                        words
+                            .iter()
+                            .flatten()
+                            .map(|word| word.text.plain_text().chars().count())
+                            .sum(),
+                    );
+
+                    let card_state_watcher = CardStateWatcher::new(words, services);
+
+                    self.state = State::Ready(Box::new(ReadyState {
                         input_state: Default::default(),
                         words: std::mem::take(words),
                         word_rects: Default::default(),
                         selected_word,
+                        preferred_x: None,
+                        paragraph_memory: Default::default(),
                         scroll_to_current_word_requested: false,
+                        expanded_word: None,
+                        monolingual: self.config.monolingual_by_default,
+                        auto_scroll: None,
+                        quick_review: None,
                         add_to_deck_job: None,
-                    });
+                        share_card_job: None,
+                        closing: None,
+                        focus: FocusArea::default(),
+                        card_state_watcher,
+                    }));
                 }
             },
         }
 
+        if let Some(stage) = stage.filter(|stage| Some(*stage) != self.state.stage_name()) {
+            let elapsed = self.stage_started_at.elapsed();
+            log::debug!("{stage} took {}ms", elapsed.as_millis());
+            self.stage_timings.push((stage, elapsed));
+            self.stage_started_at = Instant::now();
+        }
+
         Ok(())
     }
 
+    /// Show the loading spinner, along with the time spent in each loading stage and a hint if the
+    /// current one is taking longer than expected.
+    fn show_loading_ui(&self, ui: &mut egui::Ui) {
+        let faded = Color32::from_white_alpha(96);
+        let spinner = match self.config.accent.colour() {
+            Some(accent) if !self.config.monochrome => accent.gamma_multiply(0.75),
+            _ => faded,
+        };
+
+        ui.vertical_centered(|ui| {
+            ui.add_space((ui.available_height() / 2.0 - 48.0).max(0.0));
+            ui.add(egui::Spinner::new().color(spinner).size(48.0));
+            ui.add_space(12.0);
+
+            for (stage, elapsed) in &self.stage_timings {
+                ui.label(
+                    egui::RichText::new(format!("{stage}: {:.1}s", elapsed.as_secs_f32()))
+                        .color(faded),
+                );
+            }
+
+            let Some(stage) = self.state.stage_name() else {
+                return;
+            };
+            let elapsed = self.stage_started_at.elapsed();
+            ui.label(
+                egui::RichText::new(format!("{stage}: {:.1}s…", elapsed.as_secs_f32()))
+                    .color(Color32::from_white_alpha(160)),
+            );
+
+            if self
+                .state
+                .budget(&self.config)
+                .is_some_and(|budget| elapsed > budget)
+            {
+                if let Some(hint) = self.state.slow_stage_hint(&self.config) {
+                    ui.add_space(8.0);
+                    ui.label(egui::RichText::new(hint).color(Color32::from_rgb(240, 200, 120)));
+                }
+            }
+        });
+
+        // keep the elapsed time ticking while no events come in
+        ui.ctx().request_repaint_after(Duration::from_millis(100));
+    }
+
     /// Show the window to the user.
     pub fn show(
         &mut self,
@@ -203,6 +782,7 @@ impl OcrWindow {
         config: &AppConfig,
         popups: &mut Popups,
         services: &mut Services,
+        pin_board: &mut PinBoard,
     ) {
         if let Err(e) = self.manage_loading(services) {
             popups.error(e);
@@ -221,17 +801,57 @@ impl OcrWindow {
         // show errors if add_to_deck_job has failed
         if let State::Ready(state) = &mut self.state {
             if let Some(job) = &mut state.add_to_deck_job {
-                match job.try_wait() {
-                    Ok(None) => {}
-                    Ok(Some(Ok(_))) => {
+                match job.poll(services) {
+                    Ok(MiningStatus::Pending) => {}
+                    Ok(MiningStatus::Added(word)) => {
+                        state.card_state_watcher.word_mined(&word);
+                        self.mined.push((*word, job.sentence.clone()));
                         state.add_to_deck_job = None;
                     }
-                    Err(e) | Ok(Some(Err(e))) => {
+                    // nothing was added in dry-run mode, so the word doesn't count as mined
+                    Ok(MiningStatus::NotAdded) => state.add_to_deck_job = None,
+                    Err(e) => {
                         popups.error(e);
                         state.add_to_deck_job = None;
                     }
                 }
             }
+
+            if let Some(job) = &mut state.share_card_job {
+                match job.try_wait() {
+                    Ok(None) => {}
+                    Ok(Some(Ok((card, path)))) => {
+                        log::info!("Saved share card to `{}`", path.display());
+                        ctx.copy_image(egui::ColorImage::from_rgba_unmultiplied(
+                            [card.width() as usize, card.height() as usize],
+                            card.as_flat_samples().as_slice(),
+                        ));
+                        state.share_card_job = None;
+                    }
+                    Err(e) | Ok(Some(Err(e))) => {
+                        popups.error(e.context("Failed to create share card"));
+                        state.share_card_job = None;
+                    }
+                }
+            }
+
+            state.card_state_watcher.poll(
+                &state.words,
+                services,
+                Duration::from_secs(self.config.card_state_refresh_secs),
+            );
+
+            if let Some(closing) = state.closing {
+                if state.add_to_deck_job.is_none() {
+                    self.close_requested = true;
+                } else if closing.elapsed() >= PENDING_JOBS_TIMEOUT {
+                    popups.error(anyhow!(
+                        "Gave up waiting for pending operations: {}",
+                        self.pending_operations().join(", ")
+                    ));
+                    self.close_requested = true;
+                }
+            }
         }
 
         ctx.show_viewport_immediate(
@@ -254,39 +874,51 @@ impl OcrWindow {
                 }
 
                 egui::CentralPanel::default().show(ctx, |ui| {
-                    ui.painter().image(
-                        self.texture.id(),
-                        ctx.available_rect(),
-                        Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
-                        Color32::WHITE,
-                    );
-                    ui.painter().rect_filled(
-                        ctx.available_rect(),
-                        CornerRadius::ZERO,
-                        Color32::from_black_alpha(self.config.background_dimming),
-                    );
+                    if self.config.monochrome {
+                        ui.painter().rect_filled(
+                            ctx.available_rect(),
+                            CornerRadius::ZERO,
+                            Color32::BLACK,
+                        );
+                    } else {
+                        ui.painter().image(
+                            self.texture.id(),
+                            ctx.available_rect(),
+                            Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+                            Color32::WHITE,
+                        );
+                        ui.painter().rect_filled(
+                            ctx.available_rect(),
+                            CornerRadius::ZERO,
+                            Color32::from_black_alpha(self.config.background_dimming),
+                        );
+                    }
 
                     if self.state.is_loading() {
-                        ui.centered_and_justified(|ui| {
-                            ui.add(
-                                egui::Spinner::new()
-                                    .color(Color32::from_white_alpha(96))
-                                    .size(48.0),
-                            );
-                        });
+                        self.show_loading_ui(ui);
                     } else {
-                        self.show_ui(ui, services);
+                        self.show_ui(ui, services, pin_board);
+
+                        if let State::Ready(state) = &self.state {
+                            state.card_state_watcher.show_toast(ctx);
+                        }
 
-                        if let Err(e) = self.handle_input(ctx, services) {
+                        if self.config.benchmark_mode && !self.benchmark_written {
+                            self.benchmark_written = true;
+                            self.write_benchmark_entry();
+                        }
+
+                        if let Err(e) = self.handle_input(ctx, services, pin_board) {
                             popups.error(e);
                         }
                     }
 
-                    ctx.input(|input| {
-                        if input.viewport().close_requested() {
-                            self.close_requested = true;
+                    if ctx.input(|input| input.viewport().close_requested()) {
+                        if !self.pending_operations().is_empty() {
+                            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
                         }
-                    });
+                        self.request_close();
+                    }
                 });
             },
         );
@@ -294,19 +926,161 @@ impl OcrWindow {
         self.frame_count += 1;
     }
 
+    /// Append the timings of this capture to the benchmark log, once its text was first shown.
+    fn write_benchmark_entry(&self) {
+        let State::Ready(state) = &self.state else {
+            return;
+        };
+        let characters = state
+            .words
+            .iter()
+            .flatten()
+            .map(|word| word.text.plain_text().chars().count())
+            .sum();
+
+        if let Err(e) = benchmark::write_entry(
+            &self.config,
+            characters,
+            &self.stage_timings,
+            self.created_at.elapsed(),
+        ) {
+            log::error!("{e:#}");
+        }
+    }
+
+    /// Whether keyboard focus mode is enabled and `area` has the focus.
+    fn focused(&self, area: FocusArea) -> bool {
+        match &self.state {
+            State::Ready(state) => self.config.keyboard_focus_mode && state.focus == area,
+            _ => false,
+        }
+    }
+
+    /// Descriptions of the jobs which haven't finished yet, and would be lost if the window was closed.
+    pub fn pending_operations(&self) -> Vec<String> {
+        let State::Ready(state) = &self.state else {
+            return Vec::new();
+        };
+
+        state
+            .add_to_deck_job
+            .iter()
+            .map(|job| format!("Adding {} to your deck", job.word.text.plain_text()))
+            .collect()
+    }
+
+    /// Take the job adding a word to the user's deck, if one is still running. Used to keep track
+    /// of it when this window is replaced by another one.
+    pub fn take_add_to_deck_job(&mut self) -> Option<MiningJob> {
+        match &mut self.state {
+            State::Ready(state) => state.add_to_deck_job.take(),
+            _ => None,
+        }
+    }
+
+    /// Close the window, or if jobs are still pending, ask the user whether to wait for them first.
+    fn request_close(&mut self) {
+        match &mut self.state {
+            State::Ready(state) if state.add_to_deck_job.is_some() && state.closing.is_none() => {
+                state.closing = Some(Instant::now());
+            }
+            _ => self.close_requested = true,
+        }
+    }
+
     /// Updates the window's state based on the user's input.
-    fn handle_input(&mut self, ctx: &egui::Context, services: &mut Services) -> Result<()> {
+    fn handle_input(
+        &mut self,
+        ctx: &egui::Context,
+        services: &mut Services,
+        pin_board: &mut PinBoard,
+    ) -> Result<()> {
         let State::Ready(state) = &mut self.state else {
             panic!("invariant broken: handle_input should only be called when self.state is Some!");
         };
 
         state.input_state.update(ctx, &mut self.gilrs);
 
+        if state.closing.is_some() {
+            if state.input_state.exit.was_pressed() {
+                self.close_requested = true;
+            } else if state.input_state.add_to_deck.was_pressed() {
+                state.closing = None;
+            }
+
+            return Ok(());
+        }
+
+        if let Some(review) = &mut state.quick_review {
+            let input = &mut state.input_state;
+
+            let action = if input.add_to_deck.was_pressed() {
+                match review.is_revealed() {
+                    false => Some(ReviewAction::Reveal),
+                    true => Some(ReviewAction::Answer { passed: true }),
+                }
+            } else if input.right.was_pressed() {
+                Some(ReviewAction::Answer { passed: true })
+            } else if input.left.was_pressed() {
+                Some(ReviewAction::Answer { passed: false })
+            } else {
+                None
+            };
+
+            if let Some(result) = action.and_then(|action| review.apply(action)) {
+                self.reviewed.push(result);
+            }
+
+            if input.exit.was_pressed() || input.review.was_pressed() {
+                state.quick_review = None;
+            }
+
+            return Ok(());
+        }
+
+        // runs before any key `FocusAction::ALL` presses is checked, so that they are seen this frame
+        if self.config.keyboard_focus_mode {
+            state.handle_focus_input(
+                ctx,
+                self.config.max_meanings,
+                self.config.paragraphs_per_page,
+            );
+        }
+
+        if state.input_state.hardest_paragraph.was_pressed() {
+            if let Some(paragraph_idx) = hardest_paragraph(&state.words, services) {
+                let paragraph = &state.words[paragraph_idx];
+                let word_idx = paragraph
+                    .iter()
+                    .position(|word| is_relevant(word, &self.config, services))
+                    .or_else(|| paragraph.iter().position(|word| word.definition.is_some()));
+
+                if let Some(word_idx) = word_idx {
+                    state.selected_word = (paragraph_idx, word_idx);
+                    state.scroll_to_current_word_requested = true;
+                }
+            }
+        }
+
+        if state.input_state.review.was_pressed() {
+            state.quick_review =
+                QuickReview::new(&state.words, services, &self.config.part_of_speech_filter);
+            if state.quick_review.is_none() {
+                log::info!("No unknown words to review in this capture");
+            }
+        }
+
+        let words_focused = state.focus == FocusArea::Words;
+        let paragraphs_per_page = self.config.paragraphs_per_page;
+        let page = state.page(paragraphs_per_page);
+        let previous_selection = state.selected_word;
+        let mut moved_vertically = false;
+
         let skip_irrelevant_words = state.input_state.skip_irrelevant.is_pressed();
 
         let word_is_valid = |word: &Word| {
             if skip_irrelevant_words {
-                services.srs.card_state(word).is_relevant
+                is_relevant(word, &self.config, services)
             } else {
                 word.definition.is_some()
             }
@@ -350,9 +1124,16 @@ impl OcrWindow {
         };
 
         let move_v = |state: &mut ReadyState, direction: i32| {
-            let current_rect = state.word_rects.get(&state.selected_word).copied().unwrap();
+            // the selection moved to a page which hasn't been laid out yet
+            let Some(current_rect) = state.word_rects.get(&state.selected_word).copied() else {
+                return;
+            };
+            let target = Pos2::new(
+                *state.preferred_x.get_or_insert(current_rect.center().x),
+                current_rect.center().y,
+            );
 
-            state
+            if let Some((idx, rect)) = state
                 .word_rects
                 .iter()
                 .filter(|(idx, _)| state.words[idx.0][idx.1].definition.is_some())
@@ -363,46 +1144,189 @@ impl OcrWindow {
                         rect.bottom() > current_rect.bottom()
                     }
                 })
-                .map(|(idx, rect)| (idx, rect.center().distance(current_rect.center())))
-                .min_by(|(_, dist1), (_, dist2)| dist1.total_cmp(dist2))
-                .map(|(idx, _)| state.selected_word = *idx);
+                .map(|(idx, rect)| (*idx, *rect, rect.center().distance(target)))
+                .min_by(|(_, _, dist1), (_, _, dist2)| dist1.total_cmp(dist2))
+                .map(|(idx, rect, _)| (idx, rect))
+            {
+                // the remembered word is only restored if it is on the line closest to the target,
+                // otherwise moving into a paragraph could skip some of its lines
+                let remembered = state
+                    .paragraph_memory
+                    .get(&idx.0)
+                    .map(|&word_idx| (idx.0, word_idx))
+                    .filter(|remembered| {
+                        state
+                            .word_rects
+                            .get(remembered)
+                            .is_some_and(|remembered_rect| {
+                                remembered_rect.y_range().contains(rect.center().y)
+                            })
+                    });
+
+                state.selected_word = match remembered {
+                    Some(remembered) if idx.0 != state.selected_word.0 => remembered,
+                    _ => idx,
+                };
+            }
         };
 
         state.scroll_to_current_word_requested = false;
 
-        if state.input_state.left.was_pressed_with_retrigger() {
+        if words_focused && state.input_state.left.was_pressed_with_retrigger() {
             move_h(state, -1);
             state.scroll_to_current_word_requested = true;
         }
 
-        if state.input_state.right.was_pressed_with_retrigger() {
+        if words_focused && state.input_state.right.was_pressed_with_retrigger() {
             move_h(state, 1);
             state.scroll_to_current_word_requested = true;
         }
 
-        if state.input_state.up.was_pressed_with_retrigger() {
+        if words_focused && state.input_state.up.was_pressed_with_retrigger() {
             move_v(state, -1);
+            moved_vertically = true;
             if state.input_state.skip_irrelevant.is_pressed() {
                 move_h(state, -1);
             }
             state.scroll_to_current_word_requested = true;
         }
 
-        if state.input_state.down.was_pressed_with_retrigger() {
+        if words_focused && state.input_state.down.was_pressed_with_retrigger() {
             move_v(state, 1);
+            moved_vertically = true;
             if state.input_state.skip_irrelevant.is_pressed() {
                 move_h(state, 1);
             }
             state.scroll_to_current_word_requested = true;
         }
 
+        if state.input_state.previous_page.was_pressed() {
+            state.flip_page(-1, paragraphs_per_page);
+        }
+
+        if state.input_state.next_page.was_pressed() {
+            state.flip_page(1, paragraphs_per_page);
+        }
+
+        if state.input_state.auto_scroll.was_pressed() {
+            state.auto_scroll = match &state.auto_scroll {
+                Some(auto_scroll) if !auto_scroll.paused => None,
+                _ => Some(AutoScroll {
+                    last_step: Instant::now(),
+                    paused: false,
+                }),
+            };
+        }
+
+        if let Some(auto_scroll) = &mut state.auto_scroll {
+            // moving the selection manually restarts the countdown to the next step
+            if state.scroll_to_current_word_requested {
+                auto_scroll.last_step = Instant::now();
+            }
+            ctx.request_repaint_after(Duration::from_millis(50));
+        }
+
+        if let Some(auto_scroll) = state.auto_scroll.as_ref().filter(|a| !a.paused) {
+            let pace = Duration::from_millis(self.config.auto_scroll_pace_ms);
+            let dwell = match self.config.auto_scroll_paragraphs {
+                false => pace,
+                true => pace * state.words[state.selected_word.0].len() as u32,
+            };
+
+            if auto_scroll.last_step.elapsed() >= dwell {
+                let previous = state.selected_word;
+
+                if self.config.auto_scroll_paragraphs {
+                    let next = (previous.0 + 1..state.words.len()).find_map(|paragraph_idx| {
+                        let paragraph = &state.words[paragraph_idx];
+                        paragraph
+                            .iter()
+                            .position(|word| is_relevant(word, &self.config, services))
+                            .or_else(|| paragraph.iter().position(|word| word.definition.is_some()))
+                            .map(|word_idx| (paragraph_idx, word_idx))
+                    });
+                    if let Some(next) = next {
+                        state.selected_word = next;
+                    }
+                } else {
+                    move_h(state, 1);
+                }
+
+                if state.selected_word == previous {
+                    // reached the end of the text
+                    state.auto_scroll = None;
+                } else {
+                    state.scroll_to_current_word_requested = true;
+                    state.auto_scroll = Some(AutoScroll {
+                        last_step: Instant::now(),
+                        paused: self.config.auto_scroll_pause_on_unknown
+                            && is_relevant(state.selected_word(), &self.config, services),
+                    });
+                }
+            }
+        }
+
+        // the new page is laid out on the next frame
+        if state.page(paragraphs_per_page) != page {
+            state.word_rects.clear();
+            state.scroll_to_current_word_requested = true;
+        }
+
+        // only words selected explicitly are remembered, not the ones passed while moving vertically
+        if state.selected_word != previous_selection && !moved_vertically {
+            state.preferred_x = None;
+            state
+                .paragraph_memory
+                .insert(state.selected_word.0, state.selected_word.1);
+        }
+
         if state.input_state.exit.was_pressed() {
-            self.close_requested = true;
+            match state.add_to_deck_job {
+                Some(_) => state.closing = Some(Instant::now()),
+                None => self.close_requested = true,
+            }
         }
 
         if state.input_state.add_to_deck.was_pressed() {
-            let word = state.selected_word().clone();
-            state.add_to_deck_job = Some(services.srs.add_to_deck(&word));
+            let sentence = state.selected_sentence();
+            let occurrences = state.occurrences(state.selected_word());
+            state.add_to_deck_job = Some(MiningJob::new(
+                &self.config.hooks,
+                state.selected_word(),
+                sentence,
+                occurrences,
+            ));
+        }
+
+        if state.input_state.pin.was_pressed() {
+            let sentence = state.selected_sentence();
+            pin_board.toggle(state.selected_word(), sentence);
+        }
+
+        if state.input_state.toggle_dictionary.was_pressed() {
+            state.monolingual = !state.monolingual;
+        }
+
+        if state.input_state.expand.was_pressed() {
+            state.expanded_word = match state.expanded_word {
+                Some(idx) if idx == state.selected_word => None,
+                _ => Some(state.selected_word),
+            };
+        }
+
+        if state.input_state.share.was_pressed() && state.share_card_job.is_none() {
+            if let Some(definition) = state.selected_word().definition.clone() {
+                let sentence = state.selected_sentence();
+                let screenshot = self
+                    .is_capture
+                    .then(|| crop(&self.screenshot, self.text_bounds));
+
+                state.share_card_job = Some(ServiceJob::new(move || {
+                    let card = share_card::render(&definition, &sentence, screenshot.as_ref())?;
+                    let path = share_card::save(&card, &definition)?;
+                    Ok((card, path))
+                }));
+            }
         }
 
         // TODO left/right stick scrolling
@@ -411,7 +1335,7 @@ impl OcrWindow {
     }
 
     /// Show the inner UI of the window, once it has loaded.
-    fn show_ui(&mut self, ui: &mut egui::Ui, services: &Services) {
+    fn show_ui(&mut self, ui: &mut egui::Ui, services: &Services, pin_board: &PinBoard) {
         let padding_h = 32.0;
         let padding_v = padding_h / 2.0;
         let bottom_bar = 64.0;
@@ -424,6 +1348,32 @@ impl OcrWindow {
             .vertical(|mut strip| {
                 strip.empty();
 
+                let State::Ready(state) = &mut self.state else {
+                    panic!(
+                        "invariant broken: show_ui should only be called when self.state is Some!"
+                    );
+                };
+
+                if let Some(review) = &mut state.quick_review {
+                    strip.cell(|ui| {
+                        if let Some(result) =
+                            review.show_ui(ui).and_then(|action| review.apply(action))
+                        {
+                            self.reviewed.push(result);
+                        }
+                    });
+                    strip.cell(|ui| bottom_bar_ui(self, ui));
+                    return;
+                }
+
+                if let Some(closing) = state.closing {
+                    let remaining = PENDING_JOBS_TIMEOUT.saturating_sub(closing.elapsed());
+                    let pending_operations = self.pending_operations();
+                    strip.cell(|ui| closing_ui(ui, &pending_operations, remaining));
+                    strip.cell(|ui| bottom_bar_ui(self, ui));
+                    return;
+                }
+
                 strip.strip(|builder| {
                     builder
                         .size(Size::exact(padding_h))
@@ -434,11 +1384,16 @@ impl OcrWindow {
                         .horizontal(|mut strip| {
                             strip.empty();
 
-                            strip.cell(|ui| text_panel_ui(self, ui, services));
+                            strip.cell(|ui| {
+                                text_panel_ui(self, ui, services, pin_board);
+                                if self.focused(FocusArea::Words) {
+                                    focus_ring(ui, ui.max_rect());
+                                }
+                            });
 
                             strip.empty();
 
-                            strip.cell(|ui| definition_panel_ui(self, ui, services));
+                            strip.cell(|ui| definition_panel_ui(self, ui, services, pin_board));
 
                             strip.empty();
                         });
@@ -447,7 +1402,12 @@ impl OcrWindow {
                 strip.cell(|ui| bottom_bar_ui(self, ui));
             });
 
-        fn text_panel_ui(win: &mut OcrWindow, ui: &mut egui::Ui, services: &Services) {
+        fn text_panel_ui(
+            win: &mut OcrWindow,
+            ui: &mut egui::Ui,
+            services: &Services,
+            pin_board: &PinBoard,
+        ) {
             let State::Ready(state) = &mut win.state else {
                 panic!("invariant broken: show_without_rects should only be called when self.state is Some!");
             };
@@ -457,23 +1417,62 @@ impl OcrWindow {
                 .show(ui, |ui| {
                     let text_size = 32.0;
                     let ruby_size = 11.0;
-                    let selection_highlight = Color32::from_white_alpha(8);
+                    let selection_highlight = match win.config.accent.colour() {
+                        Some(accent) => accent.gamma_multiply(0.25),
+                        None => Color32::from_white_alpha(8),
+                    };
                     let paragraph_spacing = text_size / 2.0;
+                    let monochrome = win.config.monochrome;
 
                     ui.spacing_mut().item_spacing = vec2(0.0, 0.0);
 
                     let mut word_rects = HashMap::new();
+                    let page = state.page_paragraphs(win.config.paragraphs_per_page);
 
-                    for (paragraph_idx, paragraph) in state.words.iter().enumerate() {
+                    for (paragraph_idx, paragraph) in
+                        state.words.iter().enumerate().take(page.end).skip(page.start)
+                    {
                         if paragraph_idx == state.selected_word.0 {
                             ui.add_space(paragraph_spacing);
                         }
 
                         ui.horizontal_wrapped(|ui| {
+                            if let Some(difficulty) = paragraph_difficulty(paragraph, services) {
+                                let colour = match monochrome {
+                                    true => Color32::WHITE,
+                                    false => Color32::from_rgb(125, 255, 125)
+                                        .lerp_to_gamma(Color32::from_rgb(255, 75, 60), difficulty),
+                                };
+
+                                egui::Frame::new()
+                                    .stroke(egui::Stroke::new(1.0, colour))
+                                    .corner_radius(4.0)
+                                    .inner_margin(egui::Margin::symmetric(4, 0))
+                                    .outer_margin(egui::Margin {
+                                        right: 8,
+                                        ..Default::default()
+                                    })
+                                    .show(ui, |ui| {
+                                        ui.label(
+                                            egui::RichText::new(format!(
+                                                "{:.0}%",
+                                                difficulty * 100.0
+                                            ))
+                                            .size(ruby_size)
+                                            .color(colour),
+                                        )
+                                        .on_hover_text("Paragraph difficulty: share of unknown words, weighted by rarity");
+                                    });
+                            }
+
                             for (word_idx, word) in paragraph.iter().enumerate() {
-                                let colour = {
-                                    let [r, g, b] = services.srs.card_state(word).colour;
-                                    Color32::from_rgb(r, g, b)
+                                let card_state = services.srs.card_state(word);
+                                let colour = match monochrome {
+                                    true => Color32::WHITE,
+                                    false => {
+                                        let [r, g, b] = card_state.colour;
+                                        Color32::from_rgb(r, g, b)
+                                    }
                                 };
 
                                 let rect = ui
@@ -485,19 +1484,54 @@ impl OcrWindow {
                                     )
                                     .rect;
 
-                                if state.word_rects.is_empty() {
-                                    word_rects.insert((paragraph_idx, word_idx), rect);
+                                word_rects.insert((paragraph_idx, word_idx), rect);
+
+                                let is_pinned = word
+                                    .definition
+                                    .as_ref()
+                                    .is_some_and(|definition| pin_board.is_pinned(definition));
+
+                                if is_pinned {
+                                    ui.painter().hline(
+                                        rect.x_range(),
+                                        rect.bottom(),
+                                        egui::Stroke::new(2.0, colour),
+                                    );
+                                }
+
+                                // without colours, card states are told apart by their underline
+                                // instead, drawn below the pinned word underline
+                                if monochrome {
+                                    let y = match is_pinned {
+                                        true => rect.bottom() + 5.0,
+                                        false => rect.bottom(),
+                                    };
+                                    card_state.underline.paint(
+                                        ui.painter(),
+                                        rect.x_range(),
+                                        y,
+                                        colour,
+                                    );
                                 }
 
                                 if state.selected_word == (paragraph_idx, word_idx) {
                                     if state.scroll_to_current_word_requested {
                                         ui.scroll_to_rect(rect, None);
                                     }
-                                    ui.painter().rect_filled(
-                                        rect,
-                                        egui::CornerRadius::ZERO,
-                                        selection_highlight,
-                                    );
+                                    if monochrome {
+                                        ui.painter().rect_stroke(
+                                            rect.expand(2.0),
+                                            egui::CornerRadius::ZERO,
+                                            egui::Stroke::new(2.0, Color32::WHITE),
+                                            egui::StrokeKind::Outside,
+                                        );
+                                    } else {
+                                        ui.painter().rect_filled(
+                                            rect,
+                                            egui::CornerRadius::ZERO,
+                                            selection_highlight,
+                                        );
+                                    }
                                 }
                             }
                         });
@@ -508,17 +1542,23 @@ impl OcrWindow {
                         ui.add_space(paragraph_spacing);
                     }
 
-                    if state.word_rects.is_empty() {
-                        state.word_rects = word_rects;
-                    }
+                    // laid out again every frame, so the rects follow resizes, zooming and scrolling
+                    state.word_rects = word_rects;
                 });
         }
 
-        fn definition_panel_ui(win: &mut OcrWindow, ui: &mut egui::Ui, services: &Services) {
+        fn definition_panel_ui(
+            win: &mut OcrWindow,
+            ui: &mut egui::Ui,
+            services: &Services,
+            pin_board: &PinBoard,
+        ) {
             let State::Ready(state) = &mut win.state else {
                 panic!("invariant broken: show_without_rects should only be called when self.state is Some!");
             };
 
+            let mut expand_clicked = false;
+
             match &state.selected_word().definition {
                 None => {}
                 Some(word) => {
@@ -527,9 +1567,12 @@ impl OcrWindow {
 
                     let card_state = services.srs.card_state(state.selected_word());
 
-                    let card_colour = {
-                        let [r, g, b] = card_state.colour;
-                        Color32::from_rgb(r, g, b)
+                    let card_colour = match win.config.monochrome {
+                        true => Color32::WHITE,
+                        false => {
+                            let [r, g, b] = card_state.colour;
+                            Color32::from_rgb(r, g, b)
+                        }
                     };
 
                     ui.columns_const(|[col1, col2]| {
@@ -563,34 +1606,161 @@ impl OcrWindow {
                             .color(Color32::from_white_alpha(192)),
                     ));
 
+                    if let Some(reading) = state.counter_reading() {
+                        ui.add(egui::Label::new(
+                            egui::RichText::new(format!(
+                                "{} → {reading}",
+                                state.selected_word().text.plain_text()
+                            ))
+                            .size(text_size)
+                            .color(Color32::from_white_alpha(192)),
+                        ));
+                    }
+
+                    if pin_board.is_pinned(word) {
+                        ui.add(egui::Label::new(
+                            egui::RichText::new("Pinned")
+                                .size(text_size)
+                                .color(Color32::from_white_alpha(192))
+                                .italics(),
+                        ));
+                    }
+
                     ui.separator();
 
+                    if !word.monolingual_meanings.is_empty() {
+                        ui.add(egui::Label::new(
+                            egui::RichText::new(match state.monolingual {
+                                true => "国語辞典",
+                                false => "英和辞典",
+                            })
+                            .size(text_size * 0.75)
+                            .color(Color32::from_white_alpha(128)),
+                        ));
+                    }
+
+                    let glosses = word.glosses(state.monolingual);
+                    let truncated = state.meanings_truncated(win.config.max_meanings);
+                    let shown = match truncated {
+                        true => win.config.max_meanings,
+                        false => glosses.len(),
+                    };
+
+                    let focused_meaning = match state.focus {
+                        FocusArea::Meanings(idx) if win.config.keyboard_focus_mode => Some(idx),
+                        _ => None,
+                    };
+
                     egui::ScrollArea::vertical().show(ui, |ui| {
-                        for meaning in &word.meanings {
-                            ui.add(egui::Label::new(
+                        for (idx, meaning) in glosses[..shown].iter().enumerate() {
+                            let response = ui.add(egui::Label::new(
                                 egui::RichText::new(format!("・{meaning}"))
                                     .size(text_size)
                                     .color(Color32::WHITE),
                             ));
+                            if focused_meaning == Some(idx) {
+                                focus_ring(ui, response.rect);
+                                response.scroll_to_me(None);
+                            }
+                        }
+
+                        if truncated {
+                            let response = ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(format!(
+                                        "+{} more (Y)",
+                                        glosses.len() - shown
+                                    ))
+                                    .size(text_size)
+                                    .color(Color32::from_white_alpha(128)),
+                                )
+                                .sense(egui::Sense::click()),
+                            );
+                            if focused_meaning == Some(shown) {
+                                focus_ring(ui, response.rect);
+                                response.scroll_to_me(None);
+                            }
+                            expand_clicked = response.clicked();
+                        }
+
+                        for path in &word.images {
+                            ui.add_space(8.0);
+                            win.definition_images
+                                .show(ui, path, vec2(ui.available_width(), 240.0));
                         }
                     });
                 }
             }
+
+            if expand_clicked {
+                state.expanded_word = Some(state.selected_word);
+            }
         }
 
-        fn bottom_bar_ui(_win: &mut OcrWindow, ui: &mut egui::Ui) {
+        fn closing_ui(ui: &mut egui::Ui, pending_operations: &[String], remaining: Duration) {
+            let label = |ui: &mut egui::Ui, text: &str, size: f32, colour: Color32| {
+                ui.add(egui::Label::new(
+                    egui::RichText::new(text).size(size).color(colour),
+                ));
+            };
+
+            ui.ctx().request_repaint_after(Duration::from_millis(100));
+
+            ui.vertical_centered(|ui| {
+                ui.add_space(ui.available_height() / 4.0);
+
+                label(ui, "Waiting for Pending Operations", 40.0, Color32::WHITE);
+                ui.add_space(16.0);
+
+                for operation in pending_operations {
+                    label(ui, operation, 24.0, Color32::WHITE);
+                }
+
+                ui.add_space(16.0);
+                label(
+                    ui,
+                    &format!(
+                        "The window will close once they are finished, or in {}s.",
+                        remaining.as_secs() + 1
+                    ),
+                    20.0,
+                    Color32::from_white_alpha(192),
+                );
+            });
+        }
+
+        fn bottom_bar_ui(win: &mut OcrWindow, ui: &mut egui::Ui) {
             let dpad = egui::include_image!("../../assets/controller_icons/steamdeck_dpad.svg");
             let rtrigger =
                 egui::include_image!("../../assets/controller_icons/steamdeck_button_r2.svg");
             let a = egui::include_image!("../../assets/controller_icons/steamdeck_button_a.svg");
             let b = egui::include_image!("../../assets/controller_icons/steamdeck_button_b.svg");
+            let x = egui::include_image!("../../assets/controller_icons/steamdeck_button_x.svg");
+            let y = egui::include_image!("../../assets/controller_icons/steamdeck_button_y.svg");
+            let view =
+                egui::include_image!("../../assets/controller_icons/steamdeck_button_view.svg");
+            let ltrigger =
+                egui::include_image!("../../assets/controller_icons/steamdeck_button_l2.svg");
+            let lstick_press =
+                egui::include_image!("../../assets/controller_icons/steamdeck_stick_l_press.svg");
+            let options =
+                egui::include_image!("../../assets/controller_icons/steamdeck_button_options.svg");
+            let dpad_horizontal =
+                egui::include_image!("../../assets/controller_icons/steamdeck_dpad_horizontal.svg");
+            let lbumper =
+                egui::include_image!("../../assets/controller_icons/steamdeck_button_l1.svg");
+            let rbumper =
+                egui::include_image!("../../assets/controller_icons/steamdeck_button_r1.svg");
+            let rstick_press =
+                egui::include_image!("../../assets/controller_icons/steamdeck_stick_r_press.svg");
 
             let glyph_size = 48.0;
             let text_size = 20.0;
             let spacing = 24.0;
 
             let add_glyph = |ui: &mut egui::Ui, glyph| {
-                ui.add(egui::Image::new(glyph).fit_to_exact_size(vec2(glyph_size, glyph_size)));
+                ui.add(egui::Image::new(glyph).fit_to_exact_size(vec2(glyph_size, glyph_size)))
+                    .rect
             };
 
             let add_label = |ui: &mut egui::Ui, text| {
@@ -598,12 +1768,88 @@ impl OcrWindow {
                     egui::RichText::new(text)
                         .size(text_size)
                         .color(Color32::WHITE),
-                ));
+                ))
+                .rect
+            };
+
+            let focused_action = match &win.state {
+                State::Ready(state) if win.config.keyboard_focus_mode => match state.focus {
+                    FocusArea::Actions(idx) => Some(FocusAction::ALL[idx]),
+                    _ => None,
+                },
+                _ => None,
+            };
+            let ring = |ui: &mut egui::Ui, rect: Rect, action| {
+                if focused_action == Some(action) {
+                    focus_ring(ui, rect);
+                }
             };
 
             // pushing things downwards a little bit
             ui.add_space(8.0);
 
+            let closing = match &win.state {
+                State::Ready(state) => state.closing.is_some(),
+                _ => false,
+            };
+
+            if closing {
+                ui.horizontal_centered(|ui| {
+                    ui.with_layout(
+                        egui::Layout::right_to_left(egui::Align::Center).with_cross_justify(true),
+                        |ui| {
+                            ui.add_space(spacing);
+                            add_label(ui, "CLOSE NOW");
+                            add_glyph(ui, b);
+
+                            ui.add_space(spacing);
+                            add_label(ui, "STAY");
+                            add_glyph(ui, a);
+                        },
+                    );
+                });
+                return;
+            }
+
+            let review = match &win.state {
+                State::Ready(state) => state.quick_review.as_ref(),
+                _ => None,
+            };
+
+            if let Some(review) = review {
+                ui.horizontal_centered(|ui| {
+                    ui.with_layout(
+                        egui::Layout::left_to_right(egui::Align::Center).with_cross_justify(true),
+                        |ui| {
+                            if review.is_revealed() {
+                                ui.add_space(spacing);
+                                add_glyph(ui, dpad_horizontal);
+                                add_label(ui, "FAIL / PASS");
+                            }
+                        },
+                    );
+
+                    ui.with_layout(
+                        egui::Layout::right_to_left(egui::Align::Center).with_cross_justify(true),
+                        |ui| {
+                            ui.add_space(spacing);
+                            add_label(ui, "END REVIEW");
+                            add_glyph(ui, b);
+
+                            if !review.is_finished() {
+                                ui.add_space(spacing);
+                                match review.is_revealed() {
+                                    false => add_label(ui, "SHOW ANSWER"),
+                                    true => add_label(ui, "PASS"),
+                                };
+                                add_glyph(ui, a);
+                            }
+                        },
+                    );
+                });
+                return;
+            }
+
             ui.horizontal_centered(|ui| {
                 ui.with_layout(
                     egui::Layout::left_to_right(egui::Align::Center).with_cross_justify(true),
@@ -615,6 +1861,54 @@ impl OcrWindow {
                         ui.add_space(spacing);
                         add_glyph(ui, rtrigger);
                         add_label(ui, "SKIP IRRELEVANT WORDS");
+
+                        ui.add_space(spacing);
+                        let rect = add_glyph(ui, ltrigger).union(add_label(ui, "HARDEST"));
+                        ring(ui, rect, FocusAction::Hardest);
+
+                        let auto_scroll = match &win.state {
+                            State::Ready(state) => state.auto_scroll.as_ref(),
+                            _ => None,
+                        };
+                        ui.add_space(spacing);
+                        let rect = add_glyph(ui, lstick_press).union(add_label(
+                            ui,
+                            match auto_scroll {
+                                None => "AUTO-SCROLL",
+                                Some(auto_scroll) if auto_scroll.paused => "RESUME AUTO-SCROLL",
+                                Some(_) => "STOP AUTO-SCROLL",
+                            },
+                        ));
+                        ring(ui, rect, FocusAction::AutoScroll);
+
+                        ui.add_space(spacing);
+                        let rect = add_glyph(ui, view).union(add_label(ui, "SHARE"));
+                        ring(ui, rect, FocusAction::Share);
+
+                        ui.add_space(spacing);
+                        let rect = add_glyph(ui, options).union(add_label(ui, "REVIEW"));
+                        ring(ui, rect, FocusAction::Review);
+
+                        let paragraphs_per_page = win.config.paragraphs_per_page;
+                        let pages = match &win.state {
+                            State::Ready(state) => Some((
+                                state.page(paragraphs_per_page),
+                                state.page_count(paragraphs_per_page),
+                            )),
+                            _ => None,
+                        };
+                        if let Some((page, page_count)) = pages.filter(|(_, count)| *count > 1) {
+                            ui.add_space(spacing);
+                            let rect = add_glyph(ui, lbumper);
+                            ring(ui, rect, FocusAction::PreviousPage);
+                            ui.label(
+                                egui::RichText::new(format!("PAGE {} / {page_count}", page + 1))
+                                    .size(text_size)
+                                    .color(Color32::WHITE),
+                            );
+                            let rect = add_glyph(ui, rbumper);
+                            ring(ui, rect, FocusAction::NextPage);
+                        }
                     },
                 );
 
@@ -622,12 +1916,48 @@ impl OcrWindow {
                     egui::Layout::right_to_left(egui::Align::Center).with_cross_justify(true),
                     |ui| {
                         ui.add_space(spacing);
-                        add_label(ui, "EXIT");
-                        add_glyph(ui, b);
+                        let rect = add_label(ui, "EXIT").union(add_glyph(ui, b));
+                        ring(ui, rect, FocusAction::Exit);
 
                         ui.add_space(spacing);
-                        add_label(ui, "ADD TO DECK");
-                        add_glyph(ui, a);
+                        let rect = add_label(ui, "ADD TO DECK").union(add_glyph(ui, a));
+                        ring(ui, rect, FocusAction::AddToDeck);
+
+                        ui.add_space(spacing);
+                        let rect = add_label(ui, "PIN").union(add_glyph(ui, x));
+                        ring(ui, rect, FocusAction::Pin);
+
+                        let truncated = match &win.state {
+                            State::Ready(state) => {
+                                state.meanings_truncated(win.config.max_meanings)
+                            }
+                            _ => false,
+                        };
+                        if truncated {
+                            ui.add_space(spacing);
+                            let rect = add_label(ui, "MORE").union(add_glyph(ui, y));
+                            ring(ui, rect, FocusAction::More);
+                        }
+
+                        // switching dictionaries is only possible when the word is in both
+                        let monolingual = match &win.state {
+                            State::Ready(state) => {
+                                state.can_toggle_dictionary().then_some(state.monolingual)
+                            }
+                            _ => None,
+                        };
+                        if let Some(monolingual) = monolingual {
+                            ui.add_space(spacing);
+                            let rect = add_label(
+                                ui,
+                                match monolingual {
+                                    true => "JP-EN",
+                                    false => "JP-JP",
+                                },
+                            )
+                            .union(add_glyph(ui, rstick_press));
+                            ring(ui, rect, FocusAction::Dictionary);
+                        }
                     },
                 );
             });
@@ -0,0 +1,159 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use eframe::egui::{self, vec2, Color32};
+
+use crate::{
+    services::{ServiceJob, Services},
+    word::Word,
+};
+
+/// How long a notification about changed card states stays on screen.
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+
+/// Reloads the card states of the words in the OCR window every now and then, so that changes made
+/// elsewhere (eg. reviews done on another device) are shown, and tells the user what changed.
+pub struct CardStateWatcher {
+    last_refresh: Instant,
+    /// Name of each word's card state when the capture finished loading.
+    at_capture: Vec<String>,
+    /// Spelling and reading of the words mined in this window, whose new card state isn't news.
+    mined: HashSet<(String, String)>,
+    job: Option<ServiceJob<Result<()>>>,
+    /// Message describing the changes since capture, and when it was last updated.
+    toast: Option<(String, Instant)>,
+}
+
+impl CardStateWatcher {
+    pub fn new(words: &[Vec<Word>], services: &Services) -> Self {
+        Self {
+            last_refresh: Instant::now(),
+            at_capture: words
+                .iter()
+                .flatten()
+                .map(|word| services.srs.card_state(word).name.clone())
+                .collect(),
+            mined: HashSet::new(),
+            job: None,
+            toast: None,
+        }
+    }
+
+    /// Record that `word` was added to the user's deck from this window.
+    pub fn word_mined(&mut self, word: &Word) {
+        if let Some(definition) = &word.definition {
+            self.mined
+                .insert((definition.spelling.clone(), definition.reading.clone()));
+        }
+    }
+
+    /// Reload the card states of `words` if `interval` elapsed since they were last loaded, and
+    /// describe the changes since capture once they are. An `interval` of 0 never reloads them.
+    pub fn poll(&mut self, words: &[Vec<Word>], services: &mut Services, interval: Duration) {
+        if let Some(job) = &mut self.job {
+            match job.try_wait() {
+                Ok(None) => return,
+                Ok(Some(Ok(()))) => {
+                    let message = describe_changes(words, &self.at_capture, &self.mined, services);
+                    // changes are counted since capture, so only new ones are worth showing again
+                    let last_message = self.toast.as_ref().map(|(message, _)| message);
+                    if let Some(message) = message.filter(|message| Some(message) != last_message) {
+                        log::info!("{message}");
+                        self.toast = Some((message, Instant::now()));
+                    }
+                }
+                Ok(Some(Err(e))) | Err(e) => log::warn!("Failed to refresh card states: {e:#}"),
+            }
+
+            self.job = None;
+            self.last_refresh = Instant::now();
+        }
+
+        if interval.is_zero() || self.last_refresh.elapsed() < interval {
+            return;
+        }
+
+        self.job = Some(
+            services
+                .srs
+                .load_card_states(words.iter().flatten().cloned().collect()),
+        );
+    }
+
+    /// Show the message describing the last changes at the top of the window, if they are recent.
+    pub fn show_toast(&self, ctx: &egui::Context) {
+        let Some((message, shown_at)) = &self.toast else {
+            return;
+        };
+        if shown_at.elapsed() >= TOAST_DURATION {
+            return;
+        }
+
+        egui::Area::new(egui::Id::new("card state toast"))
+            .anchor(egui::Align2::CENTER_TOP, vec2(0.0, 24.0))
+            .show(ctx, |ui| {
+                egui::Frame::new()
+                    .fill(Color32::from_black_alpha(224))
+                    .stroke(egui::Stroke::new(1.0, Color32::from_white_alpha(64)))
+                    .corner_radius(8.0)
+                    .inner_margin(egui::Margin::symmetric(16, 8))
+                    .show(ui, |ui| {
+                        ui.label(
+                            egui::RichText::new(message)
+                                .size(20.0)
+                                .color(Color32::WHITE),
+                        );
+                    });
+            });
+
+        ctx.request_repaint_after(TOAST_DURATION.saturating_sub(shown_at.elapsed()));
+    }
+}
+
+/// Describe how the card states of `words` changed since they were `at_capture`, eg. "3 words
+/// became due since capture", leaving out the `mined` words. Returns `None` if none changed.
+fn describe_changes(
+    words: &[Vec<Word>],
+    at_capture: &[String],
+    mined: &HashSet<(String, String)>,
+    services: &Services,
+) -> Option<String> {
+    // words appearing several times are only counted once
+    let mut changed: BTreeMap<String, HashSet<(&str, &str)>> = BTreeMap::new();
+
+    for (word, before) in words.iter().flatten().zip(at_capture) {
+        let Some(definition) = &word.definition else {
+            continue;
+        };
+
+        let key = (definition.spelling.clone(), definition.reading.clone());
+        if mined.contains(&key) {
+            continue;
+        }
+
+        let after = services.srs.card_state(word).name.clone();
+        if after != *before {
+            changed
+                .entry(after)
+                .or_default()
+                .insert((&definition.spelling, &definition.reading));
+        }
+    }
+
+    if changed.is_empty() {
+        return None;
+    }
+
+    let changes: Vec<String> = changed
+        .iter()
+        .map(|(state, words)| match words.len() {
+            1 => format!("1 word became {state}"),
+            n => format!("{n} words became {state}"),
+        })
+        .collect();
+
+    Some(format!("{} since capture", changes.join(", ")))
+}
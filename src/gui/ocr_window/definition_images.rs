@@ -0,0 +1,65 @@
+use std::{collections::HashMap, time::Duration};
+
+use anyhow::{Context, Result};
+use eframe::egui::{self, TextureHandle, Vec2};
+
+use crate::services::ServiceJob;
+
+/// An image illustrating a definition, identified by its path.
+enum DefinitionImage {
+    Loading(ServiceJob<Result<egui::ColorImage>>),
+    Loaded(TextureHandle),
+    /// The image couldn't be loaded, the error was already logged.
+    Failed,
+}
+
+/// Images shown under the selected word's meanings, loaded in the background the first time they
+/// are shown, and kept for the following OCR windows.
+#[derive(Default)]
+pub struct DefinitionImages(HashMap<String, DefinitionImage>);
+
+impl DefinitionImages {
+    /// Show the image at `path`, scaled down to fit in `max_size`. A spinner is shown while it loads.
+    pub fn show(&mut self, ui: &mut egui::Ui, path: &str, max_size: Vec2) {
+        let image = self.0.entry(path.to_owned()).or_insert_with(|| {
+            let path = path.to_owned();
+            DefinitionImage::Loading(ServiceJob::new(move || load(&path)))
+        });
+
+        if let DefinitionImage::Loading(job) = image {
+            match job.try_wait() {
+                Ok(None) => {
+                    ui.spinner();
+                    ui.ctx().request_repaint_after(Duration::from_millis(100));
+                    return;
+                }
+                Ok(Some(Ok(color_image))) => {
+                    *image = DefinitionImage::Loaded(ui.ctx().load_texture(
+                        path,
+                        color_image,
+                        Default::default(),
+                    ));
+                }
+                Ok(Some(Err(e))) | Err(e) => {
+                    log::warn!("{e:#}");
+                    *image = DefinitionImage::Failed;
+                }
+            }
+        }
+
+        if let DefinitionImage::Loaded(texture) = image {
+            ui.add(egui::Image::new(&*texture).max_size(max_size));
+        }
+    }
+}
+
+fn load(path: &str) -> Result<egui::ColorImage> {
+    let image = image::open(path)
+        .with_context(|| format!("Could not load definition image `{path}`"))?
+        .to_rgba8();
+
+    Ok(egui::ColorImage::from_rgba_unmultiplied(
+        [image.width() as usize, image.height() as usize],
+        image.as_flat_samples().as_slice(),
+    ))
+}
@@ -0,0 +1,37 @@
+use crate::{services::Services, word::Word};
+
+/// Frequency rank assumed for words whose frequency is unknown.
+const UNKNOWN_FREQUENCY_RANK: u64 = 100_000;
+
+/// Difficulty of a paragraph, between 0 and 1: the share of unknown words in the paragraph, with
+/// each word weighted by how rare it is. Returns `None` if no word in the paragraph has a definition.
+pub fn paragraph_difficulty(paragraph: &[Word], services: &Services) -> Option<f32> {
+    let mut unknown = 0.0;
+    let mut total = 0.0;
+
+    for word in paragraph {
+        let Some(definition) = &word.definition else {
+            continue;
+        };
+
+        let rank = definition.frequency.unwrap_or(UNKNOWN_FREQUENCY_RANK);
+        let weight = ((rank + 1) as f32).ln();
+
+        total += weight;
+        if services.srs.card_state(word).is_relevant {
+            unknown += weight;
+        }
+    }
+
+    (total > 0.0).then(|| unknown / total)
+}
+
+/// Index of the paragraph with the highest difficulty, if any paragraph has one.
+pub fn hardest_paragraph(words: &[Vec<Word>], services: &Services) -> Option<usize> {
+    words
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, paragraph)| Some((idx, paragraph_difficulty(paragraph, services)?)))
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(idx, _)| idx)
+}
@@ -0,0 +1,96 @@
+use eframe::egui::{self, Color32, CornerRadius, Rect, Stroke};
+
+use super::input_state::{InputState, Key};
+
+/// The part of the OCR window which has the keyboard focus, when keyboard focus mode is enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FocusArea {
+    /// The text, where the arrow keys move the selected word.
+    #[default]
+    Words,
+    /// The selected word's meanings, and the index of the focused one. The index past the last
+    /// meaning shown is the "more" entry, if the meanings are truncated.
+    Meanings(usize),
+    /// The bottom bar, and the index of the focused action in `FocusAction::ALL`.
+    Actions(usize),
+}
+
+impl FocusArea {
+    /// The next area, when pressing Tab.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Words => Self::Meanings(0),
+            Self::Meanings(_) => Self::Actions(0),
+            Self::Actions(_) => Self::Words,
+        }
+    }
+
+    /// The previous area, when pressing Shift+Tab.
+    pub fn previous(self) -> Self {
+        match self {
+            Self::Words => Self::Actions(0),
+            Self::Meanings(_) => Self::Words,
+            Self::Actions(_) => Self::Meanings(0),
+        }
+    }
+}
+
+/// An action of the bottom bar which can be focused and activated with the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusAction {
+    Hardest,
+    AutoScroll,
+    Share,
+    Review,
+    PreviousPage,
+    NextPage,
+    Dictionary,
+    More,
+    Pin,
+    AddToDeck,
+    Exit,
+}
+
+impl FocusAction {
+    /// Every action, in the order they are shown in from left to right.
+    pub const ALL: &'static [Self] = &[
+        Self::Hardest,
+        Self::AutoScroll,
+        Self::Share,
+        Self::Review,
+        Self::PreviousPage,
+        Self::NextPage,
+        Self::Dictionary,
+        Self::More,
+        Self::Pin,
+        Self::AddToDeck,
+        Self::Exit,
+    ];
+
+    /// The key which triggers this action.
+    pub fn key(self, input: &mut InputState) -> &mut Key {
+        match self {
+            Self::Hardest => &mut input.hardest_paragraph,
+            Self::AutoScroll => &mut input.auto_scroll,
+            Self::Share => &mut input.share,
+            Self::Review => &mut input.review,
+            Self::PreviousPage => &mut input.previous_page,
+            Self::NextPage => &mut input.next_page,
+            Self::Dictionary => &mut input.toggle_dictionary,
+            Self::More => &mut input.expand,
+            Self::Pin => &mut input.pin,
+            Self::AddToDeck => &mut input.add_to_deck,
+            Self::Exit => &mut input.exit,
+        }
+    }
+}
+
+/// Draw a focus ring around `rect`.
+pub fn focus_ring(ui: &egui::Ui, rect: Rect) {
+    ui.painter().rect_stroke(
+        rect.expand(4.0),
+        CornerRadius::same(4),
+        Stroke::new(2.0, Color32::WHITE),
+        egui::StrokeKind::Outside,
+    );
+}
@@ -12,7 +12,18 @@ pub struct InputState {
     pub right: Key,
     pub skip_irrelevant: Key,
     pub add_to_deck: Key,
+    pub pin: Key,
+    pub expand: Key,
+    pub share: Key,
+    pub review: Key,
+    pub hardest_paragraph: Key,
+    pub auto_scroll: Key,
     pub exit: Key,
+    pub next_focus: Key,
+    pub previous_focus: Key,
+    pub previous_page: Key,
+    pub toggle_dictionary: Key,
+    pub next_page: Key,
     pub scroll_left: f32,
     pub scroll_right: f32,
 }
@@ -40,7 +51,16 @@ impl InputState {
             update_key(&mut self.left, K::ArrowLeft, B::DPadLeft);
             update_key(&mut self.right, K::ArrowRight, B::DPadRight);
             update_key(&mut self.add_to_deck, K::Enter, B::South);
+            update_key(&mut self.pin, K::P, B::West);
+            update_key(&mut self.expand, K::Y, B::North);
+            update_key(&mut self.share, K::S, B::Select);
+            update_key(&mut self.review, K::R, B::Start);
+            update_key(&mut self.hardest_paragraph, K::H, B::LeftTrigger2);
+            update_key(&mut self.auto_scroll, K::Space, B::LeftThumb);
             update_key(&mut self.exit, K::Escape, B::East);
+            update_key(&mut self.toggle_dictionary, K::D, B::RightThumb);
+            update_key(&mut self.previous_page, K::PageUp, B::LeftTrigger);
+            update_key(&mut self.next_page, K::PageDown, B::RightTrigger);
         }
 
         let skip_irrelevant_pressed = ctx.input(|input| {
@@ -51,6 +71,12 @@ impl InputState {
 
         self.skip_irrelevant.change_state(skip_irrelevant_pressed);
 
+        let (tab_pressed, shift_pressed) =
+            ctx.input(|input| (input.key_down(egui::Key::Tab), input.modifiers.shift));
+        self.next_focus.change_state(tab_pressed && !shift_pressed);
+        self.previous_focus
+            .change_state(tab_pressed && shift_pressed);
+
         while let Some(event) = gilrs.next_event() {
             match event.event {
                 gilrs::EventType::AxisChanged(gilrs::Axis::LeftStickY, value, _) => {
@@ -96,6 +122,12 @@ impl Key {
         }
     }
 
+    /// Press the key for this frame, as if it was pressed by the user.
+    pub fn press(&mut self) {
+        self.is_pressed = Some(Instant::now());
+        self.was_consumed = false;
+    }
+
     /// Whether the key is currently pressed.
     pub fn is_pressed(&self) -> bool {
         self.is_pressed.is_some()
@@ -0,0 +1,180 @@
+use eframe::egui::{self, Color32};
+
+use crate::{
+    services::Services,
+    word::{PartOfSpeechFilter, Word},
+};
+
+/// A quick flashcard review of the unknown words in the current capture, shown inside the OCR window.
+pub struct QuickReview {
+    cards: Vec<Word>,
+    current: usize,
+    revealed: bool,
+    passed: usize,
+}
+
+/// Something the user did during a `QuickReview`.
+#[derive(Debug, Clone, Copy)]
+pub enum ReviewAction {
+    /// Show the back of the current card.
+    Reveal,
+    /// Grade the current card, and move on to the next one.
+    Answer { passed: bool },
+}
+
+impl QuickReview {
+    /// Start a review of the relevant words in `words` which aren't skipped by `filter`, each word
+    /// appearing once. Returns `None` if there is nothing to review.
+    pub fn new(
+        words: &[Vec<Word>],
+        services: &Services,
+        filter: &PartOfSpeechFilter,
+    ) -> Option<Self> {
+        let mut cards: Vec<Word> = Vec::new();
+
+        for word in words.iter().flatten() {
+            let Some(definition) = &word.definition else {
+                continue;
+            };
+
+            let already_added = cards.iter().any(|card| {
+                card.definition.as_ref().is_some_and(|card| {
+                    card.spelling == definition.spelling && card.reading == definition.reading
+                })
+            });
+
+            if !already_added && services.srs.card_state(word).is_relevant && !filter.skips(word) {
+                cards.push(word.clone());
+            }
+        }
+
+        (!cards.is_empty()).then_some(Self {
+            cards,
+            current: 0,
+            revealed: false,
+            passed: 0,
+        })
+    }
+
+    /// Whether every card has been graded.
+    pub fn is_finished(&self) -> bool {
+        self.current >= self.cards.len()
+    }
+
+    /// Whether the back of the current card is shown.
+    pub fn is_revealed(&self) -> bool {
+        self.revealed
+    }
+
+    /// Apply an action. Returns the graded word and whether it was passed, if the action graded a card.
+    pub fn apply(&mut self, action: ReviewAction) -> Option<(Word, bool)> {
+        if self.is_finished() {
+            return None;
+        }
+
+        match action {
+            ReviewAction::Reveal => {
+                self.revealed = true;
+                None
+            }
+            ReviewAction::Answer { .. } if !self.revealed => None,
+            ReviewAction::Answer { passed } => {
+                let word = self.cards[self.current].clone();
+                self.current += 1;
+                self.revealed = false;
+                if passed {
+                    self.passed += 1;
+                }
+                Some((word, passed))
+            }
+        }
+    }
+
+    /// Show the current card. Returns the action chosen by the user, if they clicked a button.
+    pub fn show_ui(&self, ui: &mut egui::Ui) -> Option<ReviewAction> {
+        let spelling_size = 64.0;
+        let text_size = 24.0;
+
+        let mut action = None;
+
+        let label = |ui: &mut egui::Ui, text: &str, size: f32, colour: Color32| {
+            ui.add(egui::Label::new(
+                egui::RichText::new(text).size(size).color(colour),
+            ));
+        };
+
+        ui.vertical_centered(|ui| {
+            ui.add_space(ui.available_height() / 4.0);
+
+            if self.is_finished() {
+                label(ui, "Review Finished", spelling_size, Color32::WHITE);
+                label(
+                    ui,
+                    &format!("{} / {} passed", self.passed, self.cards.len()),
+                    text_size,
+                    Color32::from_white_alpha(192),
+                );
+                return;
+            }
+
+            label(
+                ui,
+                &format!("{} / {}", self.current + 1, self.cards.len()),
+                text_size,
+                Color32::from_white_alpha(128),
+            );
+
+            let Some(definition) = &self.cards[self.current].definition else {
+                return;
+            };
+
+            label(ui, &definition.spelling, spelling_size, Color32::WHITE);
+
+            if !self.revealed {
+                if ui
+                    .button(egui::RichText::new("Show Answer").size(text_size))
+                    .clicked()
+                {
+                    action = Some(ReviewAction::Reveal);
+                }
+                return;
+            }
+
+            label(
+                ui,
+                &definition.reading,
+                text_size,
+                Color32::from_white_alpha(192),
+            );
+
+            ui.separator();
+
+            for meaning in &definition.meanings {
+                label(ui, &format!("・{meaning}"), text_size, Color32::WHITE);
+            }
+
+            ui.add_space(text_size);
+
+            ui.horizontal(|ui| {
+                // center the two buttons
+                ui.add_space(ui.available_width() / 2.0 - 100.0);
+
+                if ui
+                    .button(egui::RichText::new("Fail").size(text_size))
+                    .clicked()
+                {
+                    action = Some(ReviewAction::Answer { passed: false });
+                }
+                ui.add_space(32.0);
+                if ui
+                    .button(egui::RichText::new("Pass").size(text_size))
+                    .clicked()
+                {
+                    action = Some(ReviewAction::Answer { passed: true });
+                }
+            });
+        });
+
+        action
+    }
+}
@@ -37,9 +37,10 @@ impl<'a> TextWithRubyWidget<'a> {
 
 impl<'a> Widget for TextWithRubyWidget<'a> {
     fn ui(self, ui: &mut egui::Ui) -> egui::Response {
-        let mut job = egui::text::LayoutJob::default();
-
-        job.wrap = egui::text::TextWrapping::truncate_at_width(ui.available_width());
+        let mut job = egui::text::LayoutJob {
+            wrap: egui::text::TextWrapping::truncate_at_width(ui.available_width()),
+            ..Default::default()
+        };
 
         for fragment in &self.text_with_ruby.0 {
             job.append(
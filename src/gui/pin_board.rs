@@ -0,0 +1,101 @@
+use anyhow::Context;
+use eframe::egui;
+
+use crate::{data, mining::MiningJob, EframeApp};
+
+/// Shows the words on the pin board, along with actions to mine, export or discard them.
+pub fn pin_board_ui(app: &mut EframeApp, ui: &mut egui::Ui) {
+    if app.pin_board.pins.is_empty() {
+        ui.label("No pinned words. Press X (or P) in the OCR window to pin the selected word.");
+        return;
+    }
+
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(
+                app.pin_board_jobs.is_empty(),
+                egui::Button::new("Add All to Deck"),
+            )
+            .on_hover_text("Words are removed from the pin board once they have been added.")
+            .clicked()
+        {
+            for pin in &app.pin_board.pins {
+                app.pin_board_jobs.push(MiningJob::new(
+                    &app.config.hooks,
+                    &pin.word,
+                    pin.sentence.clone(),
+                    1,
+                ));
+            }
+        }
+
+        let export_path = data::data_dir().map(|dir| dir.join("pins.tsv"));
+        let hover_text = match &export_path {
+            Ok(path) => format!("Write the pin board to `{}`.", path.display()),
+            Err(e) => format!("{e}"),
+        };
+
+        if ui.button("Export TSV").on_hover_text(hover_text).clicked() {
+            let result = export_path.and_then(|path| {
+                std::fs::create_dir_all(path.parent().unwrap_or(&path))?;
+                std::fs::write(&path, app.pin_board.to_tsv())
+                    .with_context(|| format!("Could not write to `{}`", path.display()))?;
+                log::info!("Exported pin board to `{}`", path.display());
+                Ok(())
+            });
+
+            if let Err(e) = result {
+                app.popups.error(e.context("Failed to export pin board"));
+            }
+        }
+
+        if ui.button("Copy TSV").clicked() {
+            ui.ctx().copy_text(app.pin_board.to_tsv());
+        }
+
+        if ui.button("Clear").clicked() {
+            app.pin_board.clear();
+        }
+    });
+
+    if !app.pin_board_jobs.is_empty() {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.label(format!(
+                "Adding {} word(s) to your deck...",
+                app.pin_board_jobs.len()
+            ));
+        });
+    }
+
+    let mut remove = None;
+
+    egui::Grid::new("pin board")
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            for (idx, pin) in app.pin_board.pins.iter().enumerate() {
+                let Some(definition) = &pin.word.definition else {
+                    continue;
+                };
+
+                ui.label(format!("{}【{}】", definition.spelling, definition.reading))
+                    .on_hover_text(&pin.sentence);
+                ui.label(
+                    definition
+                        .meanings
+                        .first()
+                        .map(String::as_str)
+                        .unwrap_or(""),
+                );
+                if ui.small_button("Remove").clicked() {
+                    remove = Some(idx);
+                }
+                ui.end_row();
+            }
+        });
+
+    if let Some(idx) = remove {
+        app.pin_board.remove_at(idx);
+    }
+}
@@ -0,0 +1,192 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use eframe::egui::{self, Color32, CornerRadius, Pos2, Rect, Stroke, TextureHandle};
+use image::RgbaImage;
+
+use crate::{
+    services::{ocr::ParagraphOrder, ServiceJob},
+    WINDOW_TITLE,
+};
+
+/// Selections smaller than this (in points) are ignored, so that a click doesn't capture a single pixel.
+const MIN_SELECTION_SIZE: f32 = 8.0;
+
+/// A fullscreen overlay showing a screenshot, on which the user drags a rectangle to choose which
+/// part of it should be sent to OCR.
+pub struct RegionSelect {
+    image: RgbaImage,
+    texture: TextureHandle,
+    pub paragraph_order: ParagraphOrder,
+    /// Time taken by the screenshot, recorded in the benchmark log.
+    pub capture_time: Duration,
+    /// Checks that the OCR service can be reached while the user is selecting the region.
+    pub readiness: ServiceJob<Result<()>>,
+    drag_start: Option<Pos2>,
+    /// The rectangle being dragged, in screen coordinates.
+    selection: Option<Rect>,
+    frame_count: u64,
+}
+
+impl RegionSelect {
+    pub fn new(
+        ctx: &egui::Context,
+        image: RgbaImage,
+        capture_time: Duration,
+        paragraph_order: ParagraphOrder,
+        readiness: ServiceJob<Result<()>>,
+    ) -> Self {
+        let color_image = egui::ColorImage::from_rgba_unmultiplied(
+            [image.width() as usize, image.height() as usize],
+            image.as_flat_samples().as_slice(),
+        );
+        let texture = ctx.load_texture(
+            "region select background",
+            color_image,
+            egui::TextureOptions::LINEAR,
+        );
+
+        Self {
+            image,
+            texture,
+            paragraph_order,
+            capture_time,
+            readiness,
+            drag_start: None,
+            selection: None,
+            frame_count: 0,
+        }
+    }
+
+    /// Show the overlay. Returns `None` while the user is still selecting, `Some(None)` if they
+    /// cancelled, and `Some(Some(image))` with the selected part of the screenshot once they are done.
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<Option<RgbaImage>> {
+        let mut result = None;
+
+        ctx.show_viewport_immediate(
+            egui::ViewportId(egui::Id::new("region_select_viewport")),
+            egui::ViewportBuilder {
+                title: Some(WINDOW_TITLE.to_owned()),
+                fullscreen: Some(true),
+                ..Default::default()
+            },
+            |ctx, _| {
+                if self.frame_count == 1 {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+                }
+
+                egui::CentralPanel::default()
+                    .frame(egui::Frame::NONE)
+                    .show(ctx, |ui| {
+                        let screen = ctx.available_rect();
+                        let response = ui.allocate_rect(screen, egui::Sense::drag());
+
+                        ui.painter().image(
+                            self.texture.id(),
+                            screen,
+                            Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+                            Color32::WHITE,
+                        );
+
+                        if response.drag_started() {
+                            self.drag_start = response.interact_pointer_pos();
+                        }
+
+                        if let Some(selection) = self
+                            .drag_start
+                            .zip(response.interact_pointer_pos())
+                            .map(|(a, b)| Rect::from_two_pos(a, b))
+                        {
+                            self.selection = Some(selection);
+                        }
+                        let selection = self.selection;
+
+                        // dim everything outside of the selection
+                        let dimming = Color32::from_black_alpha(160);
+                        match selection {
+                            None => {
+                                ui.painter()
+                                    .rect_filled(screen, CornerRadius::ZERO, dimming);
+                            }
+                            Some(selection) => {
+                                for rect in [
+                                    Rect::from_min_max(
+                                        screen.min,
+                                        Pos2::new(screen.max.x, selection.min.y),
+                                    ),
+                                    Rect::from_min_max(
+                                        Pos2::new(screen.min.x, selection.max.y),
+                                        screen.max,
+                                    ),
+                                    Rect::from_min_max(
+                                        Pos2::new(screen.min.x, selection.min.y),
+                                        Pos2::new(selection.min.x, selection.max.y),
+                                    ),
+                                    Rect::from_min_max(
+                                        Pos2::new(selection.max.x, selection.min.y),
+                                        Pos2::new(screen.max.x, selection.max.y),
+                                    ),
+                                ] {
+                                    ui.painter().rect_filled(rect, CornerRadius::ZERO, dimming);
+                                }
+                                ui.painter().rect_stroke(
+                                    selection,
+                                    CornerRadius::ZERO,
+                                    Stroke::new(2.0, Color32::WHITE),
+                                    egui::StrokeKind::Outside,
+                                );
+                            }
+                        }
+
+                        if self.drag_start.is_none() {
+                            ui.painter().text(
+                                screen.center(),
+                                egui::Align2::CENTER_CENTER,
+                                "Drag to select the text to read. Escape to cancel.",
+                                egui::FontId::proportional(32.0),
+                                Color32::WHITE,
+                            );
+                        }
+
+                        if response.drag_stopped() {
+                            self.drag_start = None;
+                            self.selection = None;
+
+                            if let Some(selection) = selection.filter(|selection| {
+                                selection.width() >= MIN_SELECTION_SIZE
+                                    && selection.height() >= MIN_SELECTION_SIZE
+                            }) {
+                                result = Some(Some(self.crop(screen, selection)));
+                            }
+                        }
+
+                        ctx.input(|input| {
+                            if input.key_pressed(egui::Key::Escape)
+                                || input.viewport().close_requested()
+                            {
+                                result = Some(None);
+                            }
+                        });
+                    });
+            },
+        );
+
+        self.frame_count += 1;
+
+        result
+    }
+
+    /// The part of the screenshot under `selection`, when the screenshot is shown over `screen`.
+    fn crop(&self, screen: Rect, selection: Rect) -> RgbaImage {
+        let scale_x = self.image.width() as f32 / screen.width();
+        let scale_y = self.image.height() as f32 / screen.height();
+
+        let selection = selection.intersect(screen);
+        let x = ((selection.min.x - screen.min.x) * scale_x) as u32;
+        let y = ((selection.min.y - screen.min.y) * scale_y) as u32;
+        let width = ((selection.width() * scale_x) as u32).min(self.image.width() - x);
+        let height = ((selection.height() * scale_y) as u32).min(self.image.height() - y);
+
+        image::imageops::crop_imm(&self.image, x, y, width.max(1), height.max(1)).to_image()
+    }
+}
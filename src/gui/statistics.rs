@@ -0,0 +1,81 @@
+use eframe::egui;
+
+use crate::{stats, EframeApp};
+
+/// Number of days covered by the "Last 7 Days" column.
+const DAYS_PER_WEEK: u64 = 7;
+
+const READING_TIME_HOVER_TEXT: &str = "Time the OCR window was open, attributed to the capture preset used, or\nto \"Unknown\" for captures made with the OCR hotkey. Text sent from the\nbrowser is attributed to \"Browser\".";
+
+/// Shows the user's activity today, over the last week and overall, along with the time spent
+/// reading each game.
+pub fn statistics_ui(app: &mut EframeApp, ui: &mut egui::Ui) {
+    let stats = &app.stats;
+    let today = stats::today();
+    let periods = [
+        today..=today,
+        today.saturating_sub(DAYS_PER_WEEK - 1)..=today,
+        0..=today,
+    ];
+
+    egui::Grid::new("statistics")
+        .num_columns(4)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("");
+            for heading in ["Today", "Last 7 Days", "All Time"] {
+                ui.strong(heading);
+            }
+            ui.end_row();
+
+            ui.label("Words Mined:");
+            for days in &periods {
+                let mined = days
+                    .clone()
+                    .map(|day| stats.mined_on(day).count())
+                    .sum::<usize>();
+                ui.label(mined.to_string());
+            }
+            ui.end_row();
+
+            ui.label("Characters Read:");
+            for days in &periods {
+                let characters: u64 = stats
+                    .characters_read
+                    .range(days.clone())
+                    .map(|(_, n)| n)
+                    .sum();
+                ui.label(characters.to_string());
+            }
+            ui.end_row();
+
+            ui.label("Reading Time:")
+                .on_hover_text(READING_TIME_HOVER_TEXT);
+            for days in &periods {
+                let seconds = stats
+                    .reading_time(days.clone())
+                    .iter()
+                    .map(|(_, s)| s)
+                    .sum();
+                ui.label(stats::format_duration(seconds));
+            }
+            ui.end_row();
+
+            // games are listed by all time reading time
+            for (game, all_time) in stats.reading_time(0..=today) {
+                ui.label(format!("・{game}"));
+                for days in &periods[..2] {
+                    let seconds = stats
+                        .reading_time(days.clone())
+                        .into_iter()
+                        .find(|(other, _)| *other == game)
+                        .map_or(0, |(_, seconds)| seconds);
+                    ui.label(stats::format_duration(seconds));
+                }
+                ui.label(stats::format_duration(all_time));
+                ui.end_row();
+            }
+        });
+
+    ui.label(format!("Current streak: {} day(s)", stats.streak(today)));
+}
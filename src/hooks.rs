@@ -0,0 +1,183 @@
+use std::{
+    io::{Read, Write},
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context, Result};
+use eframe::egui;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{services::ServiceJob, word::Word};
+
+/// How long a hook script may run before it is killed.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// User scripts run at different stages of the pipeline. Each hook is the path to an executable,
+/// which receives a JSON object on stdin and (for hooks transforming data) writes one to stdout.
+/// Empty paths are ignored.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HookConfig {
+    /// Run after OCR. Receives `{"stage": "post_ocr", "paragraphs": [...]}`, outputs `{"paragraphs": [...]}`.
+    pub post_ocr: String,
+    /// Run before adding a word to the user's deck. Receives `{"stage": "pre_mine", "word": {...}, "sentence": "..."}`,
+    /// outputs `{"word": {...}}`.
+    pub pre_mine: String,
+    /// Run after a word was added to the user's deck. Receives `{"stage": "post_mine", "word": {...}, "sentence": "..."}`,
+    /// its output is ignored.
+    pub post_mine: String,
+}
+
+#[derive(Deserialize)]
+struct PostOcrOutput {
+    paragraphs: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct PreMineOutput {
+    word: Word,
+}
+
+impl HookConfig {
+    /// Run the post-OCR hook on the given paragraphs, if one is set.
+    pub fn post_ocr(&self, paragraphs: Vec<String>) -> ServiceJob<Result<Vec<String>>> {
+        let command = self.post_ocr.trim().to_owned();
+
+        ServiceJob::new(move || {
+            if command.is_empty() {
+                return Ok(paragraphs);
+            }
+
+            let output: PostOcrOutput = run_json(
+                &command,
+                &json!({ "stage": "post_ocr", "paragraphs": paragraphs }),
+            )
+            .context("Post-OCR hook failed")?;
+
+            Ok(output.paragraphs)
+        })
+    }
+
+    /// Run the pre-mine hook on the given word, if one is set.
+    pub fn pre_mine(&self, word: &Word, sentence: &str) -> ServiceJob<Result<Word>> {
+        let command = self.pre_mine.trim().to_owned();
+        let word = word.clone();
+        let input = json!({ "stage": "pre_mine", "word": word, "sentence": sentence });
+
+        ServiceJob::new(move || {
+            if command.is_empty() {
+                return Ok(word);
+            }
+
+            let output: PreMineOutput =
+                run_json(&command, &input).context("Pre-mine hook failed")?;
+
+            Ok(output.word)
+        })
+    }
+
+    /// Run the post-mine hook in the background, if one is set. Errors are logged.
+    pub fn post_mine(&self, word: &Word, sentence: &str) {
+        let command = self.post_mine.trim().to_owned();
+
+        if command.is_empty() {
+            return;
+        }
+
+        let input = json!({ "stage": "post_mine", "word": word, "sentence": sentence });
+
+        std::thread::spawn(move || {
+            if let Err(e) = run(&command, &input) {
+                log::warn!("Post-mine hook failed: {e:#}");
+            }
+        });
+    }
+
+    /// Show the UI for setting the hook scripts.
+    pub fn show_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Executables run at different stages, receiving JSON on stdin and writing JSON to stdout. Leave empty to disable.");
+
+        let hooks = [
+            ("Post-OCR:", &mut self.post_ocr, "Transforms the recognised text.\nInput: {\"stage\": \"post_ocr\", \"paragraphs\": [...]}\nOutput: {\"paragraphs\": [...]}"),
+            ("Pre-Mine:", &mut self.pre_mine, "Transforms a word before it is added to your deck.\nInput: {\"stage\": \"pre_mine\", \"word\": {...}, \"sentence\": \"...\"}\nOutput: {\"word\": {...}}"),
+            ("Post-Mine:", &mut self.post_mine, "Notified after a word was added to your deck.\nInput: {\"stage\": \"post_mine\", \"word\": {...}, \"sentence\": \"...\"}"),
+        ];
+
+        for (label, command, hover_text) in hooks {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                ui.text_edit_singleline(command).on_hover_text(hover_text);
+            });
+        }
+    }
+}
+
+/// Run a hook script, writing `input` to its stdin and parsing its stdout as JSON.
+fn run_json<T: DeserializeOwned>(command: &str, input: &serde_json::Value) -> Result<T> {
+    let stdout = run(command, input)?;
+
+    serde_json::from_slice(&stdout)
+        .with_context(|| format!("`{command}` did not output valid JSON"))
+}
+
+/// Run a hook script, writing `input` to its stdin. Returns its stdout.
+fn run(command: &str, input: &serde_json::Value) -> Result<Vec<u8>> {
+    let mut child = Command::new(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Could not start `{command}`"))?;
+
+    let mut stdin = child.stdin.take().unwrap();
+    let input = serde_json::to_vec(input)?;
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+
+    // read outputs in separate threads so the child can't block on a full pipe
+    let mut stdout = child.stdout.take().unwrap();
+    let stdout_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        stdout.read_to_end(&mut buf).map(|_| buf)
+    });
+    let mut stderr = child.stderr.take().unwrap();
+    let stderr_reader = std::thread::spawn(move || {
+        let mut buf = String::new();
+        let _ = stderr.read_to_string(&mut buf);
+        buf
+    });
+
+    let started_at = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        if started_at.elapsed() > HOOK_TIMEOUT {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(anyhow!(
+                "`{command}` did not finish within {}s",
+                HOOK_TIMEOUT.as_secs()
+            ));
+        }
+
+        std::thread::sleep(Duration::from_millis(10));
+    };
+
+    let _ = writer.join();
+    let stdout = stdout_reader
+        .join()
+        .map_err(|_| anyhow!("Could not read output of `{command}`"))??;
+    let stderr = stderr_reader.join().unwrap_or_default();
+
+    if !status.success() {
+        return Err(anyhow!(
+            "`{command}` exited with {status}: {}",
+            stderr.trim()
+        ));
+    }
+
+    Ok(stdout)
+}
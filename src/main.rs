@@ -1,19 +1,59 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
 
 use anyhow::{anyhow, Context, Result};
+use bridge::BridgeServer;
+use capture::CaptureTarget;
+use companion::CompanionServer;
 use config::{AppConfig, Config};
+use data::DataFile;
 use eframe::{
     egui::{self, vec2},
-    epaint::text::{FontInsert, InsertFontFamily},
     CreationContext,
 };
-use global_hotkey::{hotkey::HotKey, GlobalHotKeyEvent, GlobalHotKeyManager};
-use gui::{config_window::show_config_window, ocr_window::OcrWindow, popups::Popups};
-use services::Services;
+use global_hotkey::{hotkey::HotKey, GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+use gui::{
+    config_window::show_config_window,
+    countdown::PendingCapture,
+    hotkey_conflict::{HotkeyConflict, Resolution},
+    lookup::Lookup,
+    ocr_window::OcrWindow,
+    popups::Popups,
+    region_select::RegionSelect,
+};
+use image::RgbaImage;
+use mining::{MiningJob, MiningStatus};
+use pins::PinBoard;
+use safe_mode::SafeMode;
+use services::{http, jpdb, ocr::ParagraphOrder, ServiceJob, Services, PENDING_JOBS_TIMEOUT};
+use stats::Stats;
+use stitching::StitchBuffer;
+use word::Word;
 
+pub mod accent;
+pub mod benchmark;
+pub mod bridge;
+pub mod capture;
+pub mod companion;
 pub mod config;
+pub mod data;
+pub mod diagnostics;
+pub mod discord;
+pub mod fonts;
+pub mod furigana;
 pub mod gui;
+pub mod hooks;
+pub mod mining;
+pub mod numbers;
+pub mod pins;
+pub mod safe_mode;
 pub mod services;
+pub mod share_card;
+pub mod stats;
+pub mod stitching;
+pub mod summary;
 pub mod word;
 
 const WINDOW_TITLE: &str = concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION"));
@@ -22,9 +62,15 @@ const WINDOW_H: f32 = 600.0;
 const WINDOW_H_MIN: f32 = 300.0;
 const WINDOW_H_MAX: f32 = 720.0;
 
+/// If the wall clock advances this much more than the monotonic clock between two frames, we
+/// assume the system was suspended in between.
+const SUSPEND_DETECTION_THRESHOLD: Duration = Duration::from_secs(5);
+
 fn main() -> Result<()> {
     pretty_env_logger::init();
 
+    let safe_mode = SafeMode::detect();
+
     // TODO: nicely show any errors returned from main to the user somehow
     eframe::run_native(
         "app_name",
@@ -48,8 +94,8 @@ fn main() -> Result<()> {
             },
             ..Default::default()
         },
-        Box::new(|cc| {
-            EframeApp::new(cc)
+        Box::new(move |cc| {
+            EframeApp::new(cc, safe_mode)
                 .map(|app| -> Box<dyn eframe::App> { Box::new(app) })
                 .map_err(|e| panic!("{e:?}"))
         }),
@@ -59,54 +105,226 @@ fn main() -> Result<()> {
 
 pub struct EframeApp {
     config: AppConfig,
-    ocr_hotkey: HotKey,
+    /// Why deckocr is running in safe mode, if it is.
+    safe_mode: Option<SafeMode>,
+    hotkey_manager: &'static GlobalHotKeyManager,
+    /// Registered hotkeys and what they do.
+    hotkeys: Vec<(HotKey, HotkeyAction)>,
+    /// Hotkeys which could not be registered, waiting for the user to decide what to do about them.
+    hotkey_conflicts: Vec<HotkeyConflict>,
+    /// Capture presets and their hotkeys when the hotkeys were last registered, so that they are
+    /// registered again when the user edits them.
+    preset_hotkeys: Vec<(u64, HotKey)>,
     services: Services,
+    warm_up_jobs: Vec<(&'static str, ServiceJob<Result<()>>)>,
 
     ocr_window: Option<OcrWindow>,
+    /// Id of the capture preset used for the last capture, whose settings apply to the OCR window
+    /// and which the time spent reading its text is attributed to. `None` if the OCR hotkey was used.
+    capture_preset: Option<u64>,
+    /// Capture requested with a hotkey, waiting for its countdown to run out.
+    pending_capture: Option<PendingCapture>,
+    /// When the OCR hotkey was pressed, if it is currently held and holding it selects a region.
+    ocr_hotkey_pressed_at: Option<Instant>,
+    /// Overlay for choosing which part of the screen to read, shown after holding the OCR hotkey.
+    region_select: Option<RegionSelect>,
+    /// Pages captured with the stitch hotkey, to be combined with the next OCR capture.
+    stitch_buffer: StitchBuffer,
+    companion: Option<CompanionServer>,
+    bridge: Option<BridgeServer>,
+
+    /// Result of the last font coverage check, shown in the configuration window.
+    font_coverage: Option<Vec<fonts::Coverage>>,
+
+    /// Text typed in the main window to be looked up without capturing the screen.
+    lookup: Lookup,
+    /// Words pinned by the user, kept across sessions.
+    pin_board: PinBoard,
+    /// Jobs created when the user adds the words on the pin board to their deck.
+    pin_board_jobs: Vec<MiningJob>,
+    /// Jobs adding a word to the user's deck, taken from OCR windows which were replaced or closed
+    /// before they finished.
+    mining_jobs: Vec<MiningJob>,
+    /// When the user asked to quit while jobs were still pending, if they did.
+    quit_requested_at: Option<Instant>,
+    /// Statistics about the user's mining, kept across sessions.
+    stats: Stats,
+
+    /// Clock readings taken on the previous frame, used to detect suspend/resume cycles.
+    last_frame_time: (Instant, SystemTime),
 
     popups: Popups,
 }
 
 impl EframeApp {
-    pub fn new(cc: &CreationContext) -> Result<Self> {
+    pub fn new(cc: &CreationContext, safe_mode: Option<SafeMode>) -> Result<Self> {
         egui_extras::install_image_loaders(&cc.egui_ctx);
 
-        // FIXME: some characters aren't being rendered properly with this font
-        cc.egui_ctx.add_font(FontInsert::new(
-            "M+",
-            egui::FontData::from_static(include_bytes!("../assets/fonts/MPLUS1-Regular.ttf")),
-            vec![InsertFontFamily {
-                family: egui::FontFamily::Proportional,
-                priority: egui::epaint::text::FontPriority::Highest,
-            }],
-        ));
+        let mut config = match safe_mode {
+            Some(_) => AppConfig::default(),
+            None => AppConfig::load().context("Could not load main configuration file")?,
+        };
+        config.assign_preset_ids();
+
+        let mut popups = Popups::default();
 
-        let config = AppConfig::load().context("Could not load main configuration file")?;
+        if let Err(e) = fonts::install_fonts(&cc.egui_ctx, &config) {
+            popups.error(e);
+        }
 
         // NOTE: this isn't documented, but GlobalHotKeyManager needs to stay alive for the entire duration of the program.
         let hotkey_manager = Box::leak(Box::new(
             GlobalHotKeyManager::new().context("Failed to initialise GlobalHotKeyManager")?,
         ));
-        let ocr_hotkey = HotKey::new(Some(config.hotkey_modifiers), config.hotkey_keycode);
-        hotkey_manager
-            .register(ocr_hotkey)
-            .context("Failed to register hotkey with GlobalHotKeyManager")?;
+        let to_register = match safe_mode {
+            Some(_) => Vec::new(),
+            None => hotkeys_from_config(&config),
+        };
+        let (hotkeys, conflicting_hotkeys): (Vec<_>, Vec<_>) = to_register
+            .into_iter()
+            .map(|(hotkey, action)| {
+                let result = hotkey_manager.register(hotkey).with_context(|| {
+                    format!("Failed to register hotkey `{hotkey}` with GlobalHotKeyManager")
+                });
+                (hotkey, action, result)
+            })
+            .partition(|(_, _, result)| result.is_ok());
+
+        let hotkeys: Vec<_> = hotkeys
+            .into_iter()
+            .map(|(hotkey, action, _)| (hotkey, action))
+            .collect();
 
-        let services = Services::new(&config).context("Failed to initialise services")?;
+        let taken: Vec<_> = hotkeys_from_config(&config)
+            .into_iter()
+            .map(|(hotkey, _)| hotkey)
+            .collect();
+        let hotkey_conflicts = conflicting_hotkeys
+            .into_iter()
+            .map(|(hotkey, action, result)| {
+                let e = result.unwrap_err();
+                log::warn!("{e:#}");
+                HotkeyConflict::new(hotkey, action, &e, hotkey_manager, &taken)
+            })
+            .collect();
+
+        let registered_presets = preset_hotkeys(&config);
+
+        // an unreadable pin board shouldn't prevent deckocr from starting, the user is told and
+        // starts over with an empty one
+        let pin_board = PinBoard::load()
+            .context("Could not load pin board, starting with an empty one")
+            .unwrap_or_else(|e| {
+                popups.error(e);
+                PinBoard::default()
+            });
+        let stats = Stats::load().context("Could not load statistics");
+        // in safe mode, data files which can't be read are ignored (and they aren't saved on exit)
+        let stats = match safe_mode {
+            Some(_) => stats.unwrap_or_else(|e| {
+                log::error!("{e:#}");
+                Stats::default()
+            }),
+            None => stats?,
+        };
+
+        let mut services = match safe_mode {
+            Some(_) => Services::disabled(),
+            None => Services::new(&config).context("Failed to initialise services")?,
+        };
+        let warm_up_jobs = match config.warm_up_services && safe_mode.is_none() {
+            true => services.warm_up(),
+            false => Vec::new(),
+        };
 
         Ok(Self {
             config,
-            ocr_hotkey,
+            safe_mode,
+            hotkey_manager,
+            hotkeys,
+            hotkey_conflicts,
+            preset_hotkeys: registered_presets,
             services,
+            warm_up_jobs,
 
             ocr_window: None,
+            capture_preset: None,
+            pending_capture: None,
+            ocr_hotkey_pressed_at: None,
+            region_select: None,
+            stitch_buffer: Default::default(),
+            companion: None,
+            bridge: None,
+
+            font_coverage: None,
+
+            lookup: Lookup::default(),
+            pin_board,
+            pin_board_jobs: Vec::new(),
+            mining_jobs: Vec::new(),
+            quit_requested_at: None,
+            stats,
 
-            popups: Default::default(),
+            last_frame_time: (Instant::now(), SystemTime::now()),
+
+            popups,
         })
     }
 
-    /// Runs when the OCR hotkey was pressed. Creates a new `OcrWindow` and sets it as the active OCR Window.
-    pub fn trigger_ocr(&mut self, ctx: &egui::Context) -> Result<()> {
+    /// Create a new `OcrWindow` for a screenshot which was already taken, and set it as the active OCR Window.
+    pub fn open_ocr_window(
+        &mut self,
+        ctx: &egui::Context,
+        image: RgbaImage,
+        capture_time: Duration,
+        paragraph_order: ParagraphOrder,
+    ) {
+        let preset = self
+            .capture_preset
+            .and_then(|id| self.config.capture_preset(id));
+        let game = preset.map_or(stats::UNKNOWN_GAME, |preset| &preset.name);
+        let game = game.to_owned();
+        let jpdb_account = preset.and_then(|preset| preset.jpdb_account.clone());
+
+        let mut config = self.config.clone();
+        config.paragraph_order = paragraph_order;
+        if let Some(preprocessing) = preset.and_then(|preset| preset.preprocessing) {
+            config.preprocessing = preprocessing;
+        }
+
+        let previous = self.take_ocr_window();
+        jpdb::use_capture_account(jpdb_account);
+        let mut ocr_window = OcrWindow::new(
+            ctx,
+            config,
+            image,
+            capture_time,
+            std::mem::take(&mut self.stitch_buffer),
+            &mut self.services,
+            previous,
+        );
+        ocr_window.game = game;
+        self.ocr_window = Some(ocr_window);
+    }
+
+    /// Take the active OCR window, if any, recording the time spent reading it in the statistics.
+    fn take_ocr_window(&mut self) -> Option<OcrWindow> {
+        let mut ocr_window = self.ocr_window.take()?;
+        self.stats
+            .record_reading_time(&ocr_window.game, ocr_window.created_at.elapsed());
+        // the window is dropped once replaced, so its pending job is kept here instead
+        self.mining_jobs.extend(ocr_window.take_add_to_deck_job());
+        jpdb::use_capture_account(None);
+        Some(ocr_window)
+    }
+
+    /// Runs when the OCR hotkey (or a preset's hotkey) was pressed. The capture is taken once the
+    /// OCR service is ready and the pre-capture countdown, if one is configured, ran out.
+    pub fn request_capture(
+        &mut self,
+        target: CaptureTarget,
+        paragraph_order: ParagraphOrder,
+    ) -> Result<()> {
         let currently_loading = self
             .ocr_window
             .as_ref()
@@ -118,30 +336,517 @@ impl EframeApp {
             return Ok(());
         }
 
-        let monitor = xcap::Monitor::all()?
-            .into_iter()
-            .find(|monitor| monitor.is_primary().unwrap_or(false))
-            .ok_or_else(|| anyhow!("No primary monitor found."))?;
+        self.pending_capture = Some(PendingCapture::new(
+            target,
+            paragraph_order,
+            // find out the OCR service is down before the user waits through the capture
+            self.services.ensure_ocr_ready(),
+            self.config.capture_delay_ms,
+            self.config
+                .settled_capture
+                .then(|| Duration::from_millis(self.config.settled_capture_interval_ms)),
+        ));
+
+        Ok(())
+    }
 
-        let image = monitor
-            .capture_image()
-            .context("Failed to capture primary monitor")?;
+    /// Runs when "Capture Now" was clicked in the configuration window. Captures the primary monitor
+    /// like the OCR hotkey does.
+    pub fn capture_now(&mut self) -> Result<()> {
+        self.capture_preset = None;
+        self.request_capture(CaptureTarget::PrimaryMonitor, self.config.paragraph_order)
+    }
 
-        self.ocr_window = Some(OcrWindow::new(
+    /// Runs when the OCR hotkey was held. Captures the primary monitor and lets the user choose
+    /// which part of it to read.
+    pub fn start_region_select(&mut self, ctx: &egui::Context) -> Result<()> {
+        let (image, capture_time) = self
+            .services
+            .take_screenshot(&CaptureTarget::PrimaryMonitor)?;
+        self.region_select = Some(RegionSelect::new(
             ctx,
-            self.config.clone(),
             image,
-            &mut self.services,
+            capture_time,
+            self.config.paragraph_order,
+            self.services.ensure_ocr_ready(),
         ));
 
         Ok(())
     }
+
+    /// Runs when the stitch hotkey was pressed. Captures the screen and starts extracting its text,
+    /// so that it can be stitched together with the next OCR capture.
+    pub fn trigger_stitch_capture(&mut self) -> Result<()> {
+        let (image, _) = self
+            .services
+            .take_screenshot(&CaptureTarget::PrimaryMonitor)?;
+        let image = self.config.preprocessing.apply(image);
+        self.stitch_buffer.push(self.services.ocr.ocr(image));
+
+        log::info!("Captured page {} for stitching", self.stitch_buffer.len());
+
+        Ok(())
+    }
+
+    /// Detects whether the system was suspended since the last frame, and if so restores the
+    /// things that don't survive a suspend/resume cycle.
+    fn detect_resume(&mut self) {
+        let (last_instant, last_system_time) = self.last_frame_time;
+        let now = (Instant::now(), SystemTime::now());
+        self.last_frame_time = now;
+
+        // `Instant` doesn't advance while the system is suspended, but `SystemTime` does
+        let monotonic_elapsed = now.0.duration_since(last_instant);
+        let Ok(wall_elapsed) = now.1.duration_since(last_system_time) else {
+            return;
+        };
+
+        if wall_elapsed.saturating_sub(monotonic_elapsed) < SUSPEND_DETECTION_THRESHOLD {
+            return;
+        }
+
+        log::info!(
+            "Detected resume from suspend (slept for about {}s)",
+            (wall_elapsed - monotonic_elapsed).as_secs()
+        );
+
+        for (hotkey, _) in &self.hotkeys {
+            let _ = self.hotkey_manager.unregister(*hotkey);
+            if let Err(e) = self.hotkey_manager.register(*hotkey).with_context(|| {
+                format!("Failed to re-register hotkey `{hotkey}` after resuming from suspend")
+            }) {
+                self.popups.error(e);
+            }
+        }
+
+        http::reset_connections();
+
+        if self.config.warm_up_services {
+            self.warm_up_jobs = self.services.warm_up();
+        }
+    }
+
+    /// Shows the dialog for the first unresolved hotkey conflict, and applies the user's choice.
+    fn show_hotkey_conflicts(&mut self, ctx: &egui::Context) {
+        let Some(conflict) = self.hotkey_conflicts.first_mut() else {
+            return;
+        };
+
+        let action_name = conflict.action.name(&self.config);
+        let Some(resolution) = conflict.show(ctx, &action_name) else {
+            return;
+        };
+
+        let conflict = self.hotkey_conflicts.remove(0);
+
+        match resolution {
+            Resolution::Ignore => {
+                log::info!("Continuing without hotkey `{}`", conflict.hotkey);
+            }
+            Resolution::UseHotkey(hotkey) => match self.hotkey_manager.register(hotkey) {
+                Ok(()) => {
+                    conflict.action.set_hotkey(&mut self.config, hotkey);
+                    self.hotkeys.push((hotkey, conflict.action));
+                }
+                Err(e) => self
+                    .popups
+                    .error(anyhow!(e).context(format!("Failed to register hotkey `{hotkey}`"))),
+            },
+        }
+    }
+
+    /// Registers the capture presets' hotkeys again when the user added, removed or edited
+    /// presets, so that the changes apply without restarting deckocr.
+    fn sync_preset_hotkeys(&mut self) {
+        let wanted = preset_hotkeys(&self.config);
+        if wanted == self.preset_hotkeys {
+            return;
+        }
+
+        let previous = std::mem::replace(&mut self.preset_hotkeys, wanted.clone());
+        let stale: Vec<_> = previous
+            .into_iter()
+            .filter(|preset| !wanted.contains(preset))
+            .map(|(id, hotkey)| (hotkey, HotkeyAction::Preset(id)))
+            .collect();
+
+        for (hotkey, action) in &stale {
+            if let Some(idx) = self
+                .hotkeys
+                .iter()
+                .position(|registered| registered == &(*hotkey, *action))
+            {
+                self.hotkeys.remove(idx);
+                // another action may still be using the same hotkey
+                if !self.hotkeys.iter().any(|(other, _)| other == hotkey) {
+                    if let Err(e) = self.hotkey_manager.unregister(*hotkey) {
+                        log::warn!("Failed to unregister hotkey `{hotkey}`: {e}");
+                    }
+                }
+            }
+        }
+        self.hotkey_conflicts
+            .retain(|conflict| !stale.contains(&(conflict.hotkey, conflict.action)));
+
+        let taken: Vec<_> = hotkeys_from_config(&self.config)
+            .into_iter()
+            .map(|(hotkey, _)| hotkey)
+            .collect();
+        for (id, hotkey) in wanted {
+            let action = HotkeyAction::Preset(id);
+            if self.hotkeys.contains(&(hotkey, action)) {
+                continue;
+            }
+
+            match self.hotkey_manager.register(hotkey) {
+                Ok(()) => self.hotkeys.push((hotkey, action)),
+                Err(e) => {
+                    let e = anyhow!(e).context(format!(
+                        "Failed to register hotkey `{hotkey}` with GlobalHotKeyManager"
+                    ));
+                    log::warn!("{e:#}");
+                    self.hotkey_conflicts.push(HotkeyConflict::new(
+                        hotkey,
+                        action,
+                        &e,
+                        self.hotkey_manager,
+                        &taken,
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Logs the results of warm-up requests as they finish.
+    fn poll_warm_up_jobs(&mut self) {
+        self.warm_up_jobs
+            .retain_mut(|(service, job)| match job.try_wait() {
+                Ok(None) => true,
+                Ok(Some(Ok(()))) => {
+                    log::info!("Warmed up {service} service");
+                    false
+                }
+                Err(e) | Ok(Some(Err(e))) => {
+                    log::warn!("Failed to warm up {service} service: {e:#}");
+                    false
+                }
+            });
+    }
+
+    /// Runs after a word was added to the user's deck.
+    fn word_mined(&mut self, word: &Word, sentence: &str) {
+        self.stats.record_mined(word, sentence);
+        self.config.hooks.post_mine(word, sentence);
+        self.config.discord.word_mined(word, sentence);
+    }
+
+    /// Descriptions of the jobs which haven't finished yet, and would be lost if deckocr was quit.
+    fn pending_operations(&self) -> Vec<String> {
+        let pin_board = self.pin_board_jobs.iter().map(|job| {
+            format!(
+                "Adding {} to your deck (pin board)",
+                job.word.text.plain_text()
+            )
+        });
+        let mining = self
+            .mining_jobs
+            .iter()
+            .map(|job| format!("Adding {} to your deck", job.word.text.plain_text()));
+        let ocr_window = self
+            .ocr_window
+            .iter()
+            .flat_map(|ocr_window| ocr_window.pending_operations());
+
+        pin_board.chain(mining).chain(ocr_window).collect()
+    }
+
+    /// Keeps deckocr open while jobs are still pending after the user asked to quit, so they don't
+    /// get dropped, and shows them in a dialog until they finish or time out.
+    fn handle_quit_request(&mut self, ctx: &egui::Context) {
+        let pending_operations = self.pending_operations();
+
+        if ctx.input(|input| input.viewport().close_requested())
+            && self.quit_requested_at.is_none()
+            && !pending_operations.is_empty()
+        {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.quit_requested_at = Some(Instant::now());
+        }
+
+        let Some(quit_requested_at) = self.quit_requested_at else {
+            return;
+        };
+
+        if pending_operations.is_empty() {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            return;
+        }
+
+        if quit_requested_at.elapsed() >= PENDING_JOBS_TIMEOUT {
+            log::warn!(
+                "Gave up waiting for pending operations: {}",
+                pending_operations.join(", ")
+            );
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            return;
+        }
+
+        let remaining = PENDING_JOBS_TIMEOUT.saturating_sub(quit_requested_at.elapsed());
+        ctx.request_repaint_after(Duration::from_millis(100));
+
+        egui::Modal::new(egui::Id::new("pending_operations_modal")).show(ctx, |ui| {
+            ui.heading("Waiting for Pending Operations");
+            ui.label(format!(
+                "deckocr will quit once these are finished, or in {}s:",
+                remaining.as_secs() + 1
+            ));
+
+            for operation in &pending_operations {
+                ui.label(format!("・{operation}"));
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button("Quit Now").clicked() {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+                if ui.button("Cancel").clicked() {
+                    self.quit_requested_at = None;
+                }
+            });
+        });
+    }
+
+    /// Records the words added to the user's deck by the jobs of previous OCR windows.
+    fn poll_mining_jobs(&mut self) {
+        let mut mined = Vec::new();
+        let mut errors = Vec::new();
+
+        self.mining_jobs
+            .retain_mut(|job| match job.poll(&mut self.services) {
+                Ok(MiningStatus::Pending) => true,
+                Ok(MiningStatus::Added(word)) => {
+                    mined.push((*word, job.sentence.clone()));
+                    false
+                }
+                Ok(MiningStatus::NotAdded) => false,
+                Err(e) => {
+                    errors.push(e);
+                    false
+                }
+            });
+
+        for (word, sentence) in mined {
+            self.word_mined(&word, &sentence);
+        }
+
+        for e in errors {
+            self.popups.error(e);
+        }
+    }
+
+    /// Saves the pin board as soon as it changes, so that pins aren't lost if deckocr is killed.
+    fn save_pin_board(&mut self) {
+        if self.safe_mode.is_some() {
+            return;
+        }
+
+        if let Err(e) = self.pin_board.save_if_changed() {
+            self.popups.error(e.context("Failed to save pin board"));
+        }
+    }
+
+    /// Removes words from the pin board once they have been added to the user's deck. Words which
+    /// weren't actually added (in dry-run mode) are kept.
+    fn poll_pin_board_jobs(&mut self) {
+        let mut added = Vec::new();
+        let mut errors = Vec::new();
+
+        self.pin_board_jobs
+            .retain_mut(|job| match job.poll(&mut self.services) {
+                Ok(MiningStatus::Pending) => true,
+                Ok(MiningStatus::Added(word)) => {
+                    added.push((job.word.clone(), *word, job.sentence.clone()));
+                    false
+                }
+                Ok(MiningStatus::NotAdded) => false,
+                Err(e) => {
+                    errors.push(e);
+                    false
+                }
+            });
+
+        // pins are removed by the word's original definition, the hook may have changed it
+        for (pinned, word, sentence) in added {
+            if let Some(definition) = &pinned.definition {
+                self.pin_board.remove(definition);
+            }
+            self.word_mined(&word, &sentence);
+        }
+
+        for e in errors {
+            self.popups
+                .error(e.context("Failed to add pinned word to deck"));
+        }
+    }
+
+    /// Starts or stops the companion server so that it matches the configuration.
+    fn update_companion(&mut self) {
+        let wanted_port = self
+            .config
+            .companion_enabled
+            .then_some(self.config.companion_port);
+
+        if self.companion.as_ref().map(CompanionServer::port) != wanted_port {
+            self.companion = None;
+
+            if let Some(port) = wanted_port {
+                match CompanionServer::start(port) {
+                    Ok(server) => self.companion = Some(server),
+                    Err(e) => {
+                        self.config.companion_enabled = false;
+                        self.popups.error(e);
+                    }
+                }
+            }
+        }
+
+        if let Some(companion) = &mut self.companion {
+            companion.update(self.ocr_window.as_ref(), &self.services);
+        }
+    }
+
+    /// Starts or stops the browser bridge so that it matches the configuration, and opens the
+    /// text it received in the OCR window.
+    fn update_bridge(&mut self, ctx: &egui::Context) {
+        let wanted_port = self
+            .config
+            .bridge_enabled
+            .then_some(self.config.bridge_port);
+
+        if self.bridge.as_ref().map(BridgeServer::port) != wanted_port {
+            self.bridge = None;
+
+            if let Some(port) = wanted_port {
+                match BridgeServer::start(port) {
+                    Ok(server) => self.bridge = Some(server),
+                    Err(e) => {
+                        self.config.bridge_enabled = false;
+                        self.popups.error(e);
+                    }
+                }
+            }
+        }
+
+        if let Some(text) = self.bridge.as_ref().and_then(BridgeServer::take_text) {
+            let previous = self.take_ocr_window();
+            let mut ocr_window = OcrWindow::from_text(
+                ctx,
+                self.config.clone(),
+                bridge::paragraphs(&text),
+                previous,
+            );
+            ocr_window.game = bridge::BRIDGE_GAME.to_owned();
+            self.ocr_window = Some(ocr_window);
+        }
+    }
+}
+
+/// What a registered hotkey does.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HotkeyAction {
+    /// Capture the primary monitor and show the OCR window.
+    Ocr,
+    /// Capture a page to be stitched together with the next OCR capture.
+    StitchPage,
+    /// Capture the target of the capture preset with the given id and show the OCR window.
+    Preset(u64),
+}
+
+impl HotkeyAction {
+    /// Name of the action, as shown to the user.
+    pub fn name(&self, config: &AppConfig) -> String {
+        match self {
+            Self::Ocr => "OCR".to_owned(),
+            Self::StitchPage => "stitch page".to_owned(),
+            Self::Preset(id) => match config.capture_preset(*id) {
+                Some(preset) => format!("capture preset `{}`", preset.name),
+                None => "capture preset".to_owned(),
+            },
+        }
+    }
+
+    /// Change the hotkey for this action in the given `AppConfig`.
+    pub fn set_hotkey(&self, config: &mut AppConfig, hotkey: HotKey) {
+        let (modifiers, keycode) = match self {
+            Self::Ocr => (&mut config.hotkey_modifiers, &mut config.hotkey_keycode),
+            Self::StitchPage => (
+                &mut config.stitch_hotkey_modifiers,
+                &mut config.stitch_hotkey_keycode,
+            ),
+            Self::Preset(id) => match config.capture_preset_mut(*id) {
+                Some(preset) => (&mut preset.hotkey_modifiers, &mut preset.hotkey_keycode),
+                None => return,
+            },
+        };
+
+        *modifiers = hotkey.mods;
+        *keycode = hotkey.key;
+    }
+}
+
+/// List the hotkeys set in the given `AppConfig`.
+fn hotkeys_from_config(config: &AppConfig) -> Vec<(HotKey, HotkeyAction)> {
+    let mut hotkeys = vec![
+        (
+            HotKey::new(Some(config.hotkey_modifiers), config.hotkey_keycode),
+            HotkeyAction::Ocr,
+        ),
+        (
+            HotKey::new(
+                Some(config.stitch_hotkey_modifiers),
+                config.stitch_hotkey_keycode,
+            ),
+            HotkeyAction::StitchPage,
+        ),
+    ];
+
+    for (id, hotkey) in preset_hotkeys(config) {
+        hotkeys.push((hotkey, HotkeyAction::Preset(id)));
+    }
+
+    hotkeys
+}
+
+/// List the capture presets set in the given `AppConfig` along with their hotkeys.
+fn preset_hotkeys(config: &AppConfig) -> Vec<(u64, HotKey)> {
+    config
+        .capture_presets
+        .iter()
+        .map(|preset| (preset.id, preset.hotkey()))
+        .collect()
 }
 
 impl eframe::App for EframeApp {
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
-        if let Err(e) = self.config.save() {
-            log::error!("Error while saving configuration file: `{e}`");
+        // safe mode's default configuration shouldn't replace the user's, it is only saved when
+        // they ask for it in the configuration window
+        if self.safe_mode.is_none() {
+            if let Err(e) = self.config.save() {
+                log::error!("Error while saving configuration file: `{e}`");
+            }
+
+            if let Err(e) = self.pin_board.save() {
+                log::error!("Error while saving pin board: `{e}`");
+            }
+
+            self.take_ocr_window();
+            if let Err(e) = self.stats.save() {
+                log::error!("Error while saving statistics: `{e}`");
+            }
+        }
+
+        for e in self.services.terminate() {
+            log::error!("Error while terminating services: `{e:#}`");
         }
     }
 
@@ -150,24 +855,152 @@ impl eframe::App for EframeApp {
 
         ctx.set_zoom_factor(self.config.zoom_factor);
 
+        self.detect_resume();
+        http::set_dry_run(self.config.dry_run);
+
+        if self.safe_mode.is_none() {
+            self.sync_preset_hotkeys();
+        }
+
         if let Ok(event) = GlobalHotKeyEvent::receiver().try_recv() {
-            if event.id == self.ocr_hotkey.id && event.state == global_hotkey::HotKeyState::Pressed
-            {
-                if let Err(e) = self.trigger_ocr(ctx) {
+            let action = self
+                .hotkeys
+                .iter()
+                .find(|(hotkey, _)| hotkey.id == event.id)
+                .map(|(_, action)| *action);
+
+            let hold_threshold = Duration::from_millis(self.config.hold_threshold_ms);
+
+            match action {
+                Some(HotkeyAction::Ocr) => self.capture_preset = None,
+                Some(HotkeyAction::Preset(id)) => self.capture_preset = Some(id),
+                Some(HotkeyAction::StitchPage) | None => {}
+            }
+
+            let result = match (action, event.state) {
+                // holding the OCR hotkey shows the region selection overlay when it is released,
+                // tapping it captures as usual
+                (Some(HotkeyAction::Ocr), HotKeyState::Pressed) if self.config.hold_for_region => {
+                    self.ocr_hotkey_pressed_at.get_or_insert_with(Instant::now);
+                    Ok(())
+                }
+                (Some(HotkeyAction::Ocr), HotKeyState::Released) if self.config.hold_for_region => {
+                    match self.ocr_hotkey_pressed_at.take() {
+                        None => Ok(()),
+                        Some(at) if at.elapsed() >= hold_threshold => self.start_region_select(ctx),
+                        Some(_) => self.request_capture(
+                            CaptureTarget::PrimaryMonitor,
+                            self.config.paragraph_order,
+                        ),
+                    }
+                }
+                (_, HotKeyState::Released) => Ok(()),
+                (None, _) => Ok(()),
+                (Some(HotkeyAction::Ocr), _) => {
+                    self.request_capture(CaptureTarget::PrimaryMonitor, self.config.paragraph_order)
+                }
+                (Some(HotkeyAction::StitchPage), _) => self.trigger_stitch_capture(),
+                (Some(HotkeyAction::Preset(id)), _) => match self.config.capture_preset(id) {
+                    Some(preset) => {
+                        log::info!("Capturing with preset `{}`", preset.name);
+                        let target = preset.target.clone();
+                        let order = preset
+                            .paragraph_order
+                            .unwrap_or(self.config.paragraph_order);
+                        self.request_capture(target, order)
+                    }
+                    None => Ok(()),
+                },
+            };
+
+            if let Err(e) = result {
+                self.popups.error(e);
+            }
+        }
+
+        if let Some(region_select) = &mut self.region_select {
+            if let Some(result) = region_select.show(ctx) {
+                let mut region_select = self.region_select.take().unwrap();
+                // the OCR service was checked while the user was selecting the region, it is only
+                // reported now if it is already known to be down
+                match (result, region_select.readiness.try_wait()) {
+                    (None, _) => {}
+                    (Some(_), Ok(Some(Err(e)))) => self.popups.error(e),
+                    (Some(image), _) => self.open_ocr_window(
+                        ctx,
+                        image,
+                        region_select.capture_time,
+                        region_select.paragraph_order,
+                    ),
+                }
+            }
+        }
+
+        if let Some(pending) = &mut self.pending_capture {
+            pending.show_countdown(ctx);
+
+            match pending.poll(&mut self.services) {
+                Ok(None) => {}
+                Ok(Some((image, capture_time))) => {
+                    let order = pending.paragraph_order;
+                    self.pending_capture = None;
+                    self.open_ocr_window(ctx, image, capture_time, order);
+                }
+                Err(e) => {
+                    self.pending_capture = None;
                     self.popups.error(e);
                 }
             }
         }
 
         if let Some(ocr_window) = &mut self.ocr_window {
-            ocr_window.show(ctx, &self.config, &mut self.popups, &mut self.services);
+            ocr_window.show(
+                ctx,
+                &self.config,
+                &mut self.popups,
+                &mut self.services,
+                &mut self.pin_board,
+            );
 
+            let mined = std::mem::take(&mut ocr_window.mined);
+            for (word, passed) in ocr_window.reviewed.drain(..) {
+                self.stats.record_review(&word, passed);
+            }
+            if let Some(characters) = ocr_window.characters_read.take() {
+                self.stats.record_read(characters);
+            }
             if ocr_window.close_requested {
-                self.ocr_window = None;
+                self.take_ocr_window();
+            }
+
+            for (word, sentence) in mined {
+                self.word_mined(&word, &sentence);
             }
         }
 
+        self.config.discord.post_daily_summary(&mut self.stats);
+        if let Err(e) = self
+            .config
+            .weekly_summary
+            .generate(&mut self.stats, &self.config.discord)
+        {
+            self.popups
+                .error(e.context("Failed to write weekly summary"));
+        }
+
+        self.poll_warm_up_jobs();
+        self.poll_pin_board_jobs();
+        self.poll_mining_jobs();
+        self.save_pin_board();
+        self.update_companion();
+        self.update_bridge(ctx);
+
         show_config_window(self, ctx);
+        safe_mode::startup_finished();
+
+        self.handle_quit_request(ctx);
+
+        self.show_hotkey_conflicts(ctx);
 
         self.popups.show(ctx);
     }
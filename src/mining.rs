@@ -0,0 +1,66 @@
+use anyhow::Result;
+
+use crate::{
+    hooks::HookConfig,
+    services::{ServiceJob, Services},
+    word::Word,
+};
+
+/// Adds a word to the user's deck, after running the pre-mine hook on it in the background so
+/// that a slow hook doesn't block the UI.
+pub struct MiningJob {
+    /// The word being added, as it was before the pre-mine hook.
+    pub word: Word,
+    /// The paragraph the word is mined from.
+    pub sentence: String,
+    /// How many times the word was encountered in the text it is mined from.
+    occurrences: u32,
+    stage: Stage,
+}
+
+enum Stage {
+    PreMine(ServiceJob<Result<Word>>),
+    /// Adding the word returned by the pre-mine hook.
+    AddToDeck(Box<Word>, ServiceJob<Result<bool>>),
+}
+
+/// Progress of a `MiningJob`.
+pub enum MiningStatus {
+    Pending,
+    /// The word (as returned by the pre-mine hook) was added to the user's deck.
+    Added(Box<Word>),
+    /// The word wasn't actually added, because dry-run mode is enabled.
+    NotAdded,
+}
+
+impl MiningJob {
+    pub fn new(hooks: &HookConfig, word: &Word, sentence: String, occurrences: u32) -> Self {
+        Self {
+            stage: Stage::PreMine(hooks.pre_mine(word, &sentence)),
+            word: word.clone(),
+            sentence,
+            occurrences,
+        }
+    }
+
+    /// Advance the job, sending the word to the SRS service once the pre-mine hook has finished.
+    pub fn poll(&mut self, services: &mut Services) -> Result<MiningStatus> {
+        match &mut self.stage {
+            Stage::PreMine(job) => {
+                if let Some(word) = job.try_wait()? {
+                    let word = word?;
+                    let job = services.srs.add_to_deck(&word, self.occurrences);
+                    self.stage = Stage::AddToDeck(Box::new(word), job);
+                }
+                Ok(MiningStatus::Pending)
+            }
+            Stage::AddToDeck(word, job) => Ok(match job.try_wait()? {
+                None => MiningStatus::Pending,
+                Some(added) => match added? {
+                    true => MiningStatus::Added(word.clone()),
+                    false => MiningStatus::NotAdded,
+                },
+            }),
+        }
+    }
+}
@@ -0,0 +1,455 @@
+//! Readings of numbers followed by counters (eg. 三百人 → さんびゃくにん), which dictionaries
+//! usually don't have an entry for.
+
+/// Readings of the digits 1 to 9 (index 0 is unused).
+const DIGITS: [&str; 10] = [
+    "",
+    "いち",
+    "に",
+    "さん",
+    "よん",
+    "ご",
+    "ろく",
+    "なな",
+    "はち",
+    "きゅう",
+];
+
+/// How a counter's first sound changes after certain numbers.
+#[derive(Clone, Copy, PartialEq)]
+enum Sound {
+    /// The reading never changes.
+    Plain,
+    /// Counters starting with a k sound: 1, 6, 8, 10 and 100 become いっ, ろっ, はっ, じゅっ and ひゃっ.
+    K,
+    /// Counters starting with an s or t sound: like `K`, except 6 doesn't change.
+    SOrT,
+    /// Counters starting with an h sound: like `K`, and the h becomes a p after those numbers.
+    H,
+}
+
+/// What happens to a counter's first sound after a number ending in ん (eg. 3, 1000).
+#[derive(Clone, Copy, PartialEq)]
+enum AfterN {
+    Unchanged,
+    /// The sound is voiced, eg. ほん → ぼん.
+    Voiced,
+    /// The sound becomes a p, eg. ふん → ぷん. Also applies after 4 (よん).
+    P,
+}
+
+struct Counter {
+    spelling: &'static str,
+    reading: &'static str,
+    sound: Sound,
+    after_n: AfterN,
+    /// Readings used instead of the regular ones for 4, 7 and 9 when they are the last digit.
+    four_seven_nine: Option<[&'static str; 3]>,
+    /// Irregular readings of the whole compound, for specific numbers.
+    special: &'static [(u64, &'static str)],
+}
+
+const fn counter(spelling: &'static str, reading: &'static str, sound: Sound) -> Counter {
+    Counter {
+        spelling,
+        reading,
+        sound,
+        after_n: AfterN::Unchanged,
+        four_seven_nine: None,
+        special: &[],
+    }
+}
+
+const COUNTERS: &[Counter] = &[
+    Counter {
+        four_seven_nine: Some(["よ", "しち", "きゅう"]),
+        special: &[(1, "ひとり"), (2, "ふたり")],
+        ..counter("人", "にん", Sound::Plain)
+    },
+    Counter {
+        special: &[
+            (1, "ついたち"),
+            (2, "ふつか"),
+            (3, "みっか"),
+            (4, "よっか"),
+            (5, "いつか"),
+            (6, "むいか"),
+            (7, "なのか"),
+            (8, "ようか"),
+            (9, "ここのか"),
+            (10, "とおか"),
+            (14, "じゅうよっか"),
+            (20, "はつか"),
+            (24, "にじゅうよっか"),
+        ],
+        ..counter("日", "にち", Sound::Plain)
+    },
+    Counter {
+        four_seven_nine: Some(["し", "しち", "く"]),
+        ..counter("月", "がつ", Sound::Plain)
+    },
+    counter("ヶ月", "かげつ", Sound::K),
+    counter("か月", "かげつ", Sound::K),
+    counter("カ月", "かげつ", Sound::K),
+    Counter {
+        four_seven_nine: Some(["よ", "なな", "きゅう"]),
+        ..counter("年", "ねん", Sound::Plain)
+    },
+    Counter {
+        four_seven_nine: Some(["よ", "しち", "く"]),
+        ..counter("時", "じ", Sound::Plain)
+    },
+    Counter {
+        four_seven_nine: Some(["よ", "なな", "く"]),
+        ..counter("時間", "じかん", Sound::Plain)
+    },
+    Counter {
+        after_n: AfterN::P,
+        ..counter("分", "ふん", Sound::H)
+    },
+    counter("秒", "びょう", Sound::Plain),
+    counter("週間", "しゅうかん", Sound::SOrT),
+    Counter {
+        four_seven_nine: Some(["よ", "なな", "きゅう"]),
+        ..counter("円", "えん", Sound::Plain)
+    },
+    counter("個", "こ", Sound::K),
+    counter("回", "かい", Sound::K),
+    Counter {
+        after_n: AfterN::Voiced,
+        ..counter("階", "かい", Sound::K)
+    },
+    counter("件", "けん", Sound::K),
+    Counter {
+        after_n: AfterN::Voiced,
+        ..counter("軒", "けん", Sound::K)
+    },
+    counter("冊", "さつ", Sound::SOrT),
+    Counter {
+        special: &[(20, "はたち")],
+        ..counter("歳", "さい", Sound::SOrT)
+    },
+    Counter {
+        special: &[(20, "はたち")],
+        ..counter("才", "さい", Sound::SOrT)
+    },
+    counter("点", "てん", Sound::SOrT),
+    Counter {
+        after_n: AfterN::Voiced,
+        ..counter("本", "ほん", Sound::H)
+    },
+    Counter {
+        after_n: AfterN::Voiced,
+        ..counter("匹", "ひき", Sound::H)
+    },
+    Counter {
+        after_n: AfterN::Voiced,
+        ..counter("杯", "はい", Sound::H)
+    },
+    counter("枚", "まい", Sound::Plain),
+    counter("台", "だい", Sound::Plain),
+    counter("度", "ど", Sound::Plain),
+    counter("番", "ばん", Sound::Plain),
+    counter("倍", "ばい", Sound::Plain),
+    counter("位", "い", Sound::Plain),
+    Counter {
+        special: &[
+            (1, "ひとつ"),
+            (2, "ふたつ"),
+            (3, "みっつ"),
+            (4, "よっつ"),
+            (5, "いつつ"),
+            (6, "むっつ"),
+            (7, "ななつ"),
+            (8, "やっつ"),
+            (9, "ここのつ"),
+            (10, "とお"),
+        ],
+        ..counter("つ", "つ", Sound::Plain)
+    },
+];
+
+/// The last part of a number's reading, which counters can change the sound of.
+#[derive(Clone, Copy)]
+enum Tail {
+    /// A digit between 1 and 9 in the ones place.
+    Digit(usize),
+    Ten,
+    /// Hundreds, with their digit.
+    Hundred(usize),
+    /// Thousands, with their digit.
+    Thousand(usize),
+    Man,
+    Oku,
+}
+
+impl Tail {
+    fn reading(self) -> String {
+        match self {
+            Self::Digit(d) => DIGITS[d].to_owned(),
+            Self::Ten => "じゅう".to_owned(),
+            Self::Hundred(d) => match d {
+                1 => "ひゃく".to_owned(),
+                3 => "さんびゃく".to_owned(),
+                6 => "ろっぴゃく".to_owned(),
+                8 => "はっぴゃく".to_owned(),
+                d => format!("{}ひゃく", DIGITS[d]),
+            },
+            Self::Thousand(d) => match d {
+                1 => "せん".to_owned(),
+                3 => "さんぜん".to_owned(),
+                8 => "はっせん".to_owned(),
+                d => format!("{}せん", DIGITS[d]),
+            },
+            Self::Man => "まん".to_owned(),
+            Self::Oku => "おく".to_owned(),
+        }
+    }
+
+    /// The reading with its last mora replaced by a small っ, if the counter causes it.
+    fn geminated(self, sound: Sound) -> Option<String> {
+        let geminates = match self {
+            Self::Digit(1 | 8) | Self::Ten | Self::Hundred(_) => true,
+            Self::Digit(6) => sound != Sound::SOrT,
+            _ => false,
+        };
+        if !geminates || sound == Sound::Plain {
+            return None;
+        }
+
+        let mut reading = self.reading();
+        reading.pop();
+        reading.push('っ');
+        Some(reading)
+    }
+}
+
+/// Split a number into the reading of everything but its last part, and its last part.
+fn split(n: u64) -> (String, Tail) {
+    if !n.is_multiple_of(10) {
+        (reading(n - n % 10), Tail::Digit((n % 10) as usize))
+    } else if !n.is_multiple_of(100) {
+        let tens = (n / 10 % 10) as usize;
+        let prefix = if tens > 1 { DIGITS[tens] } else { "" };
+        (format!("{}{prefix}", reading(n - n % 100)), Tail::Ten)
+    } else if !n.is_multiple_of(1000) {
+        (
+            reading(n - n % 1000),
+            Tail::Hundred((n / 100 % 10) as usize),
+        )
+    } else if !n.is_multiple_of(10_000) {
+        (
+            reading(n - n % 10_000),
+            Tail::Thousand((n / 1000 % 10) as usize),
+        )
+    } else if !n.is_multiple_of(100_000_000) {
+        let group = n / 10_000 % 10_000;
+        let prefix = if group == 1 {
+            "いち".to_owned()
+        } else {
+            reading(group)
+        };
+        (
+            format!("{}{prefix}", reading(n - n % 100_000_000)),
+            Tail::Man,
+        )
+    } else {
+        let group = n / 100_000_000;
+        let prefix = if group == 1 {
+            "いち".to_owned()
+        } else {
+            reading(group)
+        };
+        (prefix, Tail::Oku)
+    }
+}
+
+/// Reading of a number on its own.
+fn reading(n: u64) -> String {
+    if n == 0 {
+        return String::new();
+    }
+    let (prefix, tail) = split(n);
+    prefix + &tail.reading()
+}
+
+/// Voice the first kana of `reading` (eg. ほ → ぼ), or turn it into a p sound (eg. ほ → ぽ).
+fn change_first_kana(reading: &str, voiced: bool) -> String {
+    const UNVOICED: &str = "かきくけこさしすせそたちつてとはひふへほ";
+    const VOICED: &str = "がぎぐげござじずぜぞだぢづでどばびぶべぼ";
+    const P: &str = "ぱぴぷぺぽ";
+
+    let mut chars = reading.chars();
+    let Some(first) = chars.next() else {
+        return String::new();
+    };
+
+    let idx = UNVOICED.chars().position(|c| c == first);
+    let changed = match (idx, voiced) {
+        (Some(idx), true) => VOICED.chars().nth(idx),
+        (Some(idx), false) if idx >= 15 => P.chars().nth(idx - 15),
+        _ => None,
+    };
+
+    changed.unwrap_or(first).to_string() + chars.as_str()
+}
+
+/// Parse a number written with Arabic (half or full width) or kanji numerals. Returns `None` if
+/// it is too large to fit in a `u64` (eg. a long run of digits from OCR noise).
+fn parse_number(text: &str) -> Option<u64> {
+    if text.is_empty() {
+        return None;
+    }
+
+    if text
+        .chars()
+        .all(|c| c.is_ascii_digit() || ('０'..='９').contains(&c) || c == ',')
+    {
+        let digits: String = text
+            .chars()
+            .filter(|c| *c != ',')
+            .map(|c| match c {
+                '０'..='９' => char::from(b'0' + (c as u32 - '０' as u32) as u8),
+                c => c,
+            })
+            .collect();
+        return digits.parse().ok();
+    }
+
+    let mut total: u64 = 0;
+    // value of the current group of less than 10000
+    let mut group: u64 = 0;
+    // digit waiting for a multiplier, or to be added to the group as is
+    let mut digit: Option<u64> = None;
+
+    for c in text.chars() {
+        match c {
+            '〇' | '零' => digit = Some(digit.unwrap_or(0).checked_mul(10)?),
+            '一' | '二' | '三' | '四' | '五' | '六' | '七' | '八' | '九' => {
+                let value = "一二三四五六七八九".chars().position(|d| d == c)? as u64 + 1;
+                // digits written one after the other, eg. 二〇二四
+                digit = Some(digit.unwrap_or(0).checked_mul(10)?.checked_add(value)?);
+            }
+            '十' | '百' | '千' => {
+                let multiplier = match c {
+                    '十' => 10,
+                    '百' => 100,
+                    _ => 1000,
+                };
+                let value = digit.take().unwrap_or(1).checked_mul(multiplier)?;
+                group = group.checked_add(value)?;
+            }
+            '万' | '億' => {
+                let multiplier = if c == '万' { 10_000 } else { 100_000_000 };
+                group = group.checked_add(digit.take().unwrap_or(0))?;
+                total = total.checked_add(group.max(1).checked_mul(multiplier)?)?;
+                group = 0;
+            }
+            _ => return None,
+        }
+    }
+
+    total.checked_add(group)?.checked_add(digit.unwrap_or(0))
+}
+
+/// Reading in kana of a number followed by a counter, eg. 三百人 → さんびゃくにん. Returns `None`
+/// if `text` isn't a number followed by a known counter.
+pub fn counter_reading(text: &str) -> Option<String> {
+    let counter = COUNTERS
+        .iter()
+        .filter(|counter| text.ends_with(counter.spelling))
+        // prefer the longest counter, eg. 時間 over 間
+        .max_by_key(|counter| counter.spelling.len())?;
+
+    let n = parse_number(&text[..text.len() - counter.spelling.len()])?;
+    if n == 0 || n >= 1_000_000_000_000 {
+        return None;
+    }
+
+    if let Some((_, special)) = counter.special.iter().find(|(m, _)| *m == n) {
+        return Some((*special).to_owned());
+    }
+    // つ is only used up to 10
+    if counter.spelling == "つ" {
+        return None;
+    }
+
+    let (prefix, tail) = split(n);
+
+    if let Some(geminated) = tail.geminated(counter.sound) {
+        let reading = match counter.sound {
+            Sound::H => change_first_kana(counter.reading, false),
+            _ => counter.reading.to_owned(),
+        };
+        return Some(prefix + &geminated + &reading);
+    }
+
+    let tail_reading = match (tail, counter.four_seven_nine) {
+        (Tail::Digit(4), Some([four, _, _])) => four.to_owned(),
+        (Tail::Digit(7), Some([_, seven, _])) => seven.to_owned(),
+        (Tail::Digit(9), Some([_, _, nine])) => nine.to_owned(),
+        _ => tail.reading(),
+    };
+
+    let counter_reading = match counter.after_n {
+        AfterN::Unchanged => counter.reading.to_owned(),
+        _ if !tail_reading.ends_with('ん') => counter.reading.to_owned(),
+        AfterN::Voiced if matches!(tail, Tail::Digit(4)) => counter.reading.to_owned(),
+        AfterN::Voiced => change_first_kana(counter.reading, true),
+        AfterN::P => change_first_kana(counter.reading, false),
+    };
+
+    Some(prefix + &tail_reading + &counter_reading)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hundreds() {
+        assert_eq!(counter_reading("三百人").as_deref(), Some("さんびゃくにん"));
+    }
+
+    #[test]
+    fn h_counters() {
+        assert_eq!(counter_reading("一本").as_deref(), Some("いっぽん"));
+        assert_eq!(counter_reading("三本").as_deref(), Some("さんぼん"));
+        assert_eq!(counter_reading("四本").as_deref(), Some("よんほん"));
+        assert_eq!(counter_reading("六本").as_deref(), Some("ろっぽん"));
+        assert_eq!(counter_reading("四分").as_deref(), Some("よんぷん"));
+    }
+
+    #[test]
+    fn special_readings() {
+        assert_eq!(counter_reading("二十歳").as_deref(), Some("はたち"));
+        assert_eq!(counter_reading("十四日").as_deref(), Some("じゅうよっか"));
+    }
+
+    #[test]
+    fn k_counters() {
+        assert_eq!(counter_reading("三ヶ月").as_deref(), Some("さんかげつ"));
+    }
+
+    #[test]
+    fn digits_one_after_the_other() {
+        assert_eq!(parse_number("二〇二四"), Some(2024));
+        assert_eq!(
+            counter_reading("二〇二四年").as_deref(),
+            Some("にせんにじゅうよねん")
+        );
+    }
+
+    #[test]
+    fn arabic_numerals() {
+        assert_eq!(parse_number("1,234"), Some(1234));
+        assert_eq!(parse_number("１２"), Some(12));
+    }
+
+    #[test]
+    fn overflow() {
+        assert_eq!(parse_number(&"九".repeat(30)), None);
+        assert_eq!(parse_number(&format!("{}億", "九".repeat(12))), None);
+        assert_eq!(counter_reading(&format!("{}人", "九".repeat(30))), None);
+    }
+}
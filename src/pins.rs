@@ -0,0 +1,130 @@
+use std::{
+    fmt::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::DataFile,
+    word::{Definition, Word},
+};
+
+/// Words pinned by the user from any capture, to decide later whether they should be mined.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PinBoard {
+    pub pins: Vec<Pin>,
+    /// Whether pins were added or removed since the pin board was last saved.
+    #[serde(skip)]
+    changed: bool,
+}
+
+/// A word on the `PinBoard`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pin {
+    pub word: Word,
+    /// The paragraph the word was pinned from.
+    pub sentence: String,
+    /// When the word was pinned, in seconds since the Unix epoch.
+    pub pinned_at: u64,
+}
+
+impl DataFile for PinBoard {
+    fn path() -> &'static str {
+        "pins.json"
+    }
+}
+
+impl PinBoard {
+    /// Whether a word with the given definition is pinned.
+    pub fn is_pinned(&self, definition: &Definition) -> bool {
+        self.position(definition).is_some()
+    }
+
+    /// Pin the given word if it isn't pinned yet, otherwise unpin it. Returns whether the word is now pinned.
+    ///
+    /// Words without a definition can't be pinned.
+    pub fn toggle(&mut self, word: &Word, sentence: String) -> bool {
+        let Some(definition) = &word.definition else {
+            return false;
+        };
+
+        self.changed = true;
+        match self.position(definition) {
+            Some(idx) => {
+                self.pins.remove(idx);
+                false
+            }
+            None => {
+                self.pins.push(Pin {
+                    word: word.clone(),
+                    sentence,
+                    pinned_at: SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_secs(),
+                });
+                true
+            }
+        }
+    }
+
+    /// Unpin the word with the given definition, if it is pinned.
+    pub fn remove(&mut self, definition: &Definition) {
+        if let Some(idx) = self.position(definition) {
+            self.remove_at(idx);
+        }
+    }
+
+    /// Unpin the word at the given index.
+    pub fn remove_at(&mut self, idx: usize) {
+        self.pins.remove(idx);
+        self.changed = true;
+    }
+
+    /// Unpin every word.
+    pub fn clear(&mut self) {
+        self.pins.clear();
+        self.changed = true;
+    }
+
+    /// Save the pin board if pins were added or removed since it was last saved.
+    pub fn save_if_changed(&mut self) -> Result<()> {
+        // not retried on failure, the pin board is saved again on the next change or on exit
+        if std::mem::take(&mut self.changed) {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Export the pin board as tab-separated values: spelling, reading, meanings and sentence.
+    pub fn to_tsv(&self) -> String {
+        let mut tsv = String::new();
+
+        for pin in &self.pins {
+            let Some(definition) = &pin.word.definition else {
+                continue;
+            };
+
+            let _ = writeln!(
+                tsv,
+                "{}\t{}\t{}\t{}",
+                definition.spelling,
+                definition.reading,
+                definition.meanings.join("; "),
+                pin.sentence.replace(['\t', '\n'], " ")
+            );
+        }
+
+        tsv
+    }
+
+    fn position(&self, definition: &Definition) -> Option<usize> {
+        self.pins.iter().position(|pin| {
+            pin.word.definition.as_ref().is_some_and(|pinned| {
+                pinned.spelling == definition.spelling && pinned.reading == definition.reading
+            })
+        })
+    }
+}
@@ -0,0 +1,83 @@
+//! Safe mode, started with `--safe-mode` or automatically after deckocr failed to start, which
+//! uses the default configuration, disabled services and no hotkeys so that the user can still
+//! reach the configuration window to fix whatever broke.
+
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use anyhow::Result;
+
+use crate::data;
+
+/// Command line flag starting deckocr in safe mode.
+const SAFE_MODE_FLAG: &str = "--safe-mode";
+/// Name of the file created in the data directory while deckocr starts, and removed once it did.
+/// If it is still there on the next start, the previous one failed.
+const STARTUP_MARKER: &str = "starting";
+
+/// Whether the startup marker was removed already.
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Why deckocr is running in safe mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SafeMode {
+    /// The user passed `--safe-mode`.
+    Requested,
+    /// The previous start failed (ie. it crashed before the end of the first frame).
+    StartupFailed,
+}
+
+impl SafeMode {
+    /// Whether deckocr should start in safe mode. Must be called once, before `startup_finished`.
+    pub fn detect() -> Option<Self> {
+        let requested = std::env::args().any(|arg| arg == SAFE_MODE_FLAG);
+
+        let marker = marker_path();
+        let startup_failed = marker.as_ref().is_ok_and(|path| path.exists());
+        if let Err(e) = marker.and_then(|path| {
+            std::fs::create_dir_all(path.parent().unwrap())?;
+            Ok(std::fs::write(path, "")?)
+        }) {
+            log::warn!("Could not create startup marker: {e:#}");
+        }
+
+        match (requested, startup_failed) {
+            (true, _) => Some(Self::Requested),
+            (false, true) => {
+                log::warn!("The previous start did not finish, starting in safe mode");
+                Some(Self::StartupFailed)
+            }
+            (false, false) => None,
+        }
+    }
+
+    /// Explanation shown to the user in the configuration window.
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::Requested => "Safe mode: started with `--safe-mode`.",
+            Self::StartupFailed => "Safe mode: deckocr did not start properly last time.",
+        }
+    }
+}
+
+/// Remove the startup marker, once the main window was shown. Does nothing after the first call.
+///
+/// Only crashes before the end of the first frame are detected: a crash after that (eg. while
+/// taking a capture) doesn't start the next session in safe mode.
+pub fn startup_finished() {
+    if STARTED.swap(true, Ordering::Relaxed) {
+        return;
+    }
+
+    if let Ok(path) = marker_path() {
+        if let Err(e) = std::fs::remove_file(&path) {
+            log::warn!("Could not remove startup marker `{}`: {e}", path.display());
+        }
+    }
+}
+
+fn marker_path() -> Result<PathBuf> {
+    Ok(data::data_dir()?.join(STARTUP_MARKER))
+}
@@ -1,51 +1,161 @@
-use std::thread::JoinHandle;
+use std::{
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
 
-use anyhow::{anyhow, Result};
-use dictionary::DictionaryService;
+use anyhow::{anyhow, Context, Result};
+use capture::CaptureService;
+use dictionary::{monolingual::MonolingualDictionary, DictionaryService};
+use health::ServiceKind;
+use image::RgbaImage;
 use ocr::OcrService;
 use srs::SrsService;
 
-use crate::config::AppConfig;
+use crate::{capture::CaptureTarget, config::AppConfig};
 
+pub mod capture;
 pub mod dictionary;
+pub mod disabled;
+pub mod health;
+pub mod http;
+pub mod jpdb;
 pub mod ocr;
 pub mod srs;
 
+/// How long deckocr waits for pending jobs (eg. adding words to the user's deck) before closing a
+/// window anyway.
+pub const PENDING_JOBS_TIMEOUT: Duration = Duration::from_secs(15);
+
 /// Holds instanciated services.
 pub struct Services {
+    pub capture: Box<dyn CaptureService>,
     pub ocr: Box<dyn OcrService>,
     pub dictionary: Box<dyn DictionaryService>,
+    /// Monolingual dictionary whose glosses are added to the dictionary service's definitions.
+    pub monolingual: MonolingualDictionary,
     pub srs: Box<dyn SrsService>,
+    /// Whether `terminate` was already called, so that it isn't called again on drop.
+    terminated: bool,
 }
 
 impl Services {
     /// Create a new `Services` from the services specified in the given `AppConfig`.
     pub fn new(config: &AppConfig) -> Result<Self> {
+        http::configure(config);
+
         let mut services = Self {
+            capture: config.capture_service.create_service(),
             ocr: config.ocr_service.create_service(),
             dictionary: config.dictionary_service.create_service(),
+            monolingual: MonolingualDictionary::default(),
             srs: config.srs_service.create_service(),
+            terminated: false,
         };
 
-        services.ocr.init()?;
-        services.dictionary.init()?;
-        services.srs.init()?;
+        for service in [
+            ServiceKind::Capture,
+            ServiceKind::Ocr,
+            ServiceKind::Dictionary,
+            ServiceKind::Srs,
+        ] {
+            health::reset(service);
+        }
+
+        let result = services.capture.init();
+        health::record(ServiceKind::Capture, &result);
+        result.context("Failed to initialise capture service")?;
+
+        let result = services.ocr.init();
+        health::record(ServiceKind::Ocr, &result);
+        result.context("Failed to initialise OCR service")?;
+
+        let result = services.dictionary.init();
+        health::record(ServiceKind::Dictionary, &result);
+        result.context("Failed to initialise dictionary service")?;
+
+        let result = services.monolingual.init();
+        health::record(ServiceKind::Dictionary, &result);
+        result.context("Failed to initialise monolingual dictionary")?;
+
+        let result = services.srs.init();
+        health::record(ServiceKind::Srs, &result);
+        result.context("Failed to initialise SRS service")?;
 
         Ok(services)
     }
+
+    /// Create services which do nothing, used in safe mode.
+    ///
+    /// These are never initialised, so they are never terminated either: terminating the
+    /// monolingual dictionary would overwrite the user's configuration with the default one.
+    pub fn disabled() -> Self {
+        Self {
+            capture: Box::new(disabled::Disabled),
+            ocr: Box::new(disabled::Disabled),
+            dictionary: Box::new(disabled::Disabled),
+            monolingual: MonolingualDictionary::default(),
+            srs: Box::new(disabled::Disabled),
+            terminated: true,
+        }
+    }
+
+    /// Check that the OCR service can be reached before taking a capture, starting it if possible.
+    pub fn ensure_ocr_ready(&mut self) -> ServiceJob<Result<()>> {
+        self.ocr.ensure_ready()
+    }
+
+    /// Take a screenshot of the given target with the capture service, returning it along with
+    /// the time it took.
+    pub fn take_screenshot(&mut self, target: &CaptureTarget) -> Result<(RgbaImage, Duration)> {
+        let started_at = Instant::now();
+        let result = self.capture.capture(target);
+        health::record(ServiceKind::Capture, &result);
+        Ok((result?, started_at.elapsed()))
+    }
+
+    /// Send warm-up requests to the OCR and dictionary services, returning the jobs along with the
+    /// name of the service they were sent to.
+    pub fn warm_up(&mut self) -> Vec<(&'static str, ServiceJob<Result<()>>)> {
+        vec![
+            ("OCR", self.ocr.warm_up()),
+            ("dictionary", self.dictionary.warm_up()),
+        ]
+    }
+
+    /// Terminate every service, even if some of them fail to, and return the errors encountered.
+    pub fn terminate(&mut self) -> Vec<anyhow::Error> {
+        if std::mem::replace(&mut self.terminated, true) {
+            return Vec::new();
+        }
+
+        [
+            self.capture
+                .terminate()
+                .context("Failed to terminate capture service"),
+            self.ocr
+                .terminate()
+                .context("Failed to terminate OCR service"),
+            self.dictionary
+                .terminate()
+                .context("Failed to terminate dictionary service"),
+            self.monolingual
+                .terminate()
+                .context("Failed to terminate monolingual dictionary"),
+            self.srs
+                .terminate()
+                .context("Failed to terminate SRS service"),
+        ]
+        .into_iter()
+        .filter_map(Result::err)
+        .collect()
+    }
 }
 
 impl Drop for Services {
     fn drop(&mut self) {
-        self.ocr
-            .terminate()
-            .expect("Failed to terminate OCR Service");
-        self.dictionary
-            .terminate()
-            .expect("Failed to terminate dictionary Service");
-        self.srs
-            .terminate()
-            .expect("Failed to terminate SRS Service");
+        for e in self.terminate() {
+            log::error!("Error while terminating services: `{e:#}`");
+        }
     }
 }
 
@@ -60,6 +170,17 @@ impl<T: Send + 'static> ServiceJob<T> {
     }
 }
 
+impl<T: Send + 'static> ServiceJob<Result<T>> {
+    /// Like `new`, but the job's result is recorded in the health status of the given service.
+    pub fn tracked<F: FnOnce() -> Result<T> + Send + 'static>(service: ServiceKind, f: F) -> Self {
+        Self::new(move || {
+            let result = f();
+            health::record(service, &result);
+            result
+        })
+    }
+}
+
 impl<T> ServiceJob<T> {
     /// Get the return value of this `ServiceJob` if it was finished.
     ///
@@ -89,8 +210,8 @@ impl<T> ServiceJob<T> {
     }
 }
 
-impl<T> Into<ServiceJob<T>> for JoinHandle<T> {
-    fn into(self) -> ServiceJob<T> {
-        ServiceJob { handle: Some(self) }
+impl<T> From<JoinHandle<T>> for ServiceJob<T> {
+    fn from(val: JoinHandle<T>) -> Self {
+        ServiceJob { handle: Some(val) }
     }
 }
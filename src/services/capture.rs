@@ -0,0 +1,26 @@
+use anyhow::Result;
+use eframe::egui;
+use image::RgbaImage;
+
+use crate::capture::CaptureTarget;
+
+pub mod clipboard;
+pub mod command;
+pub mod image_file;
+pub mod portal;
+pub mod screen;
+
+/// A capture service, which provides the images sent to OCR.
+pub trait CaptureService {
+    /// Initialise the service (ie. load its configuration file, etc).
+    fn init(&mut self) -> Result<()>;
+    /// Terminate the service (ie. save its configuration file, etc).
+    fn terminate(&mut self) -> Result<()>;
+
+    /// Show the config UI for the service's configuration.
+    fn show_config_ui(&mut self, ui: &mut egui::Ui);
+
+    /// Take a screenshot of the given target. Services which don't capture the screen directly
+    /// (eg. reading an image from a file) ignore it.
+    fn capture(&mut self, target: &CaptureTarget) -> Result<RgbaImage>;
+}
@@ -0,0 +1,39 @@
+use anyhow::{Context, Result};
+use eframe::egui;
+use image::RgbaImage;
+
+use crate::capture::CaptureTarget;
+
+use super::CaptureService;
+
+/// Reads the image currently in the clipboard, eg. one copied by another screenshot tool.
+#[derive(Default)]
+pub struct Clipboard;
+
+impl CaptureService for Clipboard {
+    fn init(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn terminate(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn show_config_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Reads the image in the clipboard. Capture targets are ignored.");
+    }
+
+    fn capture(&mut self, _target: &CaptureTarget) -> Result<RgbaImage> {
+        let image = arboard::Clipboard::new()
+            .context("Clipboard: Could not access the clipboard")?
+            .get_image()
+            .context("Clipboard: The clipboard does not contain an image")?;
+
+        RgbaImage::from_raw(
+            image.width as u32,
+            image.height as u32,
+            image.bytes.into_owned(),
+        )
+        .context("Clipboard: The image in the clipboard is malformed")
+    }
+}
@@ -0,0 +1,81 @@
+use std::process::Command as Process;
+
+use anyhow::{anyhow, Context, Result};
+use eframe::egui;
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+
+use crate::{capture::CaptureTarget, config::Config};
+
+use super::CaptureService;
+
+/// Runs an external screenshot tool which writes an image to its standard output, eg. `grim -`
+/// on wlroots compositors.
+#[derive(Default)]
+pub struct Command {
+    config: CommandConfig,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommandConfig {
+    /// The program to run, followed by its arguments separated by spaces.
+    command: String,
+}
+
+impl Config for CommandConfig {
+    fn path() -> &'static str {
+        "capture_services/command.json"
+    }
+
+    fn show_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Command:");
+            ui.text_edit_singleline(&mut self.command)
+                .on_hover_text("A program writing a PNG image to its standard output, followed by its\narguments, eg. `grim -`. Capture targets are ignored.");
+        });
+    }
+}
+
+impl CaptureService for Command {
+    fn init(&mut self) -> Result<()> {
+        self.config =
+            CommandConfig::load().context("Command: Failed to load configuration file")?;
+        Ok(())
+    }
+
+    fn terminate(&mut self) -> Result<()> {
+        self.config
+            .save()
+            .context("Command: Failed to save configuration file")?;
+        Ok(())
+    }
+
+    fn show_config_ui(&mut self, ui: &mut egui::Ui) {
+        self.config.show_ui(ui);
+    }
+
+    fn capture(&mut self, _target: &CaptureTarget) -> Result<RgbaImage> {
+        let mut args = self.config.command.split_whitespace();
+        let program = args
+            .next()
+            .ok_or_else(|| anyhow!("Command: No command configured"))?;
+
+        let output = Process::new(program)
+            .args(args)
+            .output()
+            .with_context(|| format!("Command: Could not start `{program}`"))?;
+
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Command: `{program}` exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        Ok(image::load_from_memory(&output.stdout)
+            .with_context(|| format!("Command: `{program}` did not output a valid image"))?
+            .to_rgba8())
+    }
+}
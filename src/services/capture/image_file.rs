@@ -0,0 +1,89 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use eframe::egui;
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+
+use crate::{capture::CaptureTarget, config::Config};
+
+use super::CaptureService;
+
+/// Extensions of the files considered when reading the newest image in a folder.
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "bmp", "webp"];
+
+/// Reads an image file, or the newest image in a folder (eg. a game's screenshot folder).
+#[derive(Default)]
+pub struct ImageFile {
+    config: ImageFileConfig,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImageFileConfig {
+    /// Path to an image file, or to a folder whose newest image is read.
+    path: String,
+}
+
+impl Config for ImageFileConfig {
+    fn path() -> &'static str {
+        "capture_services/image_file.json"
+    }
+
+    fn show_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Image or Folder:");
+            ui.text_edit_singleline(&mut self.path)
+                .on_hover_text("When this is a folder, the most recently modified image in it is read.\nCapture targets are ignored.");
+        });
+    }
+}
+
+impl CaptureService for ImageFile {
+    fn init(&mut self) -> Result<()> {
+        self.config =
+            ImageFileConfig::load().context("ImageFile: Failed to load configuration file")?;
+        Ok(())
+    }
+
+    fn terminate(&mut self) -> Result<()> {
+        self.config
+            .save()
+            .context("ImageFile: Failed to save configuration file")?;
+        Ok(())
+    }
+
+    fn show_config_ui(&mut self, ui: &mut egui::Ui) {
+        self.config.show_ui(ui);
+    }
+
+    fn capture(&mut self, _target: &CaptureTarget) -> Result<RgbaImage> {
+        let mut path = PathBuf::from(&self.config.path);
+
+        if path.is_dir() {
+            path = newest_image(&path)?;
+        }
+
+        Ok(image::open(&path)
+            .with_context(|| format!("ImageFile: Could not read image `{}`", path.display()))?
+            .to_rgba8())
+    }
+}
+
+/// The most recently modified image in a folder.
+fn newest_image(folder: &PathBuf) -> Result<PathBuf> {
+    fs::read_dir(folder)
+        .with_context(|| format!("ImageFile: Could not read folder `{}`", folder.display()))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        })
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+        .ok_or_else(|| anyhow!("ImageFile: No images found in `{}`", folder.display()))
+}
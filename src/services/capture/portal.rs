@@ -0,0 +1,95 @@
+use anyhow::{anyhow, Context, Result};
+use eframe::egui;
+use image::RgbaImage;
+
+use crate::capture::CaptureTarget;
+
+use super::CaptureService;
+
+/// Takes screenshots through the desktop's screenshot portal, eg. on Wayland compositors where
+/// monitors can't be captured directly.
+#[derive(Default)]
+pub struct Portal;
+
+impl CaptureService for Portal {
+    fn init(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn terminate(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn show_config_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Asks the desktop for a screenshot of the whole screen, which may show a permission dialog the first time. Regions are cropped from it, other capture targets are ignored.");
+    }
+
+    fn capture(&mut self, target: &CaptureTarget) -> Result<RgbaImage> {
+        let image = request_screenshot()?;
+
+        let CaptureTarget::Region {
+            x,
+            y,
+            width,
+            height,
+        } = target
+        else {
+            return Ok(image);
+        };
+
+        if *x >= image.width() || *y >= image.height() {
+            return Err(anyhow!(
+                "Portal: Capture region is outside of the screenshot ({}×{})",
+                image.width(),
+                image.height()
+            ));
+        }
+
+        let width = (*width).min(image.width() - x);
+        let height = (*height).min(image.height() - y);
+
+        Ok(image::imageops::crop_imm(&image, *x, *y, width, height).to_image())
+    }
+}
+
+/// Request a screenshot from the portal and read the image it saved.
+#[cfg(target_os = "linux")]
+fn request_screenshot() -> Result<RgbaImage> {
+    use ashpd::desktop::screenshot::Screenshot;
+
+    let uri = futures_lite::future::block_on(async {
+        let response = Screenshot::request()
+            .interactive(false)
+            .modal(false)
+            .send()
+            .await?
+            .response()?;
+        ashpd::Result::Ok(response.uri().clone())
+    })
+    .context("Portal: Screenshot request failed")?;
+
+    let path = uri
+        .to_file_path()
+        .map_err(|()| anyhow!("Portal: Screenshot was not saved to a file: {uri}"))?;
+
+    let image = image::open(&path)
+        .with_context(|| format!("Portal: Failed to read screenshot `{}`", path.display()))?
+        .into_rgba8();
+
+    // the portal saves every screenshot (eg. to ~/Pictures), which the user didn't ask for
+    if let Err(e) = std::fs::remove_file(&path) {
+        log::warn!(
+            "Portal: Failed to remove screenshot `{}`: {e}",
+            path.display()
+        );
+    }
+
+    Ok(image)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn request_screenshot() -> Result<RgbaImage> {
+    Err(anyhow!(
+        "Portal: The screenshot portal is only available on Linux"
+    ))
+}
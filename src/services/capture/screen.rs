@@ -0,0 +1,77 @@
+use anyhow::{anyhow, Context, Result};
+use eframe::egui;
+use image::RgbaImage;
+
+use crate::capture::CaptureTarget;
+
+use super::CaptureService;
+
+/// Captures monitors, windows and regions of the screen directly.
+#[derive(Default)]
+pub struct Screen;
+
+impl CaptureService for Screen {
+    fn init(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn terminate(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn show_config_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Captures the monitor, window or region chosen for each hotkey.");
+    }
+
+    fn capture(&mut self, target: &CaptureTarget) -> Result<RgbaImage> {
+        match target {
+            CaptureTarget::PrimaryMonitor => capture_primary_monitor(),
+            CaptureTarget::Monitor { name } => xcap::Monitor::all()?
+                .into_iter()
+                .find(|monitor| monitor.name().is_ok_and(|n| &n == name))
+                .ok_or_else(|| anyhow!("No monitor named `{name}` found."))?
+                .capture_image()
+                .with_context(|| format!("Failed to capture monitor `{name}`")),
+            CaptureTarget::Window { title } => xcap::Window::all()?
+                .into_iter()
+                .filter(|window| !window.is_minimized().unwrap_or(false))
+                .find(|window| window.title().is_ok_and(|t| t.contains(title.as_str())))
+                .ok_or_else(|| anyhow!("No window with a title containing `{title}` found."))?
+                .capture_image()
+                .with_context(|| format!("Failed to capture window `{title}`")),
+            CaptureTarget::Region {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                let image = capture_primary_monitor()?;
+
+                if *x >= image.width() || *y >= image.height() {
+                    return Err(anyhow!(
+                        "Capture region is outside of the primary monitor ({}×{})",
+                        image.width(),
+                        image.height()
+                    ));
+                }
+
+                let width = (*width).min(image.width() - x);
+                let height = (*height).min(image.height() - y);
+
+                Ok(image::imageops::crop_imm(&image, *x, *y, width, height).to_image())
+            }
+        }
+    }
+}
+
+/// Take a screenshot of the primary monitor.
+fn capture_primary_monitor() -> Result<RgbaImage> {
+    let monitor = xcap::Monitor::all()?
+        .into_iter()
+        .find(|monitor| monitor.is_primary().unwrap_or(false))
+        .ok_or_else(|| anyhow!("No primary monitor found."))?;
+
+    monitor
+        .capture_image()
+        .context("Failed to capture primary monitor")
+}
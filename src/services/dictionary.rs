@@ -6,6 +6,7 @@ use crate::word::Word;
 use super::ServiceJob;
 
 pub mod jpdb_dictionary;
+pub mod monolingual;
 
 pub type DictionaryServiceJob = ServiceJob<Result<Vec<Vec<Word>>>>;
 
@@ -21,4 +22,7 @@ pub trait DictionaryService {
 
     /// Parse a list of paragraphs into a list of list of words with definitions.
     fn parse(&mut self, paragraphs: Vec<String>) -> DictionaryServiceJob;
+
+    /// Send a tiny request to the service so that the first real request doesn't pay cold-start costs.
+    fn warm_up(&mut self) -> ServiceJob<Result<()>>;
 }
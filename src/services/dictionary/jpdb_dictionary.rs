@@ -5,13 +5,13 @@ use serde_json::{json, Value};
 
 use crate::{
     config::Config,
-    services::ServiceJob,
+    services::{health::ServiceKind, http, jpdb, ServiceJob},
     word::{Definition, TextFragment, TextWithRuby, Word},
 };
 
 use super::DictionaryService;
 
-const API_URL_PARSE: &'static str = "https://jpdb.io/api/v1/parse";
+const API_URL_PARSE: &str = "https://jpdb.io/api/v1/parse";
 
 #[derive(Default)]
 pub struct JpdbDictionary {
@@ -19,16 +19,18 @@ pub struct JpdbDictionary {
 }
 
 #[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct JpdbDictionaryConfig {
-    pub api_key: String,
     pub filter_paragraphs_with_no_definitions: bool,
+    /// Captures longer than this are split into multiple parse requests.
+    pub max_characters_per_request: usize,
 }
 
 impl Default for JpdbDictionaryConfig {
     fn default() -> Self {
         Self {
-            api_key: "".to_owned(),
             filter_paragraphs_with_no_definitions: true,
+            max_characters_per_request: 2000,
         }
     }
 }
@@ -39,14 +41,19 @@ impl Config for JpdbDictionaryConfig {
     }
 
     fn show_ui(&mut self, ui: &mut egui::Ui) {
-        ui.horizontal(|ui| {
-            ui.label("API Key:");
-            ui.text_edit_singleline(&mut self.api_key);
-        });
         ui.checkbox(
             &mut self.filter_paragraphs_with_no_definitions,
             "Filter out paragraphs with no definitions",
         );
+        ui.horizontal(|ui| {
+            ui.label("Max Characters per Request:");
+            ui.add(
+                egui::DragValue::new(&mut self.max_characters_per_request)
+                    .range(100..=20000)
+                    .speed(10),
+            )
+            .on_hover_text("Larger captures are split into multiple requests to stay\nwithin the jpdb API's payload limits.");
+        });
     }
 }
 
@@ -54,6 +61,7 @@ impl DictionaryService for JpdbDictionary {
     fn init(&mut self) -> anyhow::Result<()> {
         self.config = JpdbDictionaryConfig::load()
             .context("JpdbDictionary: Failed to load configuration file")?;
+        jpdb::load_accounts()?;
         Ok(())
     }
 
@@ -61,6 +69,7 @@ impl DictionaryService for JpdbDictionary {
         self.config
             .save()
             .context("JpdbDictionary: Failed to save configuration file")?;
+        jpdb::save_accounts()?;
         Ok(())
     }
 
@@ -71,172 +80,35 @@ impl DictionaryService for JpdbDictionary {
     fn parse(&mut self, text: Vec<String>) -> ServiceJob<Result<Vec<Vec<Word>>>> {
         let config = self.config.clone();
 
-        ServiceJob::new(move || {
-            let json: Value = attohttpc::post(API_URL_PARSE)
-                .bearer_auth(&config.api_key)
-                .json(&json!({
-                    "text": text,
-                    "token_fields": [
-                        "vocabulary_index",
-                        "position",
-                        "length",
-                        "furigana"
-                    ],
-                    "vocabulary_fields": [
-                        "vid",
-                        "sid",
-                        "spelling",
-                        "reading",
-                        "frequency_rank",
-                        "meanings"
-                    ]
-                }))
-                .unwrap()
-                .send()
-                .context("JpdbDictionary: Failed to send http request")?
-                .error_for_status()
-                .context("JpdbDictionary: Response status code is not a success code")?
-                .json()
-                .context("JpdbDictionary: Response from the server is not valid json")?;
-
-            let tokens_json = json.get("tokens").map(Value::as_array).flatten().ok_or({
-            anyhow!("Response from `{API_URL_PARSE}` did not contain a `tokens` field, or it was not an array")
-        })?;
-
-            let vocab_json = json.get("vocabulary").map(Value::as_array).flatten().ok_or_else(|| {
-            anyhow!("Response from `{API_URL_PARSE}` did not contain a `vocabulary` field, or it was not an array")
-        })?;
-
-            struct Token {
-                vocab_index: usize,
-                position: usize,
-                length: usize,
-                furigana: Option<Vec<TextFragment>>,
-            }
+        ServiceJob::tracked(ServiceKind::Dictionary, move || {
+            let api_key = jpdb::api_key()?;
 
-            let mut tokens = Vec::new();
-
-            (|| {
-                for line in tokens_json {
-                    let mut v = Vec::new();
-
-                    for token in line.as_array()? {
-                        let furigana = if let Some(array) = token.get(3)?.as_array() {
-                            let mut furigana = Vec::new();
-                            for val in array {
-                                if let Some(furi) = val.as_array() {
-                                    furigana.push(TextFragment {
-                                        text: furi.get(0)?.as_str()?.to_owned(),
-                                        ruby: Some(furi.get(1)?.as_str()?.to_owned()),
-                                    });
-                                } else {
-                                    furigana.push(TextFragment {
-                                        text: val.as_str()?.to_owned(),
-                                        ruby: None,
-                                    });
-                                }
-                            }
-                            Some(furigana)
-                        } else {
-                            None
-                        };
-
-                        v.push(Token {
-                            vocab_index: token.get(0)?.as_u64()? as usize,
-                            position: token.get(1)?.as_u64()? as usize,
-                            length: token.get(2)?.as_u64()? as usize,
-                            furigana,
-                        });
-                    }
-                    tokens.push(v);
-                }
-                Some(())
-            })()
-            .ok_or_else(|| {
-                anyhow!("Malformed item in token list returned from `{API_URL_PARSE}`")
-            })?;
-
-            struct Vocabulary {
-                vid: u64,
-                sid: u64,
-                spelling: String,
-                reading: String,
-                frequency: Option<u64>,
-                meanings: Vec<String>,
+            let chunks = split_into_chunks(&text, config.max_characters_per_request);
+
+            if chunks.len() > 1 {
+                log::warn!(
+                    "JpdbDictionary: Capture contains {} characters, splitting it into {} parse requests",
+                    text.iter().map(|paragraph| paragraph.chars().count()).sum::<usize>(),
+                    chunks.len()
+                );
             }
 
-            let mut vocab = Vec::new();
-
-            (|| {
-                for word in vocab_json {
-                    let vocab_data = Vocabulary {
-                        vid: word.get(0)?.as_u64()?,
-                        sid: word.get(1)?.as_u64()?,
-                        spelling: word.get(2)?.as_str()?.to_owned(),
-                        reading: word.get(3)?.as_str()?.to_owned(),
-                        frequency: word.get(4)?.as_u64(),
-                        meanings: word
-                            .get(5)?
-                            .as_array()?
-                            .iter()
-                            .map(|v| v.as_str())
-                            .flatten()
-                            .map(str::to_owned)
-                            .collect(),
-                    };
-
-                    vocab.push(vocab_data);
-                }
-                Some(())
-            })()
-            .ok_or_else(|| {
-                anyhow!("Malformed item in token list returned from `{API_URL_PARSE}`")
-            })?;
-
-            let mut words = Vec::new();
-
-            for (text, tokens) in text.iter().zip(tokens) {
-                let mut cursor = 0;
-                let mut vec = Vec::new();
-
-                if tokens.is_empty() {
-                    vec.push(Word {
-                        text: text.clone().into(),
-                        definition: None,
-                    });
-                }
+            let mut words: Vec<Vec<Word>> = vec![Vec::new(); text.len()];
 
-                for token in tokens {
-                    if token.position > cursor {
-                        // next token is ahead of the cursor, unparsed text ahead
-                        vec.push(Word {
-                            text: text[cursor..token.position].to_owned().into(),
-                            definition: None,
-                        });
-                    }
-                    // we are now sure to be at the next token
-                    let text = TextWithRuby(token.furigana.unwrap_or_else(|| {
-                        vec![TextFragment {
-                            text: text[token.position..token.position + token.length].to_owned(),
-                            ruby: None,
-                        }]
-                    }));
-                    let definition = Some(Definition {
-                        spelling: vocab[token.vocab_index].spelling.clone(),
-                        reading: vocab[token.vocab_index].reading.clone(),
-                        frequency: vocab[token.vocab_index].frequency,
-                        meanings: vocab[token.vocab_index].meanings.clone(),
-                        jpdb_vid_sid: Some((
-                            vocab[token.vocab_index].vid,
-                            vocab[token.vocab_index].sid,
-                        )),
-                    });
-                    vec.push(Word { text, definition });
-
-                    cursor = token.position + token.length;
-                }
+            for (chunk_idx, chunk) in chunks.iter().enumerate() {
+                let pieces: Vec<String> = chunk.iter().map(|(_, piece)| piece.clone()).collect();
+
+                let parsed = parse_chunk(&api_key, &pieces).with_context(|| {
+                    format!(
+                        "JpdbDictionary: Failed to parse chunk {} of {}",
+                        chunk_idx + 1,
+                        chunks.len()
+                    )
+                })?;
 
-                words.push(vec);
+                for ((paragraph_idx, _), piece_words) in chunk.iter().zip(parsed) {
+                    words[*paragraph_idx].extend(piece_words);
+                }
             }
 
             if config.filter_paragraphs_with_no_definitions {
@@ -246,4 +118,303 @@ impl DictionaryService for JpdbDictionary {
             Ok(words)
         })
     }
+
+    fn warm_up(&mut self) -> ServiceJob<Result<()>> {
+        ServiceJob::tracked(ServiceKind::Dictionary, move || {
+            let api_key = jpdb::api_key()?;
+
+            parse_chunk(&api_key, &["日本語".to_owned()])?;
+            Ok(())
+        })
+    }
+}
+
+/// Send a single parse request to jpdb, returning one list of words per paragraph in `text`.
+fn parse_chunk(api_key: &str, text: &[String]) -> Result<Vec<Vec<Word>>> {
+    let json: Value = http::post_json(
+        API_URL_PARSE,
+        api_key,
+        &json!({
+            "text": text,
+            "token_fields": [
+                "vocabulary_index",
+                "position",
+                "length",
+                "furigana"
+            ],
+            "vocabulary_fields": [
+                "vid",
+                "sid",
+                "spelling",
+                "reading",
+                "frequency_rank",
+                "meanings",
+                "part_of_speech"
+            ]
+        }),
+    )
+    .context("JpdbDictionary: HTTP request failed")?;
+
+    let tokens_json = json.get("tokens").and_then(Value::as_array).ok_or({
+        anyhow!("Response from `{API_URL_PARSE}` did not contain a `tokens` field, or it was not an array")
+    })?;
+
+    let vocab_json = json.get("vocabulary").and_then(Value::as_array).ok_or_else(|| {
+        anyhow!("Response from `{API_URL_PARSE}` did not contain a `vocabulary` field, or it was not an array")
+    })?;
+
+    struct Token {
+        vocab_index: usize,
+        position: usize,
+        length: usize,
+        furigana: Option<Vec<TextFragment>>,
+    }
+
+    let mut tokens = Vec::new();
+
+    (|| {
+        for line in tokens_json {
+            let mut v = Vec::new();
+
+            for token in line.as_array()? {
+                let furigana = if let Some(array) = token.get(3)?.as_array() {
+                    let mut furigana = Vec::new();
+                    for val in array {
+                        if let Some(furi) = val.as_array() {
+                            furigana.push(TextFragment {
+                                text: furi.first()?.as_str()?.to_owned(),
+                                ruby: Some(furi.get(1)?.as_str()?.to_owned()),
+                            });
+                        } else {
+                            furigana.push(TextFragment {
+                                text: val.as_str()?.to_owned(),
+                                ruby: None,
+                            });
+                        }
+                    }
+                    Some(furigana)
+                } else {
+                    None
+                };
+
+                v.push(Token {
+                    vocab_index: token.get(0)?.as_u64()? as usize,
+                    position: token.get(1)?.as_u64()? as usize,
+                    length: token.get(2)?.as_u64()? as usize,
+                    furigana,
+                });
+            }
+            tokens.push(v);
+        }
+        Some(())
+    })()
+    .ok_or_else(|| anyhow!("Malformed item in token list returned from `{API_URL_PARSE}`"))?;
+
+    struct Vocabulary {
+        vid: u64,
+        sid: u64,
+        spelling: String,
+        reading: String,
+        frequency: Option<u64>,
+        meanings: Vec<String>,
+        part_of_speech: Vec<String>,
+    }
+
+    let mut vocab = Vec::new();
+
+    (|| {
+        for word in vocab_json {
+            let vocab_data = Vocabulary {
+                vid: word.get(0)?.as_u64()?,
+                sid: word.get(1)?.as_u64()?,
+                spelling: word.get(2)?.as_str()?.to_owned(),
+                reading: word.get(3)?.as_str()?.to_owned(),
+                frequency: word.get(4)?.as_u64(),
+                meanings: word
+                    .get(5)?
+                    .as_array()?
+                    .iter()
+                    .filter_map(|v| v.as_str())
+                    .map(str::to_owned)
+                    .collect(),
+                part_of_speech: word
+                    .get(6)?
+                    .as_array()?
+                    .iter()
+                    .filter_map(|v| v.as_str())
+                    .map(str::to_owned)
+                    .collect(),
+            };
+
+            vocab.push(vocab_data);
+        }
+        Some(())
+    })()
+    .ok_or_else(|| anyhow!("Malformed item in token list returned from `{API_URL_PARSE}`"))?;
+
+    let mut words = Vec::new();
+
+    for (text, tokens) in text.iter().zip(tokens) {
+        let mut cursor = 0;
+        let mut vec = Vec::new();
+
+        if tokens.is_empty() {
+            vec.push(Word {
+                text: text.clone().into(),
+                definition: None,
+            });
+        }
+
+        for token in tokens {
+            if token.position > cursor {
+                // next token is ahead of the cursor, unparsed text ahead
+                vec.push(Word {
+                    text: text[cursor..token.position].to_owned().into(),
+                    definition: None,
+                });
+            }
+            // we are now sure to be at the next token
+            let text = TextWithRuby(token.furigana.unwrap_or_else(|| {
+                vec![TextFragment {
+                    text: text[token.position..token.position + token.length].to_owned(),
+                    ruby: None,
+                }]
+            }));
+            let definition = Some(Definition {
+                spelling: vocab[token.vocab_index].spelling.clone(),
+                reading: vocab[token.vocab_index].reading.clone(),
+                frequency: vocab[token.vocab_index].frequency,
+                meanings: vocab[token.vocab_index].meanings.clone(),
+                monolingual_meanings: Vec::new(),
+                images: Vec::new(),
+                part_of_speech: vocab[token.vocab_index].part_of_speech.clone(),
+                jpdb_vid_sid: Some((vocab[token.vocab_index].vid, vocab[token.vocab_index].sid)),
+            });
+            vec.push(Word { text, definition });
+
+            cursor = token.position + token.length;
+        }
+
+        words.push(vec);
+    }
+
+    Ok(words)
+}
+
+/// Split paragraphs into chunks of at most `max_chars` characters, so that each chunk can be sent
+/// in its own parse request. Paragraphs longer than `max_chars` are themselves split into
+/// multiple pieces, preferably at the end of a sentence.
+///
+/// Each piece is returned along with the index of the paragraph it came from.
+fn split_into_chunks(text: &[String], max_chars: usize) -> Vec<Vec<(usize, String)>> {
+    let max_chars = max_chars.max(1);
+
+    let mut chunks = Vec::new();
+    let mut chunk = Vec::new();
+    let mut chunk_len = 0;
+
+    for (paragraph_idx, paragraph) in text.iter().enumerate() {
+        let mut rest = paragraph.as_str();
+
+        loop {
+            let rest_len = rest.chars().count();
+
+            let piece = if rest_len <= max_chars {
+                rest
+            } else {
+                let hard_limit = rest
+                    .char_indices()
+                    .nth(max_chars)
+                    .map(|(idx, _)| idx)
+                    .unwrap_or(rest.len());
+                let split_at = rest[..hard_limit]
+                    .rmatch_indices(['。', '！', '？', '!', '?', '\n'])
+                    .next()
+                    .map(|(idx, matched)| idx + matched.len())
+                    .unwrap_or(hard_limit);
+                &rest[..split_at]
+            };
+            let piece_len = piece.chars().count();
+
+            if chunk_len + piece_len > max_chars && !chunk.is_empty() {
+                chunks.push(std::mem::take(&mut chunk));
+                chunk_len = 0;
+            }
+
+            chunk.push((paragraph_idx, piece.to_owned()));
+            chunk_len += piece_len;
+
+            rest = &rest[piece.len()..];
+            if rest.is_empty() {
+                break;
+            }
+        }
+    }
+
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paragraphs(text: &[&str]) -> Vec<String> {
+        text.iter().map(|s| (*s).to_owned()).collect()
+    }
+
+    #[test]
+    fn short_paragraphs_share_a_chunk() {
+        let chunks = split_into_chunks(&paragraphs(&["あいう", "えお"]), 10);
+        assert_eq!(
+            chunks,
+            vec![vec![(0, "あいう".to_owned()), (1, "えお".to_owned())]]
+        );
+    }
+
+    #[test]
+    fn full_chunks_start_a_new_one() {
+        let chunks = split_into_chunks(&paragraphs(&["あいう", "えおか"]), 4);
+        assert_eq!(
+            chunks,
+            vec![
+                vec![(0, "あいう".to_owned())],
+                vec![(1, "えおか".to_owned())]
+            ]
+        );
+    }
+
+    #[test]
+    fn long_paragraphs_are_split_at_sentence_ends() {
+        let chunks = split_into_chunks(&paragraphs(&["あい。うえおか"]), 5);
+        assert_eq!(
+            chunks,
+            vec![
+                vec![(0, "あい。".to_owned())],
+                vec![(0, "うえおか".to_owned())]
+            ]
+        );
+    }
+
+    #[test]
+    fn long_sentences_are_split_at_the_limit() {
+        let chunks = split_into_chunks(&paragraphs(&["あいうえお"]), 2);
+        assert_eq!(
+            chunks,
+            vec![
+                vec![(0, "あい".to_owned())],
+                vec![(0, "うえ".to_owned())],
+                vec![(0, "お".to_owned())],
+            ]
+        );
+    }
+
+    #[test]
+    fn zero_limit_is_treated_as_one() {
+        let chunks = split_into_chunks(&paragraphs(&["あい"]), 0);
+        assert_eq!(chunks.len(), 2);
+    }
 }
@@ -0,0 +1,243 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{Context, Result};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    config::Config,
+    services::{health::ServiceKind, ServiceJob},
+    word::Word,
+};
+
+/// Glosses of a monolingual dictionary's headwords, keyed by their spelling.
+type Entries = HashMap<String, Vec<Entry>>;
+
+/// A headword of a monolingual dictionary.
+struct Entry {
+    /// The headword's reading, empty if it is written in kana.
+    reading: String,
+    glosses: Vec<String>,
+    /// Paths to the headword's images.
+    images: Vec<String>,
+}
+
+/// A monolingual (JP-JP) dictionary in the Yomitan format, whose glosses are added to the
+/// definitions found by the dictionary service so the user can switch between the two.
+#[derive(Default)]
+pub struct MonolingualDictionary {
+    config: MonolingualDictionaryConfig,
+    /// The dictionary's entries, once they are loaded.
+    entries: Option<Entries>,
+    /// Job loading the dictionary's entries, if they are still being loaded.
+    loading_job: Option<ServiceJob<Result<Entries>>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonolingualDictionaryConfig {
+    /// Path to an extracted Yomitan dictionary, ie. the folder containing its `term_bank_*.json`
+    /// files. Empty if unused.
+    path: String,
+}
+
+impl Config for MonolingualDictionaryConfig {
+    fn path() -> &'static str {
+        "dictionary_services/monolingual.json"
+    }
+
+    fn show_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Yomitan Dictionary Folder:");
+            ui.text_edit_singleline(&mut self.path)
+                .on_hover_text("Folder of an extracted Yomitan dictionary (eg. a JP-JP dictionary),\ncontaining its `term_bank_*.json` files. Applied when services are reloaded.");
+        });
+    }
+}
+
+impl MonolingualDictionary {
+    /// Load the configuration file, and start loading the dictionary in the background if one is set.
+    pub fn init(&mut self) -> Result<()> {
+        self.config = MonolingualDictionaryConfig::load()
+            .context("MonolingualDictionary: Failed to load configuration file")?;
+
+        if !self.config.path.is_empty() {
+            let path = self.config.path.clone();
+            self.loading_job = Some(ServiceJob::tracked(ServiceKind::Dictionary, move || {
+                load_entries(Path::new(&path))
+            }));
+        }
+
+        Ok(())
+    }
+
+    pub fn terminate(&mut self) -> Result<()> {
+        self.config
+            .save()
+            .context("MonolingualDictionary: Failed to save configuration file")?;
+        Ok(())
+    }
+
+    pub fn show_config_ui(&mut self, ui: &mut egui::Ui) {
+        self.config.show_ui(ui);
+    }
+
+    /// Add the dictionary's glosses to the definitions of the given words, if it has loaded.
+    pub fn annotate(&mut self, words: &mut [Vec<Word>]) {
+        if let Some(job) = &mut self.loading_job {
+            match job.try_wait() {
+                Ok(None) => {
+                    log::warn!("MonolingualDictionary: Dictionary is still loading, monolingual definitions won't be shown for this capture");
+                    return;
+                }
+                Ok(Some(Ok(entries))) => {
+                    log::info!("MonolingualDictionary: Loaded {} headwords", entries.len());
+                    self.entries = Some(entries);
+                }
+                Ok(Some(Err(e))) | Err(e) => log::error!("{e:#}"),
+            }
+            self.loading_job = None;
+        }
+
+        let Some(entries) = &self.entries else {
+            return;
+        };
+
+        for definition in words
+            .iter_mut()
+            .flatten()
+            .filter_map(|word| word.definition.as_mut())
+        {
+            let Some(candidates) = entries.get(&definition.spelling) else {
+                continue;
+            };
+
+            let entry = candidates
+                .iter()
+                .find(|entry| entry.reading == definition.reading)
+                .or_else(|| candidates.iter().find(|entry| entry.reading.is_empty()));
+
+            if let Some(entry) = entry {
+                definition.monolingual_meanings = entry.glosses.clone();
+                definition.images.extend(entry.images.iter().cloned());
+            }
+        }
+    }
+}
+
+/// Read every term bank of the dictionary in the given folder.
+fn load_entries(folder: &Path) -> Result<Entries> {
+    let mut entries = Entries::new();
+
+    let term_banks = fs::read_dir(folder)
+        .with_context(|| {
+            format!(
+                "MonolingualDictionary: Could not read folder `{}`",
+                folder.display()
+            )
+        })?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("term_bank_") && name.ends_with(".json"))
+        });
+
+    for path in term_banks {
+        let file = fs::File::open(&path).with_context(|| {
+            format!(
+                "MonolingualDictionary: Could not open term bank `{}`",
+                path.display()
+            )
+        })?;
+        let rows: Vec<Vec<Value>> = serde_json::from_reader(std::io::BufReader::new(file))
+            .with_context(|| {
+                format!(
+                    "MonolingualDictionary: Could not parse term bank `{}`",
+                    path.display()
+                )
+            })?;
+
+        // rows are `[expression, reading, definition tags, rules, score, glossary, sequence, term tags]`
+        for row in rows {
+            let (Some(spelling), Some(reading), Some(glossary)) = (
+                row.first().and_then(Value::as_str),
+                row.get(1).and_then(Value::as_str),
+                row.get(5).and_then(Value::as_array),
+            ) else {
+                continue;
+            };
+
+            let mut images = Vec::new();
+            let glosses: Vec<String> = glossary
+                .iter()
+                .map(|gloss| {
+                    let mut text = String::new();
+                    read_gloss(gloss, &mut text, &mut images);
+                    text.trim().to_owned()
+                })
+                .filter(|text| !text.is_empty())
+                .collect();
+
+            if glosses.is_empty() && images.is_empty() {
+                continue;
+            }
+
+            let images = images
+                .into_iter()
+                .map(|path| folder.join(path).to_string_lossy().into_owned());
+
+            let reading = match reading == spelling {
+                true => String::new(),
+                false => reading.to_owned(),
+            };
+
+            // a headword can be split into several rows, one per sense
+            let candidates = entries.entry(spelling.to_owned()).or_default();
+            match candidates.iter_mut().find(|entry| entry.reading == reading) {
+                Some(entry) => {
+                    entry.glosses.extend(glosses);
+                    entry.images.extend(images);
+                }
+                None => candidates.push(Entry {
+                    reading,
+                    glosses,
+                    images: images.collect(),
+                }),
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Append the text of a gloss to `text`, and the paths of its images (relative to the dictionary's
+/// folder) to `images`. Glosses are either plain strings, or structured content made of nested nodes.
+fn read_gloss(gloss: &Value, text: &mut String, images: &mut Vec<String>) {
+    match gloss {
+        Value::String(s) => text.push_str(s),
+        Value::Array(nodes) => nodes.iter().for_each(|node| read_gloss(node, text, images)),
+        Value::Object(node) => {
+            let kind = node.get("type").and_then(Value::as_str);
+            let tag = node.get("tag").and_then(Value::as_str);
+
+            if kind == Some("image") || tag == Some("img") {
+                if let Some(path) = node.get("path").and_then(Value::as_str) {
+                    images.push(path.to_owned());
+                }
+            } else if kind == Some("text") {
+                read_gloss(node.get("text").unwrap_or(&Value::Null), text, images);
+            } else {
+                if tag == Some("br") {
+                    text.push(' ');
+                }
+                if let Some(content) = node.get("content") {
+                    read_gloss(content, text, images);
+                }
+            }
+        }
+        _ => {}
+    }
+}
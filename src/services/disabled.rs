@@ -0,0 +1,122 @@
+use std::borrow::Cow;
+
+use anyhow::{anyhow, Result};
+use eframe::egui;
+use image::RgbaImage;
+
+use crate::{capture::CaptureTarget, word::Word};
+
+use super::{
+    capture::CaptureService,
+    dictionary::{DictionaryService, DictionaryServiceJob},
+    ocr::{OcrService, OcrServiceJob},
+    srs::{CardState, SrsService, UnderlineStyle},
+    ServiceJob,
+};
+
+/// Stands in for every service in safe mode, so that a broken service or service configuration
+/// can't prevent deckocr from starting. Every request fails.
+pub struct Disabled;
+
+fn disabled<T>() -> Result<T> {
+    Err(anyhow!(
+        "Safe mode: Services are disabled, restart deckocr without `--safe-mode` to use them"
+    ))
+}
+
+impl CaptureService for Disabled {
+    fn init(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn terminate(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn show_config_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Disabled in safe mode.");
+    }
+
+    fn capture(&mut self, _target: &CaptureTarget) -> Result<RgbaImage> {
+        disabled()
+    }
+}
+
+impl OcrService for Disabled {
+    fn init(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn terminate(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn show_config_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Disabled in safe mode.");
+    }
+
+    fn ocr(&mut self, _image: RgbaImage) -> OcrServiceJob {
+        ServiceJob::new(disabled)
+    }
+
+    fn warm_up(&mut self) -> ServiceJob<Result<()>> {
+        ServiceJob::new(disabled)
+    }
+
+    fn ensure_ready(&mut self) -> ServiceJob<Result<()>> {
+        ServiceJob::new(disabled)
+    }
+}
+
+impl DictionaryService for Disabled {
+    fn init(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn terminate(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn show_config_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Disabled in safe mode.");
+    }
+
+    fn parse(&mut self, _paragraphs: Vec<String>) -> DictionaryServiceJob {
+        ServiceJob::new(disabled)
+    }
+
+    fn warm_up(&mut self) -> ServiceJob<Result<()>> {
+        ServiceJob::new(disabled)
+    }
+}
+
+impl SrsService for Disabled {
+    fn init(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn terminate(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn show_config_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Disabled in safe mode.");
+    }
+
+    fn load_card_states(&mut self, _words: Vec<Word>) -> ServiceJob<Result<()>> {
+        ServiceJob::new(disabled)
+    }
+
+    fn add_to_deck(&mut self, _word: &Word, _occurrences: u32) -> ServiceJob<Result<bool>> {
+        ServiceJob::new(disabled)
+    }
+
+    fn card_state(&self, _word: &Word) -> Cow<'_, CardState> {
+        Cow::Owned(CardState {
+            name: "Disabled".to_owned(),
+            colour: [255, 255, 255],
+            is_relevant: false,
+            underline: UnderlineStyle::None,
+        })
+    }
+}
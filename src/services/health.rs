@@ -0,0 +1,101 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+
+/// How long a service is considered degraded after one of its calls failed.
+const DEGRADED_DURATION: Duration = Duration::from_secs(5 * 60);
+
+/// The kinds of services, for tracking their health.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceKind {
+    Ocr,
+    Dictionary,
+    Srs,
+    Capture,
+}
+
+impl ServiceKind {
+    fn index(self) -> usize {
+        match self {
+            Self::Ocr => 0,
+            Self::Dictionary => 1,
+            Self::Srs => 2,
+            Self::Capture => 3,
+        }
+    }
+}
+
+/// Summary of a service's health, based on the results of its last calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthStatus {
+    /// The service hasn't been called yet.
+    Unknown,
+    /// The service's last call succeeded, and no call failed recently.
+    Healthy,
+    /// The service's last call succeeded, but a call failed recently.
+    Degraded,
+    /// The service's last call failed.
+    Failing,
+}
+
+impl HealthStatus {
+    /// Colour representing this status.
+    pub fn colour(self) -> Option<[u8; 3]> {
+        match self {
+            Self::Unknown => None,
+            Self::Healthy => Some([125, 255, 125]),
+            Self::Degraded => Some([255, 200, 60]),
+            Self::Failing => Some([255, 75, 60]),
+        }
+    }
+}
+
+/// Results of a service's last calls.
+#[derive(Debug, Clone, Default)]
+pub struct ServiceHealth {
+    /// Whether the last call succeeded, if there was one.
+    pub last_result_ok: Option<bool>,
+    /// The last error returned by the service, and when it happened.
+    pub last_error: Option<(Instant, String)>,
+}
+
+impl ServiceHealth {
+    pub fn status(&self) -> HealthStatus {
+        match self.last_result_ok {
+            None => HealthStatus::Unknown,
+            Some(false) => HealthStatus::Failing,
+            Some(true) => match &self.last_error {
+                Some((at, _)) if at.elapsed() < DEGRADED_DURATION => HealthStatus::Degraded,
+                _ => HealthStatus::Healthy,
+            },
+        }
+    }
+}
+
+static HEALTH: Mutex<[Option<ServiceHealth>; 4]> = Mutex::new([None, None, None, None]);
+
+/// Record the result of a call to the given service.
+pub fn record<T>(service: ServiceKind, result: &Result<T>) {
+    let mut health = HEALTH.lock().unwrap();
+    let health = health[service.index()].get_or_insert_with(Default::default);
+
+    health.last_result_ok = Some(result.is_ok());
+    if let Err(e) = result {
+        health.last_error = Some((Instant::now(), format!("{e:#}")));
+    }
+}
+
+/// Forget everything recorded about the given service, eg. because it was restarted.
+pub fn reset(service: ServiceKind) {
+    HEALTH.lock().unwrap()[service.index()] = None;
+}
+
+/// Health of the given service.
+pub fn get(service: ServiceKind) -> ServiceHealth {
+    HEALTH.lock().unwrap()[service.index()]
+        .clone()
+        .unwrap_or_default()
+}
@@ -0,0 +1,263 @@
+use std::{
+    hash::{BuildHasher, RandomState},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        LazyLock, Mutex, RwLock,
+    },
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+
+use crate::config::AppConfig;
+
+/// Timeout applied to every HTTP request made by services.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Agent shared by every request, so that connections (and their TLS sessions) to the same host
+/// are kept alive and reused between requests. Responses are transparently decompressed.
+static AGENT: LazyLock<RwLock<ureq::Agent>> = LazyLock::new(|| RwLock::new(new_agent()));
+
+fn new_agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout(REQUEST_TIMEOUT)
+        .max_idle_connections_per_host(4)
+        .user_agent(concat!(
+            env!("CARGO_PKG_NAME"),
+            "/",
+            env!("CARGO_PKG_VERSION")
+        ))
+        .build()
+}
+
+/// Drop all pooled connections, eg. after they were killed by the system being suspended.
+pub fn reset_connections() {
+    *AGENT.write().unwrap() = new_agent();
+}
+
+/// How failed HTTP requests should be retried.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub attempts: u32,
+    /// Delay before the first retry. Doubled on every subsequent retry.
+    pub base_delay: Duration,
+}
+
+static RETRY_POLICY: RwLock<RetryPolicy> = RwLock::new(RetryPolicy {
+    attempts: 3,
+    base_delay: Duration::from_millis(500),
+});
+
+/// Apply the network settings from the given `AppConfig` to all subsequent requests.
+pub fn configure(config: &AppConfig) {
+    *RETRY_POLICY.write().unwrap() = RetryPolicy {
+        attempts: config.network_retry_attempts.max(1),
+        base_delay: Duration::from_millis(config.network_retry_base_delay_ms),
+    };
+}
+
+/// A request recorded while dry-run mode was enabled.
+#[derive(Debug, Clone)]
+pub struct DryRunRequest {
+    pub timestamp: SystemTime,
+    pub url: String,
+    /// The request's body, with anything that looks like a credential redacted.
+    pub body: Value,
+    /// Whether the request was actually sent. Requests with side effects never are.
+    pub sent: bool,
+}
+
+/// Maximum number of requests kept in the dry-run log.
+const DRY_RUN_LOG_CAPACITY: usize = 100;
+
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+static DRY_RUN_LOG: Mutex<Vec<DryRunRequest>> = Mutex::new(Vec::new());
+
+/// Enable or disable dry-run mode. While enabled, all requests are recorded, and requests with
+/// side effects are not sent.
+pub fn set_dry_run(enabled: bool) {
+    DRY_RUN.store(enabled, Ordering::Relaxed);
+}
+
+/// Requests recorded while dry-run mode was enabled, oldest first.
+pub fn dry_run_log() -> Vec<DryRunRequest> {
+    DRY_RUN_LOG.lock().unwrap().clone()
+}
+
+/// Forget all requests recorded while dry-run mode was enabled.
+pub fn clear_dry_run_log() {
+    DRY_RUN_LOG.lock().unwrap().clear();
+}
+
+/// Fields redacted from the payloads recorded in dry-run mode, as they hold credentials. Only exact
+/// names are matched, so that eg. jpdb's `token_fields` is still shown.
+const SECRET_FIELDS: &[&str] = &["api_key", "apikey", "key", "token", "password", "secret"];
+
+fn record_dry_run(url: &str, body: &Value, sent: bool) {
+    fn redact(value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    if SECRET_FIELDS.contains(&key.to_lowercase().as_str()) {
+                        *value = Value::String("<redacted>".to_owned());
+                    } else {
+                        redact(value);
+                    }
+                }
+            }
+            Value::Array(array) => array.iter_mut().for_each(redact),
+            _ => {}
+        }
+    }
+
+    let mut body = body.clone();
+    redact(&mut body);
+
+    let mut log = DRY_RUN_LOG.lock().unwrap();
+    if log.len() >= DRY_RUN_LOG_CAPACITY {
+        log.remove(0);
+    }
+    log.push(DryRunRequest {
+        timestamp: SystemTime::now(),
+        url: url.to_owned(),
+        body,
+        sent,
+    });
+}
+
+/// Like `post_json`, but for requests with side effects (eg. adding a card to a deck): in
+/// dry-run mode, the request is recorded but not sent, and `Ok(None)` is returned.
+pub fn post_json_with_side_effects(
+    url: &str,
+    api_key: &str,
+    body: &Value,
+) -> Result<Option<Value>> {
+    if DRY_RUN.load(Ordering::Relaxed) {
+        log::info!("Dry run: not sending request to `{url}`");
+        record_dry_run(url, body, false);
+        return Ok(None);
+    }
+
+    post_json(url, api_key, body).map(Some)
+}
+
+/// Send a POST request with a JSON body to a webhook, ignoring the response's body. Requests are
+/// not retried, and are never sent in dry-run mode.
+///
+/// Webhook URLs contain their own credentials, so only the URL's origin is recorded in dry-run mode.
+pub fn post_webhook(url: &str, body: &Value) -> Result<()> {
+    if DRY_RUN.load(Ordering::Relaxed) {
+        let origin = url.splitn(4, '/').take(3).collect::<Vec<_>>().join("/");
+        log::info!("Dry run: not sending request to webhook at `{origin}`");
+        record_dry_run(&format!("{origin}/<redacted>"), body, false);
+        return Ok(());
+    }
+
+    AGENT
+        .read()
+        .unwrap()
+        .post(url)
+        .send_json(body)
+        // ureq's errors include the URL, which shouldn't end up in logs
+        .map_err(|e| match e {
+            ureq::Error::Status(status, _) => {
+                anyhow!("Webhook responded with status code {status}")
+            }
+            ureq::Error::Transport(transport) => {
+                anyhow!("Failed to send webhook request: {}", transport.kind())
+            }
+        })?;
+
+    Ok(())
+}
+
+/// Send a POST request with a JSON body and bearer authentication, and parse the response as JSON.
+///
+/// Timeouts, connection errors and 5xx responses are retried according to the configured
+/// `RetryPolicy`, with exponential backoff and jitter.
+pub fn post_json(url: &str, api_key: &str, body: &Value) -> Result<Value> {
+    if DRY_RUN.load(Ordering::Relaxed) {
+        record_dry_run(url, body, true);
+    }
+
+    let policy = *RETRY_POLICY.read().unwrap();
+
+    let mut attempt = 1;
+    loop {
+        match try_post_json(url, api_key, body) {
+            Ok(value) => return Ok(value),
+            Err(Failure::Permanent(e)) => return Err(e),
+            Err(Failure::Transient(e)) if attempt >= policy.attempts => {
+                return Err(e.context(format!("Giving up after {attempt} attempt(s)")))
+            }
+            Err(Failure::Transient(e)) => {
+                let delay = backoff_delay(&policy, attempt);
+                log::warn!(
+                    "Request to `{url}` failed (attempt {attempt}/{}), retrying in {}ms: {e:#}",
+                    policy.attempts,
+                    delay.as_millis()
+                );
+                std::thread::sleep(delay);
+                attempt += 1;
+            }
+        }
+    }
+}
+
+enum Failure {
+    /// The request may succeed if tried again.
+    Transient(anyhow::Error),
+    /// Retrying will not help.
+    Permanent(anyhow::Error),
+}
+
+fn try_post_json(url: &str, api_key: &str, body: &Value) -> Result<Value, Failure> {
+    let agent = AGENT.read().unwrap().clone();
+
+    let response = agent
+        .post(url)
+        .set("Authorization", &format!("Bearer {api_key}"))
+        .send_json(body)
+        .map_err(|e| match e {
+            ureq::Error::Status(status, _) => {
+                let e =
+                    anyhow!("Response status code from `{url}` is not a success code: {status}");
+                match status >= 500 {
+                    true => Failure::Transient(e),
+                    false => Failure::Permanent(e),
+                }
+            }
+            ureq::Error::Transport(transport) => {
+                let transient = matches!(
+                    transport.kind(),
+                    ureq::ErrorKind::Io | ureq::ErrorKind::ConnectionFailed | ureq::ErrorKind::Dns
+                );
+                let e =
+                    anyhow!(transport).context(format!("Failed to send http request to `{url}`"));
+                match transient {
+                    true => Failure::Transient(e),
+                    false => Failure::Permanent(e),
+                }
+            }
+        })?;
+
+    response
+        .into_json()
+        .with_context(|| format!("Response from `{url}` is not valid json"))
+        .map_err(Failure::Permanent)
+}
+
+/// Exponential backoff with full jitter: a random delay between zero and `base_delay * 2^(attempt - 1)`.
+fn backoff_delay(policy: &RetryPolicy, attempt: u32) -> Duration {
+    let max_delay = policy
+        .base_delay
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)));
+
+    // `RandomState` is randomly seeded, which is plenty for jitter
+    let random = RandomState::new().hash_one(attempt);
+    let fraction = (random % 1000) as f64 / 1000.0;
+
+    max_delay.mul_f64(fraction)
+}
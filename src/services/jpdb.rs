@@ -0,0 +1,198 @@
+use std::sync::RwLock;
+
+use anyhow::{Context, Result};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{self, Config},
+    services::{dictionary::jpdb_dictionary::JpdbDictionaryConfig, srs::jpdb_srs::JpdbSrsConfig},
+};
+
+/// Accounts shared by every jpdb service, loaded by whichever is initialised first.
+static ACCOUNTS: RwLock<JpdbAccounts> = RwLock::new(JpdbAccounts {
+    accounts: Vec::new(),
+    selected: None,
+});
+
+/// Name of the account used instead of the selected one while reading a capture preset's
+/// captures, if the preset has one.
+static CAPTURE_ACCOUNT: RwLock<Option<String>> = RwLock::new(None);
+
+/// A jpdb account, and the API key used to access it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct JpdbAccount {
+    pub name: String,
+    pub api_key: String,
+}
+
+/// Credentials store for jpdb, shared by every jpdb service so that API keys only need to be
+/// entered once, and several accounts (eg. separate study accounts) can be switched between.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JpdbAccounts {
+    pub accounts: Vec<JpdbAccount>,
+    /// Index of the account used by the jpdb services, if one is selected.
+    pub selected: Option<usize>,
+}
+
+impl Config for JpdbAccounts {
+    fn path() -> &'static str {
+        "jpdb_accounts.json"
+    }
+
+    fn show_ui(&mut self, ui: &mut egui::Ui) {
+        let none_label = "None";
+
+        ui.horizontal(|ui| {
+            ui.label("Account:");
+            egui::ComboBox::from_id_salt("jpdb_account")
+                .selected_text(
+                    self.selected
+                        .and_then(|idx| self.accounts.get(idx))
+                        .map_or(none_label, |account| &account.name),
+                )
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.selected, None, none_label);
+                    for (idx, account) in self.accounts.iter().enumerate() {
+                        ui.selectable_value(&mut self.selected, Some(idx), &account.name);
+                    }
+                });
+        });
+
+        let mut removed = None;
+
+        for (idx, account) in self.accounts.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut account.name).desired_width(120.0));
+                ui.label("API Key:");
+                ui.add(egui::TextEdit::singleline(&mut account.api_key).password(true));
+                if ui.button("Remove").clicked() {
+                    removed = Some(idx);
+                }
+            });
+        }
+
+        if let Some(removed) = removed {
+            self.accounts.remove(removed);
+            self.selected = match self.selected {
+                Some(selected) if selected == removed => None,
+                Some(selected) if selected > removed => Some(selected - 1),
+                selected => selected,
+            };
+        }
+
+        if ui.button("Add Account").clicked() {
+            self.accounts.push(JpdbAccount {
+                name: format!("Account {}", self.accounts.len() + 1),
+                api_key: String::new(),
+            });
+            self.selected.get_or_insert(self.accounts.len() - 1);
+        }
+    }
+}
+
+/// Load the accounts from their configuration file, migrating the API keys from the jpdb
+/// services' configuration files if it doesn't exist yet.
+pub fn load_accounts() -> Result<()> {
+    let mut path = config::config_dir()?;
+    path.push(JpdbAccounts::path());
+
+    let accounts = match path.exists() {
+        true => JpdbAccounts::load().context("jpdb: Failed to load accounts file")?,
+        false => {
+            let accounts = migrate_legacy_api_keys();
+            accounts
+                .save()
+                .context("jpdb: Failed to save migrated accounts file")?;
+            accounts
+        }
+    };
+
+    *ACCOUNTS.write().unwrap() = accounts;
+    Ok(())
+}
+
+/// Create accounts from the `api_key` fields the jpdb services' configuration files used to have.
+fn migrate_legacy_api_keys() -> JpdbAccounts {
+    let mut accounts = JpdbAccounts::default();
+
+    for path in [JpdbDictionaryConfig::path(), JpdbSrsConfig::path()] {
+        let Some(api_key) = (|| -> Option<String> {
+            let mut config_path = config::config_dir().ok()?;
+            config_path.push(path);
+            let file = std::fs::File::open(config_path).ok()?;
+            let json: serde_json::Value = serde_json::from_reader(file).ok()?;
+            Some(json.get("api_key")?.as_str()?.to_owned())
+        })() else {
+            continue;
+        };
+
+        if api_key.is_empty() || accounts.accounts.iter().any(|a| a.api_key == api_key) {
+            continue;
+        }
+
+        log::info!("jpdb: Migrating API key from `{path}` to the shared jpdb accounts");
+        accounts.accounts.push(JpdbAccount {
+            name: format!("Account {}", accounts.accounts.len() + 1),
+            api_key,
+        });
+    }
+
+    if accounts.accounts.len() > 1 {
+        log::warn!("jpdb: The dictionary and SRS services used different API keys, both jpdb services will now use `Account 1`");
+    }
+
+    accounts.selected = (!accounts.accounts.is_empty()).then_some(0);
+    accounts
+}
+
+/// Save the accounts to their configuration file.
+pub fn save_accounts() -> Result<()> {
+    ACCOUNTS
+        .read()
+        .unwrap()
+        .save()
+        .context("jpdb: Failed to save accounts file")
+}
+
+/// The API key of the account used for the current capture, or of the selected account.
+pub fn api_key() -> Result<String> {
+    let accounts = ACCOUNTS.read().unwrap();
+    let capture_account = CAPTURE_ACCOUNT.read().unwrap();
+
+    // falls back to the selected account if the preset's one was renamed or removed
+    capture_account
+        .as_ref()
+        .and_then(|name| {
+            accounts
+                .accounts
+                .iter()
+                .find(|account| account.name == *name)
+        })
+        .or_else(|| accounts.selected.and_then(|idx| accounts.accounts.get(idx)))
+        .map(|account| account.api_key.clone())
+        .context("jpdb: No jpdb account selected")
+}
+
+/// Use the account with the given name instead of the selected one until this is called again,
+/// eg. while reading the captures of a preset with its own account.
+pub fn use_capture_account(name: Option<String>) {
+    *CAPTURE_ACCOUNT.write().unwrap() = name;
+}
+
+/// Names of the accounts, in the order they were added.
+pub fn account_names() -> Vec<String> {
+    ACCOUNTS
+        .read()
+        .unwrap()
+        .accounts
+        .iter()
+        .map(|account| account.name.clone())
+        .collect()
+}
+
+/// Show the UI for managing the jpdb accounts and selecting the one to use.
+pub fn accounts_ui(ui: &mut egui::Ui) {
+    ACCOUNTS.write().unwrap().show_ui(ui);
+}
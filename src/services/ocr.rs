@@ -1,12 +1,20 @@
+use std::time::Duration;
+
 use anyhow::Result;
 use eframe::egui::{self, Rect};
 use image::RgbaImage;
+use serde::{Deserialize, Serialize};
 
 use super::ServiceJob;
 
 pub mod owocr;
 
-pub type OcrServiceJob = ServiceJob<Result<OcrResponse>>;
+/// Job returning the text recognised by an OCR service, along with the time the service took to
+/// encode the image before sending it, if it had to.
+pub type OcrServiceJob = ServiceJob<Result<(OcrResponse, Option<Duration>)>>;
+
+/// A paragraph and its bounds on the screen.
+pub type ParagraphWithRect = (Rect, String);
 
 pub trait OcrService {
     /// Initialise the service (ie. load its configuration file, etc).
@@ -19,14 +27,238 @@ pub trait OcrService {
 
     /// Extract text from an image, returning a list of paragraphs.
     fn ocr(&mut self, image: RgbaImage) -> OcrServiceJob;
+
+    /// Send a tiny request to the service so that the first real request doesn't pay cold-start costs.
+    fn warm_up(&mut self) -> ServiceJob<Result<()>>;
+
+    /// Check that the service can be reached before a capture is taken, starting it if possible.
+    /// The job returns an error if it can't be reached and wasn't started.
+    fn ensure_ready(&mut self) -> ServiceJob<Result<()>> {
+        ServiceJob::new(|| Ok(()))
+    }
 }
 
 /// The data returned by an OCR service.
 pub enum OcrResponse {
     /// A list of paragraphs with their associated text bounds.
     ///
-    /// NOTE: This is not currently supported.
-    WithRects(Vec<(Rect, String)>),
+    /// NOTE: the bounds are only used to sort paragraphs into reading order for now.
+    WithRects(Vec<ParagraphWithRect>),
     /// A simple list of paragraphs.
     WithoutRects(Vec<String>),
 }
+
+impl OcrResponse {
+    /// Bounds of all the paragraphs together, if they are known.
+    pub fn text_bounds(&self) -> Option<Rect> {
+        match self {
+            Self::WithRects(paragraphs) => {
+                paragraphs.iter().map(|(rect, _)| *rect).reduce(Rect::union)
+            }
+            Self::WithoutRects(_) => None,
+        }
+    }
+
+    /// Extract the paragraphs, sorting them in the given order if their bounds are known.
+    pub fn into_paragraphs(self, order: ParagraphOrder) -> Vec<String> {
+        match self {
+            Self::WithRects(paragraphs) => order
+                .sort(paragraphs)
+                .into_iter()
+                .map(|(_, text)| text)
+                .collect(),
+            Self::WithoutRects(paragraphs) => paragraphs,
+        }
+    }
+}
+
+/// Merge paragraphs with fewer than `min_chars` characters (not counting whitespace), eg. stray UI
+/// glyphs or particles split off by the OCR, into the paragraph following them, or into the
+/// previous one for the last paragraph. Short paragraphs are dropped instead if `merge` is false.
+pub fn filter_short_paragraphs(
+    paragraphs: Vec<String>,
+    min_chars: usize,
+    merge: bool,
+) -> Vec<String> {
+    let is_short =
+        |paragraph: &str| paragraph.chars().filter(|c| !c.is_whitespace()).count() < min_chars;
+
+    if !merge {
+        return paragraphs
+            .into_iter()
+            .filter(|paragraph| !is_short(paragraph))
+            .collect();
+    }
+
+    let mut filtered: Vec<String> = Vec::new();
+    let mut carried = String::new();
+
+    for paragraph in paragraphs {
+        carried.push_str(&paragraph);
+        if !is_short(&carried) {
+            filtered.push(std::mem::take(&mut carried));
+        }
+    }
+
+    if !carried.is_empty() {
+        match filtered.last_mut() {
+            Some(last) => last.push_str(&carried),
+            None => filtered.push(carried),
+        }
+    }
+
+    filtered
+}
+
+/// The order in which paragraphs should be read, when OCR services return their bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParagraphOrder {
+    /// Keep the order returned by the OCR service.
+    AsReturned,
+    /// Horizontal text: lines from top to bottom, paragraphs on the same line from left to right.
+    TopToBottom,
+    /// Vertical text (eg. manga): columns from right to left, paragraphs in the same column from top to bottom.
+    RightToLeftColumns,
+}
+
+impl ParagraphOrder {
+    pub const ALL: &'static [Self] = &[
+        Self::AsReturned,
+        Self::TopToBottom,
+        Self::RightToLeftColumns,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::AsReturned => "As Returned by OCR",
+            Self::TopToBottom => "Top to Bottom",
+            Self::RightToLeftColumns => "Right to Left Columns",
+        }
+    }
+
+    /// Sort paragraphs in this order.
+    pub fn sort(&self, mut paragraphs: Vec<ParagraphWithRect>) -> Vec<ParagraphWithRect> {
+        match self {
+            Self::AsReturned => paragraphs,
+            Self::TopToBottom => {
+                paragraphs.sort_by(|(a, _), (b, _)| a.min.y.total_cmp(&b.min.y));
+                group_into_lines(paragraphs, |rect| (rect.min.y, rect.max.y))
+                    .into_iter()
+                    .flat_map(|mut line| {
+                        line.sort_by(|(a, _), (b, _)| a.min.x.total_cmp(&b.min.x));
+                        line
+                    })
+                    .collect()
+            }
+            Self::RightToLeftColumns => {
+                paragraphs.sort_by(|(a, _), (b, _)| b.max.x.total_cmp(&a.max.x));
+                group_into_lines(paragraphs, |rect| (-rect.max.x, -rect.min.x))
+                    .into_iter()
+                    .flat_map(|mut column| {
+                        column.sort_by(|(a, _), (b, _)| a.min.y.total_cmp(&b.min.y));
+                        column
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Group paragraphs (sorted by the start of their `span`) into lines: a paragraph belongs to the
+/// current line if the center of its span falls within the line's span.
+fn group_into_lines(
+    paragraphs: Vec<ParagraphWithRect>,
+    span: impl Fn(&Rect) -> (f32, f32),
+) -> Vec<Vec<ParagraphWithRect>> {
+    let mut lines: Vec<(f32, f32, Vec<ParagraphWithRect>)> = Vec::new();
+
+    for paragraph in paragraphs {
+        let (start, end) = span(&paragraph.0);
+        let center = (start + end) / 2.0;
+
+        match lines.last_mut() {
+            Some((line_start, line_end, line)) if (*line_start..=*line_end).contains(&center) => {
+                *line_end = line_end.max(end);
+                line.push(paragraph);
+            }
+            _ => lines.push((start, end, vec![paragraph])),
+        }
+    }
+
+    lines.into_iter().map(|(_, _, line)| line).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use eframe::egui::{pos2, vec2};
+
+    use super::*;
+
+    fn paragraph(x: f32, y: f32, width: f32, height: f32, text: &str) -> ParagraphWithRect {
+        (
+            Rect::from_min_size(pos2(x, y), vec2(width, height)),
+            text.to_owned(),
+        )
+    }
+
+    fn texts(paragraphs: Vec<ParagraphWithRect>) -> Vec<String> {
+        paragraphs.into_iter().map(|(_, text)| text).collect()
+    }
+
+    #[test]
+    fn as_returned() {
+        let paragraphs = vec![
+            paragraph(0.0, 100.0, 50.0, 20.0, "b"),
+            paragraph(0.0, 0.0, 50.0, 20.0, "a"),
+        ];
+
+        assert_eq!(
+            texts(ParagraphOrder::AsReturned.sort(paragraphs)),
+            ["b", "a"]
+        );
+    }
+
+    #[test]
+    fn top_to_bottom() {
+        // the second paragraph of the first line is slightly lower, but still on the same line
+        let paragraphs = vec![
+            paragraph(0.0, 50.0, 100.0, 20.0, "c"),
+            paragraph(120.0, 4.0, 100.0, 20.0, "b"),
+            paragraph(0.0, 0.0, 100.0, 20.0, "a"),
+            paragraph(120.0, 52.0, 100.0, 20.0, "d"),
+        ];
+
+        assert_eq!(
+            texts(ParagraphOrder::TopToBottom.sort(paragraphs)),
+            ["a", "b", "c", "d"]
+        );
+    }
+
+    #[test]
+    fn right_to_left_columns() {
+        // the second paragraph of the right column is slightly to the left, but still in the same column
+        let paragraphs = vec![
+            paragraph(0.0, 0.0, 20.0, 100.0, "c"),
+            paragraph(56.0, 120.0, 20.0, 100.0, "b"),
+            paragraph(60.0, 0.0, 20.0, 100.0, "a"),
+            paragraph(0.0, 120.0, 20.0, 100.0, "d"),
+        ];
+
+        assert_eq!(
+            texts(ParagraphOrder::RightToLeftColumns.sort(paragraphs)),
+            ["a", "b", "c", "d"]
+        );
+    }
+
+    #[test]
+    fn lines_grouped_by_center() {
+        // the second paragraph overlaps the first, but its center is below it
+        let paragraphs = vec![
+            paragraph(0.0, 0.0, 100.0, 20.0, "a"),
+            paragraph(0.0, 15.0, 100.0, 20.0, "b"),
+        ];
+
+        let lines = group_into_lines(paragraphs, |rect| (rect.min.y, rect.max.y));
+        assert_eq!(lines.len(), 2);
+    }
+}
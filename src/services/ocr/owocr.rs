@@ -1,23 +1,45 @@
-use std::io::Cursor;
-
-use anyhow::{Context, Result};
+use std::{
+    io::Cursor,
+    net::{TcpStream, ToSocketAddrs},
+    process::{Child, Command},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context, Result};
 use eframe::egui;
 use image::{ImageFormat, RgbaImage};
 use serde::{Deserialize, Serialize};
 
-use crate::{config::Config, services::ServiceJob};
+use crate::{
+    config::Config,
+    services::{health::ServiceKind, ServiceJob},
+};
+
+use super::{OcrResponse, OcrService, OcrServiceJob};
 
-use super::{OcrResponse, OcrService};
+/// How long the readiness check waits for owocr to accept a connection.
+const READINESS_TIMEOUT: Duration = Duration::from_millis(500);
+/// How long an OCR request waits for owocr to be up after starting it, as it loads its models.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
 
 #[derive(Default)]
 pub struct Owocr {
     config: OwocrConfig,
+    /// owocr's process and when it was started, if it was started by deckocr. Shared with the
+    /// readiness checks, which start it in the background.
+    child: Arc<Mutex<Option<(Child, Instant)>>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OwocrConfig {
     address: String,
     port: u16,
+    /// Command starting owocr when it isn't running on capture, followed by its arguments
+    /// separated by spaces. Empty to never start it.
+    #[serde(default)]
+    auto_start_command: String,
 }
 
 impl Default for OwocrConfig {
@@ -25,6 +47,7 @@ impl Default for OwocrConfig {
         Self {
             address: "127.0.0.1".to_owned(),
             port: 7331,
+            auto_start_command: String::new(),
         }
     }
 }
@@ -35,7 +58,9 @@ impl Config for OwocrConfig {
     }
 
     fn show_ui(&mut self, ui: &mut egui::Ui) {
-        ui.label("Make sure you start owocr separately!");
+        if self.auto_start_command.is_empty() {
+            ui.label("Make sure you start owocr separately, or set a command to start it with!");
+        }
         ui.horizontal(|ui| {
             ui.label("Address:");
             ui.text_edit_singleline(&mut self.address);
@@ -44,6 +69,11 @@ impl Config for OwocrConfig {
             ui.label("Port:");
             ui.add(egui::DragValue::new(&mut self.port));
         });
+        ui.horizontal(|ui| {
+            ui.label("Auto-Start Command:");
+            ui.text_edit_singleline(&mut self.auto_start_command)
+                .on_hover_text("Started when owocr can't be reached as a capture is taken, followed by its\narguments, eg. `owocr -r websocket -w clipboard`. Stopped when deckocr exits.");
+        });
     }
 }
 
@@ -54,6 +84,12 @@ impl OcrService for Owocr {
     }
 
     fn terminate(&mut self) -> anyhow::Result<()> {
+        if let Some((mut child, _)) = self.child.lock().unwrap().take() {
+            log::info!("Owocr: Stopping owocr");
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+
         self.config
             .save()
             .context("Owocr: Failed to save configuration file")?;
@@ -64,38 +100,146 @@ impl OcrService for Owocr {
         self.config.show_ui(ui);
     }
 
-    fn ocr(&mut self, image: RgbaImage) -> ServiceJob<Result<OcrResponse>> {
+    fn ocr(&mut self, image: RgbaImage) -> OcrServiceJob {
         let addr = format!("ws://{}:{}", self.config.address, self.config.port);
+        let startup = self.remaining_startup_time();
 
-        ServiceJob::new(move || {
-            let mut buf = Cursor::new(Vec::new());
-            image.write_to(&mut buf, ImageFormat::Png).unwrap();
-
-            let (mut socket, _) = tungstenite::connect(&addr)
-                .with_context(|| format!("Owocr: Failed to connect to websocket `{addr}`"))?;
-
-            socket
-                .send(tungstenite::Message::binary(buf.into_inner()))
-                .context("Owocr: Failed to send image through websocket")?;
-            // NOTE: owocr sends a text message containing just "True" the socket is first connected to. we need to consume it
-            socket
-                .read()
-                .context("Owocr: Failed to read confirmation message from websocket")?;
-            let text = socket
-                .read()
-                .context("Owocr: Failed to read response message from websocket")?
-                .into_text()
-                .context(
-                    "Owocr: Response message from websocket did not contain UTF-8 encoded text",
-                )?;
-
-            socket
-                .close(None)
-                .context("Owocr: Failed to close websocket")?;
+        ServiceJob::tracked(ServiceKind::Ocr, move || {
+            if let Some((address, remaining)) = startup {
+                wait_until_reachable(&address, remaining)?;
+            }
 
+            let (text, encode_time) = send_image(&addr, image)?;
             let text = text.split('\u{3000}').map(str::to_owned).collect();
 
-            Ok(OcrResponse::WithoutRects(text))
+            Ok((OcrResponse::WithoutRects(text), Some(encode_time)))
+        })
+    }
+
+    fn warm_up(&mut self) -> ServiceJob<Result<()>> {
+        let addr = format!("ws://{}:{}", self.config.address, self.config.port);
+
+        // a blank image is enough to get owocr to load its models
+        ServiceJob::tracked(ServiceKind::Ocr, move || {
+            send_image(&addr, RgbaImage::from_pixel(32, 32, image::Rgba([255; 4])))?;
+            Ok(())
+        })
+    }
+
+    fn ensure_ready(&mut self) -> ServiceJob<Result<()>> {
+        let address = format!("{}:{}", self.config.address, self.config.port);
+        let command = self.config.auto_start_command.clone();
+        let child = self.child.clone();
+
+        ServiceJob::tracked(ServiceKind::Ocr, move || {
+            if is_reachable(address.as_str()) {
+                return Ok(());
+            }
+
+            let mut child = child.lock().unwrap();
+            if let Some((process, _)) = child.as_mut() {
+                match process.try_wait() {
+                    // owocr may still be loading, OCR requests wait for it
+                    Ok(None) => return Ok(()),
+                    _ => {
+                        log::warn!("Owocr: owocr exited after being started");
+                        *child = None;
+                    }
+                }
+            }
+
+            let mut args = command.split_whitespace();
+            let Some(program) = args.next() else {
+                return Err(anyhow!(
+                    "Owocr: Could not connect to owocr at `{address}`, is it running?"
+                ));
+            };
+
+            log::info!("Owocr: owocr isn't running, starting it with `{program}`");
+            let process = Command::new(program)
+                .args(args)
+                .spawn()
+                .with_context(|| format!("Owocr: Could not start owocr with `{program}`"))?;
+            *child = Some((process, Instant::now()));
+
+            Ok(())
         })
     }
 }
+
+impl Owocr {
+    /// If owocr was started by deckocr and is still running, its address and how much longer
+    /// requests should wait for it to load.
+    fn remaining_startup_time(&mut self) -> Option<(String, Duration)> {
+        let mut child = self.child.lock().unwrap();
+        let (process, started_at) = child.as_mut()?;
+
+        if !matches!(process.try_wait(), Ok(None)) {
+            log::warn!("Owocr: owocr exited after being started");
+            *child = None;
+            return None;
+        }
+
+        // once it had time to load, requests fail right away if it can't be reached
+        let remaining = STARTUP_TIMEOUT.saturating_sub(started_at.elapsed());
+        Some((
+            format!("{}:{}", self.config.address, self.config.port),
+            remaining,
+        ))
+    }
+}
+
+/// Whether something accepts connections at the given address.
+fn is_reachable(address: impl ToSocketAddrs) -> bool {
+    address
+        .to_socket_addrs()
+        .into_iter()
+        .flatten()
+        .any(|addr| TcpStream::connect_timeout(&addr, READINESS_TIMEOUT).is_ok())
+}
+
+/// Wait until owocr accepts connections at the given address, for at most `timeout`.
+fn wait_until_reachable(address: &str, timeout: Duration) -> Result<()> {
+    let started_at = Instant::now();
+
+    while !is_reachable(address) {
+        if started_at.elapsed() >= timeout {
+            return Err(anyhow!(
+                "Owocr: owocr was started, but still couldn't be reached at `{address}`"
+            ));
+        }
+        thread::sleep(Duration::from_millis(250));
+    }
+
+    Ok(())
+}
+
+/// Send an image to owocr, returning the text it recognised and the time taken to encode the image.
+fn send_image(addr: &str, image: RgbaImage) -> Result<(String, Duration)> {
+    let encode_started_at = Instant::now();
+    let mut buf = Cursor::new(Vec::new());
+    image.write_to(&mut buf, ImageFormat::Png).unwrap();
+    let encode_time = encode_started_at.elapsed();
+
+    let (mut socket, _) = tungstenite::connect(addr)
+        .with_context(|| format!("Owocr: Failed to connect to websocket `{addr}`"))?;
+
+    socket
+        .send(tungstenite::Message::binary(buf.into_inner()))
+        .context("Owocr: Failed to send image through websocket")?;
+    // NOTE: owocr sends a text message containing just "True" the socket is first connected to. we need to consume it
+    socket
+        .read()
+        .context("Owocr: Failed to read confirmation message from websocket")?;
+    let text = socket
+        .read()
+        .context("Owocr: Failed to read response message from websocket")?
+        .into_text()
+        .context("Owocr: Response message from websocket did not contain UTF-8 encoded text")?;
+
+    socket
+        .close(None)
+        .context("Owocr: Failed to close websocket")?;
+
+    Ok((text.to_string(), encode_time))
+}
@@ -1,3 +1,8 @@
+use std::{
+    borrow::Cow,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
 use anyhow::Result;
 use eframe::egui;
 use serde::{Deserialize, Serialize};
@@ -8,6 +13,20 @@ use super::ServiceJob;
 
 pub mod jpdb_srs;
 
+/// Bumped every time an SRS service's card states change, so that anything showing them knows
+/// when to refresh.
+static CARD_STATES_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Record that card states changed. Called by SRS services after loading or updating them.
+pub fn card_states_changed() {
+    CARD_STATES_GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Number of times card states changed so far.
+pub fn card_states_generation() -> u64 {
+    CARD_STATES_GENERATION.load(Ordering::Relaxed)
+}
+
 pub trait SrsService {
     /// Initialise the service.
     fn init(&mut self) -> Result<()>;
@@ -20,10 +39,12 @@ pub trait SrsService {
     /// Query the card states for the given words and stores them inside the `SrsService` for later retrieval.
     fn load_card_states(&mut self, words: Vec<Word>) -> ServiceJob<Result<()>>;
     /// Add the given word to the user's mining deck and update its internal card state.
-    fn add_to_deck(&mut self, word: &Word) -> ServiceJob<Result<()>>;
+    /// `occurrences` is how many times the word was encountered in the text it was mined from.
+    /// The job returns whether the word was actually added, ie. `false` in dry-run mode.
+    fn add_to_deck(&mut self, word: &Word, occurrences: u32) -> ServiceJob<Result<bool>>;
 
     /// Retrieve the card state for a given word.
-    fn card_state(&self, word: &Word) -> &CardState;
+    fn card_state(&self, word: &Word) -> Cow<'_, CardState>;
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,4 +55,71 @@ pub struct CardState {
     pub colour: [u8; 3],
     /// If this is `false`, words this card state is associated with will be skipped when the user moves their selection while holding R2.
     pub is_relevant: bool,
+    /// How words with this card state are underlined in monochrome mode, where colours aren't shown.
+    #[serde(default)]
+    pub underline: UnderlineStyle,
+}
+
+/// Underline marking a card state in monochrome mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnderlineStyle {
+    #[default]
+    None,
+    Solid,
+    Dashed,
+    Dotted,
+    Double,
+    /// A dot below the middle of the word.
+    Marker,
+}
+
+impl UnderlineStyle {
+    pub const ALL: &'static [Self] = &[
+        Self::None,
+        Self::Solid,
+        Self::Dashed,
+        Self::Dotted,
+        Self::Double,
+        Self::Marker,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::Solid => "Solid",
+            Self::Dashed => "Dashed",
+            Self::Dotted => "Dotted",
+            Self::Double => "Double",
+            Self::Marker => "Marker",
+        }
+    }
+
+    /// Paint the underline below a word spanning `x_range`, starting at `y`.
+    pub fn paint(
+        &self,
+        painter: &egui::Painter,
+        x_range: egui::Rangef,
+        y: f32,
+        colour: egui::Color32,
+    ) {
+        let stroke = egui::Stroke::new(2.0, colour);
+        let points = [egui::pos2(x_range.min, y), egui::pos2(x_range.max, y)];
+
+        match self {
+            Self::None => {}
+            Self::Solid => {
+                painter.hline(x_range, y, stroke);
+            }
+            Self::Dashed => painter.extend(egui::Shape::dashed_line(&points, stroke, 6.0, 4.0)),
+            Self::Dotted => painter.extend(egui::Shape::dotted_line(&points, colour, 5.0, 1.5)),
+            Self::Double => {
+                let stroke = egui::Stroke::new(1.0, colour);
+                painter.hline(x_range, y - 1.0, stroke);
+                painter.hline(x_range, y + 2.0, stroke);
+            }
+            Self::Marker => {
+                painter.circle_filled(egui::pos2(x_range.center(), y + 1.0), 3.0, colour);
+            }
+        }
+    }
 }
@@ -1,4 +1,8 @@
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
 
 use anyhow::{anyhow, Context, Result};
 use dashmap::DashMap;
@@ -6,33 +10,51 @@ use eframe::egui;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
-use crate::services::ServiceJob;
+use crate::services::{health::ServiceKind, http, jpdb, srs, ServiceJob};
 use crate::word::Word;
 use crate::{config::Config, word::Definition};
 
-use super::{CardState, SrsService};
+use super::{CardState, SrsService, UnderlineStyle};
 
 // This file only contains the code for using jpdb as an SRS. For jpdb configuration and other
 // jpdb features, see `service/dictionary/jpdb.rs`.
 
-const API_URL_PARSE: &'static str = "https://jpdb.io/api/v1/parse";
-const API_URL_LOOKUP: &'static str = "https://jpdb.io/api/v1/lookup-vocabulary";
-const API_URL_ADD_TO_DECK: &'static str = "https://jpdb.io/api/v1/deck/add-vocabulary";
-const API_URL_LIST_DECKS: &'static str = "https://jpdb.io/api/v1/list-user-decks";
+const API_URL_PARSE: &str = "https://jpdb.io/api/v1/parse";
+const API_URL_LOOKUP: &str = "https://jpdb.io/api/v1/lookup-vocabulary";
+const API_URL_ADD_TO_DECK: &str = "https://jpdb.io/api/v1/deck/add-vocabulary";
+const API_URL_LIST_DECKS: &str = "https://jpdb.io/api/v1/list-user-decks";
+
+const STATE_UNPARSED: &str = "unparsed";
+const STATE_NOT_IN_DECK: &str = "not in deck";
+const STATE_NEW: &str = "new";
+
+/// Colour given to card states returned by jpdb which aren't in the configuration yet.
+const UNKNOWN_STATE_COLOUR: [u8; 3] = [200, 160, 255];
 
 #[derive(Default)]
 pub struct JpdbSrs {
     config: JpdbSrsConfig,
-    card_states_with_ids: Arc<DashMap<(u64, u64), usize>>,
-    card_states_without_ids: Arc<DashMap<String, usize>>,
+    /// Names of the card states of words, by jpdb ids.
+    card_states_with_ids: Arc<DashMap<(u64, u64), String>>,
+    /// Names of the card states of words which have no jpdb ids, by spelling.
+    card_states_without_ids: Arc<DashMap<String, String>>,
+    /// Names of the card states returned by jpdb since they were last added to the configuration.
+    returned_states: Arc<Mutex<HashSet<String>>>,
+    /// Index of each configured card state in `config.card_states`, by name.
+    state_indices: HashMap<String, usize>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct JpdbSrsConfig {
-    pub api_key: String,
     pub mining_deck_id: u64,
+    /// Whether the occurrence count sent when adding a word replaces the one already in the deck,
+    /// instead of being added to it.
+    pub replace_existing_occurrences: bool,
 
-    pub card_states: [CardState; 7],
+    /// Card states jpdb can return, in the order they are shown in the config UI. States returned by
+    /// jpdb which aren't in the list yet are added to it automatically.
+    pub card_states: Vec<CardState>,
 
     #[serde(skip)]
     pub decks: BTreeMap<u64, String>,
@@ -41,43 +63,56 @@ pub struct JpdbSrsConfig {
 impl Default for JpdbSrsConfig {
     fn default() -> Self {
         Self {
-            api_key: String::new(),
             mining_deck_id: 0,
-            card_states: [
+            replace_existing_occurrences: true,
+            card_states: vec![
                 CardState {
-                    name: "unparsed".to_owned(),
+                    name: STATE_UNPARSED.to_owned(),
                     colour: [255, 255, 255],
                     is_relevant: false,
+                    underline: UnderlineStyle::None,
                 },
                 CardState {
-                    name: "not in deck".to_owned(),
+                    name: STATE_NOT_IN_DECK.to_owned(),
                     colour: [0, 200, 255],
                     is_relevant: true,
+                    underline: UnderlineStyle::Solid,
                 },
                 CardState {
-                    name: "new".to_owned(),
+                    name: STATE_NEW.to_owned(),
                     colour: [170, 240, 255],
                     is_relevant: true,
+                    underline: UnderlineStyle::Dashed,
                 },
                 CardState {
                     name: "learning".to_owned(),
                     colour: [170, 240, 255],
                     is_relevant: true,
+                    underline: UnderlineStyle::Dotted,
                 },
                 CardState {
                     name: "due".to_owned(),
                     colour: [255, 75, 60],
                     is_relevant: true,
+                    underline: UnderlineStyle::Double,
+                },
+                CardState {
+                    name: "failed".to_owned(),
+                    colour: [255, 75, 60],
+                    is_relevant: true,
+                    underline: UnderlineStyle::Marker,
                 },
                 CardState {
                     name: "known".to_owned(),
                     colour: [125, 255, 125],
                     is_relevant: false,
+                    underline: UnderlineStyle::None,
                 },
                 CardState {
                     name: "blacklisted".to_owned(),
                     colour: [192, 192, 192],
                     is_relevant: false,
+                    underline: UnderlineStyle::None,
                 },
             ],
             decks: BTreeMap::new(),
@@ -91,11 +126,6 @@ impl Config for JpdbSrsConfig {
     }
 
     fn show_ui(&mut self, ui: &mut eframe::egui::Ui) {
-        ui.horizontal(|ui| {
-            ui.label("API Key:");
-            ui.text_edit_singleline(&mut self.api_key);
-        });
-
         if self.decks.is_empty() {
             ui.horizontal(|ui| {
                 ui.label("Mining Deck ID:");
@@ -119,6 +149,9 @@ impl Config for JpdbSrsConfig {
             });
         }
 
+        ui.checkbox(&mut self.replace_existing_occurrences, "Replace Existing Occurrences")
+            .on_hover_text("When adding a word which is already in the mining deck, replace its\noccurrence count instead of adding the occurrences in the current text to it.");
+
         ui.collapsing("Card States", |ui| {
             ui.columns_const(|[col1, col2]| {
                 for state in &mut self.card_states {
@@ -126,7 +159,18 @@ impl Config for JpdbSrsConfig {
                         egui::color_picker::color_edit_button_srgb(ui, &mut state.colour);
                         ui.label(&state.name);
                     });
-                    col2.checkbox(&mut state.is_relevant, "is relevant").on_hover_text("The selection will skip over words not marked as\nrelevant when holding R2.");
+                    col2.horizontal(|ui| {
+                        ui.checkbox(&mut state.is_relevant, "is relevant").on_hover_text("The selection will skip over words not marked as\nrelevant when holding R2.");
+                        egui::ComboBox::from_id_salt(("card state underline", &state.name))
+                            .selected_text(state.underline.name())
+                            .show_ui(ui, |ui| {
+                                for style in UnderlineStyle::ALL {
+                                    ui.selectable_value(&mut state.underline, *style, style.name());
+                                }
+                            })
+                            .response
+                            .on_hover_text("Underline shown in monochrome mode.");
+                    });
                 }
             });
         });
@@ -137,21 +181,21 @@ impl SrsService for JpdbSrs {
     fn init(&mut self) -> Result<()> {
         self.config =
             JpdbSrsConfig::load().context("JpdbSrs: Failed to load configuration file")?;
+        self.index_states();
+        jpdb::load_accounts()?;
 
         let _ = (|| -> Option<()> {
-            let decks: Value = attohttpc::post(API_URL_LIST_DECKS)
-                .bearer_auth(&self.config.api_key)
-                .json(&json!({
+            let decks = http::post_json(
+                API_URL_LIST_DECKS,
+                &jpdb::api_key().ok()?,
+                &json!({
                     "fields": [
                         "id",
                         "name"
                     ]
-                }))
-                .ok()?
-                .send()
-                .ok()?
-                .json()
-                .ok()?;
+                }),
+            )
+            .ok()?;
 
             for deck in decks.get("decks")?.as_array()? {
                 let id = deck.get(0)?.as_u64()?;
@@ -165,17 +209,20 @@ impl SrsService for JpdbSrs {
     }
 
     fn terminate(&mut self) -> anyhow::Result<()> {
+        self.add_unknown_states();
         self.config
             .save()
             .context("JpdbSrs: Failed to save configuration file")?;
+        jpdb::save_accounts()?;
         Ok(())
     }
 
     fn show_config_ui(&mut self, ui: &mut eframe::egui::Ui) {
+        self.add_unknown_states();
         self.config.show_ui(ui);
     }
 
-    fn add_to_deck(&mut self, word: &Word) -> ServiceJob<Result<()>> {
+    fn add_to_deck(&mut self, word: &Word, occurrences: u32) -> ServiceJob<Result<bool>> {
         let config = self.config.clone();
 
         let spelling = word
@@ -187,10 +234,13 @@ impl SrsService for JpdbSrs {
 
         let card_states = Arc::clone(&self.card_states_with_ids);
 
-        ServiceJob::new(move || {
-            let json: Value = attohttpc::post(API_URL_PARSE)
-                .bearer_auth(&config.api_key)
-                .json(&json!({
+        ServiceJob::tracked(ServiceKind::Srs, move || {
+            let api_key = jpdb::api_key()?;
+
+            let json: Value = http::post_json(
+                API_URL_PARSE,
+                &api_key,
+                &json!({
                     "text": [spelling],
                     "token_fields": [
                     ],
@@ -198,58 +248,53 @@ impl SrsService for JpdbSrs {
                         "vid",
                         "sid"
                     ]
-                }))
-                .unwrap()
-                .send()
-                .context("JpdbSrs: Failed to send http request")?
-                .error_for_status()
-                .context("JpdbSrs: Response status code is not a success code")?
-                .json()
-                .context("JpdbSrs: Response from server is not valid json")?;
+                }),
+            )
+            .context("JpdbSrs: HTTP request failed")?;
 
             let ids = json
                 .get("vocabulary")
-                .map(|v| v.get(0))
-                .flatten()
+                .and_then(|v| v.get(0))
                 .ok_or_else(|| anyhow!("Response from `{API_URL_PARSE}` did not contain a `vocabulary` field, or it was not an array containing at least one element"))?;
 
             let vid = ids
                 .get(0)
-                .map(|v| v.as_u64())
-                .flatten()
+                .and_then(|v| v.as_u64())
                 .ok_or_else(|| anyhow!("Data returned from `{API_URL_PARSE}` is incorrect."))?;
 
             let sid = ids
                 .get(1)
-                .map(|v| v.as_u64())
-                .flatten()
+                .and_then(|v| v.as_u64())
                 .ok_or_else(|| anyhow!("Data returned from `{API_URL_PARSE}` is incorrect."))?;
 
-            attohttpc::post(API_URL_ADD_TO_DECK)
-                .bearer_auth(&config.api_key)
-                .json(&json!({
+            let sent = http::post_json_with_side_effects(
+                API_URL_ADD_TO_DECK,
+                &api_key,
+                &json!({
                     "id": config.mining_deck_id,
                     "vocabulary": [[vid, sid]],
-                    "occurences": [1],
-                    "replace_existing_occurences": true
-                }))
-                .unwrap()
-                .send()
-                .context("JpdbSrs: Failed to send http request")?
-                .error_for_status()
-                .context("JpdbSrs: Response status code is not a success code")?;
-
-            card_states.insert((vid, sid), 2);
+                    "occurences": [occurrences.max(1)],
+                    "replace_existing_occurences": config.replace_existing_occurrences
+                }),
+            )
+            .context("JpdbSrs: HTTP request failed")?
+            .is_some();
+
+            if sent {
+                card_states.insert((vid, sid), STATE_NEW.to_owned());
+                srs::card_states_changed();
+            }
 
-            Ok(())
+            Ok(sent)
         })
     }
 
     fn load_card_states(&mut self, words: Vec<Word>) -> ServiceJob<Result<()>> {
-        let config = self.config.clone();
-
         let map_with_ids = Arc::clone(&self.card_states_with_ids);
         let map_without_ids = Arc::clone(&self.card_states_without_ids);
+        let returned_states = Arc::clone(&self.returned_states);
+
+        self.add_unknown_states();
 
         // we do this in two steps here to ensure we get the right word if ids are set, since jpdb
         // can have different entries with the same spelling.
@@ -267,45 +312,34 @@ impl SrsService for JpdbSrs {
             .map(|definition| definition.spelling.clone())
             .collect();
 
-        ServiceJob::new(move || -> Result<()> {
+        ServiceJob::tracked(ServiceKind::Srs, move || -> Result<()> {
+            let api_key = jpdb::api_key()?;
+            let mut seen_states = HashSet::new();
+
             if !words_without_ids.is_empty() {
-                let json: Value = attohttpc::post(API_URL_PARSE)
-                    .bearer_auth(&config.api_key)
-                    .json(&json!({
+                let json: Value = http::post_json(
+                    API_URL_PARSE,
+                    &api_key,
+                    &json!({
                         "text": words_without_ids,
                         "token_fields": [],
                         "vocabulary_fields": [
                             "card_state"
                         ]
-                    }))
-                    .unwrap()
-                    .send()
-                    .context("JpdbSrs: Failed to send http request")?
-                    .error_for_status()
-                    .context("JpdbSrs: Response status code is not a success code")?
-                    .json()
-                    .context("JpdbSrs: Response from server is not valid json")?;
+                    }),
+                )
+                .context("JpdbSrs: HTTP request failed")?;
 
                 let ids_and_states = json
                 .get("vocabulary")
-                .map(Value::as_array)
-                .flatten()
+                .and_then(Value::as_array)
                 .ok_or_else(|| anyhow!("Response from `{API_URL_PARSE}` did not contain a `vocabulary` field, or it was not an array containing at least one element"))?;
 
                 for (value, spelling) in ids_and_states.iter().zip(words_without_ids) {
                     (|| -> Option<()> {
-                        if let Some(state_name) = value.get(0)?.as_str() {
-                            if let Some((idx, _)) = config
-                                .card_states
-                                .iter()
-                                .enumerate()
-                                .find(|(_, state)| state.name == state_name)
-                            {
-                                map_without_ids.insert(spelling, idx);
-                            }
-                        } else {
-                            map_without_ids.insert(spelling, 1);
-                        }
+                        let state_name = value.get(0)?.as_str().unwrap_or(STATE_NOT_IN_DECK);
+                        map_without_ids.insert(spelling, state_name.to_owned());
+                        seen_states.insert(state_name.to_owned());
 
                         Some(())
                     })()
@@ -314,41 +348,30 @@ impl SrsService for JpdbSrs {
             }
 
             if !words_with_ids.is_empty() {
-                let json: Value = attohttpc::post(API_URL_LOOKUP)
-                    .bearer_auth(&config.api_key)
-                    .json(&json!({
+                let json: Value = http::post_json(
+                    API_URL_LOOKUP,
+                    &api_key,
+                    &json!({
                         "list": words_with_ids,
                         "fields": ["card_state"]
-                    }))
-                    .unwrap()
-                    .send()
-                    .context("JpdbSrs: Failed to send http request")?
-                    .error_for_status()
-                    .context("JpdbSrs: Response status code is not a success code")?
-                    .json()
-                    .context("JpdbSrs: Response from server is not valid json")?;
+                    }),
+                )
+                .context("JpdbSrs: HTTP request failed")?;
 
                 let states = json
                 .get("vocabulary_info")
-                .map(Value::as_array)
-                .flatten()
+                .and_then(Value::as_array)
                 .ok_or_else(|| anyhow!("Response from `{API_URL_LOOKUP}` did not contain a `vocabulary_info` field, or it was not an array containing at least one element"))?;
 
                 for (value, ids) in states.iter().zip(words_with_ids) {
                     (|| -> Option<()> {
-                        if let Some(state_name) = value.get(0)?.get(0).map(Value::as_str).flatten()
-                        {
-                            if let Some((idx, _)) = config
-                                .card_states
-                                .iter()
-                                .enumerate()
-                                .find(|(_, state)| state.name == state_name)
-                            {
-                                map_with_ids.insert(ids, idx);
-                            }
-                        } else {
-                            map_with_ids.insert(ids, 1);
-                        }
+                        let state_name = value
+                            .get(0)?
+                            .get(0)
+                            .and_then(Value::as_str)
+                            .unwrap_or(STATE_NOT_IN_DECK);
+                        map_with_ids.insert(ids, state_name.to_owned());
+                        seen_states.insert(state_name.to_owned());
 
                         Some(())
                     })()
@@ -358,21 +381,22 @@ impl SrsService for JpdbSrs {
                 }
             }
 
+            returned_states.lock().unwrap().extend(seen_states);
+            srs::card_states_changed();
             Ok(())
         })
     }
 
-    fn card_state(&self, word: &Word) -> &CardState {
-        match &word.definition {
-            None => &self.config.card_states[0],
+    fn card_state(&self, word: &Word) -> Cow<'_, CardState> {
+        let state = match &word.definition {
+            None => None,
             Some(Definition {
                 jpdb_vid_sid: Some(ids),
                 ..
             }) => self
                 .card_states_with_ids
                 .get(ids)
-                .map(|idx| &self.config.card_states[*idx.value()])
-                .unwrap_or(&self.config.card_states[0]),
+                .map(|name| self.state_named(&name)),
             Some(Definition {
                 reading,
                 jpdb_vid_sid: None,
@@ -380,8 +404,51 @@ impl SrsService for JpdbSrs {
             }) => self
                 .card_states_without_ids
                 .get(reading)
-                .map(|idx| &self.config.card_states[*idx.value()])
-                .unwrap_or(&self.config.card_states[0]),
+                .map(|name| self.state_named(&name)),
+        };
+
+        state.unwrap_or_else(|| self.state_named(STATE_UNPARSED))
+    }
+}
+
+impl JpdbSrs {
+    /// The configured card state with the given name, or a default one if it isn't configured yet.
+    fn state_named(&self, name: &str) -> Cow<'_, CardState> {
+        match self.state_indices.get(name) {
+            Some(&idx) => Cow::Borrowed(&self.config.card_states[idx]),
+            None => Cow::Owned(CardState {
+                name: name.to_owned(),
+                colour: UNKNOWN_STATE_COLOUR,
+                is_relevant: false,
+                underline: UnderlineStyle::None,
+            }),
+        }
+    }
+
+    /// Index the configured card states by name.
+    fn index_states(&mut self) {
+        self.state_indices = self
+            .config
+            .card_states
+            .iter()
+            .enumerate()
+            .map(|(idx, state)| (state.name.clone(), idx))
+            .collect();
+    }
+
+    /// Add the card states returned by jpdb which aren't in the configuration yet to it, so that
+    /// the user can customise them.
+    fn add_unknown_states(&mut self) {
+        let names = std::mem::take(&mut *self.returned_states.lock().unwrap());
+
+        for name in names {
+            if !self.state_indices.contains_key(&name) {
+                log::info!("JpdbSrs: Adding unknown card state `{name}`");
+                let state = self.state_named(&name).into_owned();
+                self.config.card_states.push(state);
+                self.state_indices
+                    .insert(name, self.config.card_states.len() - 1);
+            }
         }
     }
 }
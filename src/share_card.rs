@@ -0,0 +1,218 @@
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+use anyhow::{anyhow, Context, Result};
+use image::{imageops::FilterType, Rgba, RgbaImage};
+
+use crate::{data, fonts::MAIN_FONT, word::Definition};
+
+const CARD_WIDTH: u32 = 800;
+const PADDING: u32 = 32;
+const SPACING: u32 = 16;
+const LINE_HEIGHT_FACTOR: f32 = 1.3;
+
+const BACKGROUND: [u8; 3] = [0x1e, 0x1e, 0x1e];
+const WHITE: [u8; 3] = [0xff, 0xff, 0xff];
+const GREY: [u8; 3] = [0xb0, 0xb0, 0xb0];
+
+/// A block of text on the card, wrapped to the card's width.
+struct TextBlock {
+    lines: Vec<String>,
+    size: f32,
+    colour: [u8; 3],
+}
+
+impl TextBlock {
+    fn height(&self) -> u32 {
+        (self.lines.len() as f32 * self.size * LINE_HEIGHT_FACTOR).ceil() as u32
+    }
+}
+
+/// Render a shareable card for a word: its spelling, reading and meanings, the sentence it was
+/// found in, and a thumbnail of the part of the screenshot it was read from, if there is one.
+pub fn render(
+    definition: &Definition,
+    sentence: &str,
+    screenshot: Option<&RgbaImage>,
+) -> Result<RgbaImage> {
+    let font = FontRef::try_from_slice(MAIN_FONT).context("Could not load font for share card")?;
+    let text_width = (CARD_WIDTH - 2 * PADDING) as f32;
+
+    let meanings = definition
+        .meanings
+        .iter()
+        .map(|meaning| format!("・{meaning}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let blocks: Vec<TextBlock> = [
+        (definition.spelling.as_str(), 64.0, WHITE),
+        (definition.reading.as_str(), 24.0, GREY),
+        (meanings.as_str(), 22.0, WHITE),
+        (sentence, 22.0, GREY),
+    ]
+    .into_iter()
+    .filter(|(text, _, _)| !text.is_empty())
+    .map(|(text, size, colour)| TextBlock {
+        lines: wrap(&font, text, size, text_width),
+        size,
+        colour,
+    })
+    .collect();
+
+    let thumbnail = screenshot
+        .filter(|screenshot| screenshot.width() > 0)
+        .map(|screenshot| {
+            let thumbnail_width = CARD_WIDTH - 2 * PADDING;
+            let thumbnail_height = (screenshot.height() as u64 * thumbnail_width as u64
+                / screenshot.width() as u64)
+                .max(1) as u32;
+            image::imageops::resize(
+                screenshot,
+                thumbnail_width,
+                thumbnail_height,
+                FilterType::Triangle,
+            )
+        });
+
+    let height = 2 * PADDING
+        + blocks
+            .iter()
+            .map(|block| block.height() + SPACING)
+            .sum::<u32>()
+        + thumbnail.as_ref().map(|t| t.height()).unwrap_or(0);
+
+    let [r, g, b] = BACKGROUND;
+    let mut card = RgbaImage::from_pixel(CARD_WIDTH, height, Rgba([r, g, b, 0xff]));
+
+    let mut y = PADDING as f32;
+    for block in &blocks {
+        let line_height = block.size * LINE_HEIGHT_FACTOR;
+        for line in &block.lines {
+            draw_text(
+                &mut card,
+                &font,
+                line,
+                block.size,
+                block.colour,
+                PADDING as f32,
+                y,
+            );
+            y += line_height;
+        }
+        y += SPACING as f32;
+    }
+
+    if let Some(thumbnail) = thumbnail {
+        image::imageops::overlay(&mut card, &thumbnail, PADDING as i64, y as i64);
+    }
+
+    Ok(card)
+}
+
+/// Save a card to the `cards` folder in the data directory. Returns the path it was saved to.
+pub fn save(card: &RgbaImage, definition: &Definition) -> Result<PathBuf> {
+    let dir = data::data_dir()?.join("cards");
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("Could not create directory: `{}`", dir.display()))?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let file_name: String = definition
+        .spelling
+        .chars()
+        .filter(|c| !std::path::is_separator(*c) && !r#":*?"<>|"#.contains(*c))
+        .collect();
+    let path = dir.join(format!("{file_name}-{timestamp}.png"));
+
+    card.save(&path)
+        .map_err(|e| anyhow!(e))
+        .with_context(|| format!("Could not save share card to `{}`", path.display()))?;
+
+    Ok(path)
+}
+
+/// Split text into lines which fit in `max_width`, breaking at spaces when possible.
+fn wrap(font: &FontRef, text: &str, size: f32, max_width: f32) -> Vec<String> {
+    let font = font.as_scaled(PxScale::from(size));
+    let mut lines = Vec::new();
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        let mut width = 0.0;
+
+        for c in paragraph.chars() {
+            let advance = font.h_advance(font.glyph_id(c));
+
+            if width + advance > max_width && !line.is_empty() {
+                // break at the last space if there is one, otherwise in the middle of the text
+                let rest = match line.rfind(' ') {
+                    Some(idx) if c != ' ' => line.split_off(idx + 1),
+                    _ => String::new(),
+                };
+                lines.push(line.trim_end().to_owned());
+                width = rest.chars().map(|c| font.h_advance(font.glyph_id(c))).sum();
+                line = rest;
+
+                if c == ' ' {
+                    continue;
+                }
+            }
+
+            line.push(c);
+            width += advance;
+        }
+
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Draw a single line of text with its top left corner at (`x`, `y`).
+fn draw_text(
+    image: &mut RgbaImage,
+    font: &FontRef,
+    text: &str,
+    size: f32,
+    colour: [u8; 3],
+    x: f32,
+    y: f32,
+) {
+    let scale = PxScale::from(size);
+    let scaled = font.as_scaled(scale);
+    let baseline = y + scaled.ascent();
+
+    let mut caret = x;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        let glyph = id.with_scale_and_position(scale, point(caret, baseline));
+        caret += scaled.h_advance(id);
+
+        let Some(outlined) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outlined.px_bounds();
+
+        outlined.draw(|gx, gy, coverage| {
+            let px = bounds.min.x as i64 + gx as i64;
+            let py = bounds.min.y as i64 + gy as i64;
+
+            if px < 0 || py < 0 || px >= image.width() as i64 || py >= image.height() as i64 {
+                return;
+            }
+
+            let pixel = image.get_pixel_mut(px as u32, py as u32);
+            for i in 0..3 {
+                let blended =
+                    pixel[i] as f32 + (colour[i] as f32 - pixel[i] as f32) * coverage.min(1.0);
+                pixel[i] = blended.round() as u8;
+            }
+        });
+    }
+}
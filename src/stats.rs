@@ -0,0 +1,182 @@
+use std::{
+    collections::BTreeMap,
+    ops::RangeInclusive,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{data::DataFile, word::Word};
+
+pub const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+/// Game reading time is attributed to when the text wasn't captured with a capture preset.
+pub const UNKNOWN_GAME: &str = "Unknown";
+
+/// Statistics about the user's mining, kept across sessions.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    /// Words added to the user's deck, oldest first.
+    pub mined: Vec<MinedWord>,
+    /// Results of quick reviews in the OCR window, oldest first.
+    pub reviews: Vec<ReviewResult>,
+    /// Number of characters in the text captured each day (see `today()`).
+    pub characters_read: BTreeMap<u64, u64>,
+    /// Number of seconds the OCR window was open each day (see `today()`), keyed by the game it
+    /// was attributed to (the name of the capture preset used, or `UNKNOWN_GAME`).
+    pub reading_seconds: BTreeMap<String, BTreeMap<u64, u64>>,
+    /// Day (see `today()`) on which the last daily summary was sent, if any.
+    pub last_summary_day: Option<u64>,
+    /// Day (see `today()`) on which the last weekly summary was generated, if any.
+    pub last_weekly_summary_day: Option<u64>,
+}
+
+/// A word which was added to the user's deck.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MinedWord {
+    pub spelling: String,
+    pub reading: String,
+    /// The paragraph the word was mined from.
+    pub sentence: String,
+    /// When the word was mined, in seconds since the Unix epoch.
+    pub mined_at: u64,
+}
+
+/// The result of reviewing a word in the OCR window's quick review.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewResult {
+    pub spelling: String,
+    pub reading: String,
+    pub passed: bool,
+    /// When the word was reviewed, in seconds since the Unix epoch.
+    pub reviewed_at: u64,
+}
+
+impl DataFile for Stats {
+    fn path() -> &'static str {
+        "stats.json"
+    }
+}
+
+impl Stats {
+    /// Record that a word was added to the user's deck.
+    pub fn record_mined(&mut self, word: &Word, sentence: &str) {
+        let Some(definition) = &word.definition else {
+            return;
+        };
+
+        self.mined.push(MinedWord {
+            spelling: definition.spelling.clone(),
+            reading: definition.reading.clone(),
+            sentence: sentence.to_owned(),
+            mined_at: now(),
+        });
+    }
+
+    /// Record the result of a quick review.
+    pub fn record_review(&mut self, word: &Word, passed: bool) {
+        let Some(definition) = &word.definition else {
+            return;
+        };
+
+        self.reviews.push(ReviewResult {
+            spelling: definition.spelling.clone(),
+            reading: definition.reading.clone(),
+            passed,
+            reviewed_at: now(),
+        });
+    }
+
+    /// Record that the text of a capture was read.
+    pub fn record_read(&mut self, characters: usize) {
+        *self.characters_read.entry(today()).or_default() += characters as u64;
+    }
+
+    /// Record the time an OCR window was open, attributed to the given game.
+    pub fn record_reading_time(&mut self, game: &str, duration: Duration) {
+        let seconds = duration.as_secs();
+        if seconds == 0 {
+            return;
+        }
+
+        *self
+            .reading_seconds
+            .entry(game.to_owned())
+            .or_default()
+            .entry(today())
+            .or_default() += seconds;
+    }
+
+    /// Number of seconds spent reading each game over the given days, most read first. Games which
+    /// weren't read on those days are left out.
+    pub fn reading_time(&self, days: RangeInclusive<u64>) -> Vec<(&str, u64)> {
+        let mut games: Vec<(&str, u64)> = self
+            .reading_seconds
+            .iter()
+            .map(|(game, seconds)| {
+                (
+                    game.as_str(),
+                    seconds.range(days.clone()).map(|(_, s)| s).sum(),
+                )
+            })
+            .filter(|(_, seconds)| *seconds > 0)
+            .collect();
+        games.sort_by_key(|(_, seconds)| std::cmp::Reverse(*seconds));
+        games
+    }
+
+    /// Words mined on the given day.
+    pub fn mined_on(&self, day: u64) -> impl Iterator<Item = &MinedWord> {
+        self.mined
+            .iter()
+            .filter(move |word| word.mined_at / SECONDS_PER_DAY == day)
+    }
+
+    /// Number of consecutive days, ending on the given day, on which text was read or words were mined.
+    pub fn streak(&self, until_day: u64) -> u64 {
+        let active = |day: u64| {
+            self.characters_read.get(&day).is_some_and(|n| *n > 0)
+                || self.mined_on(day).next().is_some()
+        };
+
+        (0..=until_day).rev().take_while(|day| active(*day)).count() as u64
+    }
+}
+
+/// Current time, in seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Number of days since the Unix epoch (in UTC).
+pub fn today() -> u64 {
+    now() / SECONDS_PER_DAY
+}
+
+/// Format a number of seconds as hours and minutes, eg. `2h 05m`.
+pub fn format_duration(seconds: u64) -> String {
+    let minutes = seconds / 60;
+    match minutes / 60 {
+        0 => format!("{minutes}m"),
+        hours => format!("{hours}h {:02}m", minutes % 60),
+    }
+}
+
+/// Format a number of days since the Unix epoch as a `YYYY-MM-DD` date.
+pub fn format_day(day: u64) -> String {
+    // civil-from-days, from Howard Hinnant's date algorithms
+    let z = day as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+
+    format!("{y:04}-{m:02}-{d:02}")
+}
@@ -0,0 +1,84 @@
+use anyhow::{Context, Result};
+
+use crate::services::ocr::{OcrServiceJob, ParagraphOrder};
+
+/// Pages captured with the stitch hotkey, waiting to be combined with the next OCR capture.
+#[derive(Default)]
+pub struct StitchBuffer {
+    pages: Vec<Page>,
+}
+
+enum Page {
+    Loading(OcrServiceJob),
+    Done(Vec<String>),
+}
+
+impl StitchBuffer {
+    /// Add a page whose text is being extracted by the given job.
+    pub fn push(&mut self, job: OcrServiceJob) {
+        self.pages.push(Page::Loading(job));
+    }
+
+    /// Number of pages in the buffer.
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Whether the buffer contains no pages.
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+
+    /// Check on the pages which are still loading, sorting their paragraphs in the given order.
+    /// Returns `Ok(true)` once every page is done.
+    pub fn poll(&mut self, order: ParagraphOrder) -> Result<bool> {
+        let mut all_done = true;
+
+        for (idx, page) in self.pages.iter_mut().enumerate() {
+            if let Page::Loading(job) = page {
+                match job
+                    .try_wait()?
+                    .transpose()
+                    .with_context(|| format!("OCR failed for stitched page {}", idx + 1))?
+                {
+                    None => all_done = false,
+                    Some((response, _)) => *page = Page::Done(response.into_paragraphs(order)),
+                }
+            }
+        }
+
+        Ok(all_done)
+    }
+
+    /// Stitch all pages in the buffer and `last_page` together, removing lines duplicated
+    /// because consecutive captures overlapped.
+    ///
+    /// Pages which haven't finished loading are skipped, so `poll()` should return `Ok(true)` first.
+    pub fn stitch(self, last_page: Vec<String>) -> Vec<String> {
+        self.pages
+            .into_iter()
+            .filter_map(|page| match page {
+                Page::Done(text) => Some(text),
+                Page::Loading(_) => None,
+            })
+            .chain(std::iter::once(last_page))
+            .fold(Vec::new(), |mut stitched, page| {
+                let overlap = overlap(&stitched, &page);
+                stitched.extend(page.into_iter().skip(overlap));
+                stitched
+            })
+    }
+}
+
+/// Length of the longest run of lines at the end of `previous` which also starts `next`.
+fn overlap(previous: &[String], next: &[String]) -> usize {
+    let normalise = |line: &String| line.split_whitespace().collect::<String>();
+
+    let previous: Vec<_> = previous.iter().map(normalise).collect();
+    let next: Vec<_> = next.iter().map(normalise).collect();
+
+    (1..=previous.len().min(next.len()))
+        .rev()
+        .find(|&len| previous[previous.len() - len..] == next[..len])
+        .unwrap_or(0)
+}
@@ -0,0 +1,185 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data,
+    discord::DiscordConfig,
+    stats::{self, Stats},
+};
+
+/// Number of days covered by a weekly summary.
+const DAYS_PER_WEEK: u64 = 7;
+/// Maximum number of words listed in a weekly summary posted to Discord.
+const DISCORD_MAX_WORDS: usize = 30;
+
+/// Optional weekly digest of the user's mining activity, written to a file and optionally posted
+/// to Discord. Off by default.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeeklySummaryConfig {
+    /// Whether a summary of the previous week should be written once a week.
+    pub enabled: bool,
+    /// Whether the summary should also be posted to the Discord webhook, if one is configured.
+    pub post_to_discord: bool,
+}
+
+impl WeeklySummaryConfig {
+    /// Write a summary of the previous 7 days, if enabled and one wasn't written in that time.
+    pub fn generate(&self, stats: &mut Stats, discord: &DiscordConfig) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let today = stats::today();
+        if stats
+            .last_weekly_summary_day
+            .is_some_and(|day| today < day + DAYS_PER_WEEK)
+        {
+            return Ok(());
+        }
+        stats.last_weekly_summary_day = Some(today);
+
+        let summary = Summary::new(stats, today - DAYS_PER_WEEK, today - 1);
+
+        let path = summary_path(today)?;
+        std::fs::create_dir_all(path.parent().unwrap()).with_context(|| {
+            format!(
+                "Could not create weekly summary directory: `{}`",
+                path.display()
+            )
+        })?;
+        std::fs::write(&path, summary.to_text(usize::MAX))
+            .with_context(|| format!("Could not write weekly summary: `{}`", path.display()))?;
+        log::info!("Wrote weekly summary to `{}`", path.display());
+
+        if self.post_to_discord {
+            discord.post_summary(summary.to_text(DISCORD_MAX_WORDS));
+        }
+
+        Ok(())
+    }
+
+    /// Show the UI for configuring the weekly summary.
+    pub fn show_ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.enabled, "Write Weekly Summary")
+            .on_hover_text("Written to the `weekly_summaries` folder in the data directory the first\ntime deckocr is used each week, for the previous 7 days (UTC).");
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.checkbox(&mut self.post_to_discord, "Post to Discord")
+                .on_hover_text("Requires a webhook URL in the Discord section.");
+        });
+    }
+}
+
+/// Path of the summary written on the given day.
+fn summary_path(day: u64) -> Result<PathBuf> {
+    let mut path = data::data_dir()?;
+    path.push("weekly_summaries");
+    path.push(format!("{}.txt", stats::format_day(day)));
+    Ok(path)
+}
+
+/// The user's activity over a range of days.
+struct Summary {
+    first_day: u64,
+    last_day: u64,
+    /// Spelling and reading of the words mined, oldest first.
+    words: Vec<(String, String)>,
+    characters_read: u64,
+    /// Seconds spent reading each game, most read first.
+    reading_time: Vec<(String, u64)>,
+    reviews_passed: usize,
+    reviews: usize,
+    streak: u64,
+    /// The day with the most words mined, and how many were, if any were.
+    best_day: Option<(u64, usize)>,
+}
+
+impl Summary {
+    fn new(stats: &Stats, first_day: u64, last_day: u64) -> Self {
+        let days = first_day..=last_day;
+        let in_range = |timestamp: u64| days.contains(&(timestamp / stats::SECONDS_PER_DAY));
+
+        let reviews: Vec<_> = stats
+            .reviews
+            .iter()
+            .filter(|review| in_range(review.reviewed_at))
+            .collect();
+
+        Self {
+            first_day,
+            last_day,
+            words: stats
+                .mined
+                .iter()
+                .filter(|word| in_range(word.mined_at))
+                .map(|word| (word.spelling.clone(), word.reading.clone()))
+                .collect(),
+            characters_read: stats
+                .characters_read
+                .range(days.clone())
+                .map(|(_, n)| n)
+                .sum(),
+            reading_time: stats
+                .reading_time(days.clone())
+                .into_iter()
+                .map(|(game, seconds)| (game.to_owned(), seconds))
+                .collect(),
+            reviews_passed: reviews.iter().filter(|review| review.passed).count(),
+            reviews: reviews.len(),
+            streak: stats.streak(last_day),
+            best_day: days
+                .clone()
+                .map(|day| (day, stats.mined_on(day).count()))
+                .filter(|(_, count)| *count > 0)
+                .max_by_key(|(_, count)| *count),
+        }
+    }
+
+    /// The summary as plain text, listing at most `max_words` of the words mined.
+    fn to_text(&self, max_words: usize) -> String {
+        let mut text = format!(
+            "Weekly summary for {} to {}\n\nWords mined: {}\nCharacters read: {}\nQuick reviews passed: {} / {}\nCurrent streak: {} day(s)\n",
+            stats::format_day(self.first_day),
+            stats::format_day(self.last_day),
+            self.words.len(),
+            self.characters_read,
+            self.reviews_passed,
+            self.reviews,
+            self.streak,
+        );
+
+        if let Some((day, count)) = self.best_day {
+            text.push_str(&format!(
+                "Most active day: {} ({count} word(s))\n",
+                stats::format_day(day)
+            ));
+        }
+
+        if !self.reading_time.is_empty() {
+            let total = self.reading_time.iter().map(|(_, seconds)| seconds).sum();
+            text.push_str(&format!(
+                "\nReading time: {}",
+                stats::format_duration(total)
+            ));
+            for (game, seconds) in &self.reading_time {
+                text.push_str(&format!("\n・{game}: {}", stats::format_duration(*seconds)));
+            }
+            text.push('\n');
+        }
+
+        if !self.words.is_empty() {
+            text.push_str("\nWords:");
+            for (spelling, reading) in self.words.iter().take(max_words) {
+                text.push_str(&format!("\n・{spelling} ({reading})"));
+            }
+            if self.words.len() > max_words {
+                text.push_str(&format!("\n…and {} more", self.words.len() - max_words));
+            }
+        }
+
+        text
+    }
+}
@@ -1,5 +1,7 @@
+use serde::{Deserialize, Serialize};
+
 /// A word and its definition, if one was found.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Word {
     /// The word as it should appear in text.
     pub text: TextWithRuby,
@@ -8,7 +10,7 @@ pub struct Word {
 }
 
 /// A word's definition and associated data.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Definition {
     /// The word's spelling.
     pub spelling: String,
@@ -18,22 +20,126 @@ pub struct Definition {
     pub frequency: Option<u64>,
     /// The word's meanings.
     pub meanings: Vec<String>,
+    /// The word's meanings in the monolingual dictionary, if one is configured and has the word.
+    #[serde(default)]
+    pub monolingual_meanings: Vec<String>,
+    /// Paths to images illustrating the word, shown under its meanings. Loaded when first shown.
+    #[serde(default)]
+    pub images: Vec<String>,
+    /// The word's parts of speech, as jpdb abbreviations (eg. `prt`, `aux-v`). Empty if unknown.
+    #[serde(default)]
+    pub part_of_speech: Vec<String>,
 
     /// The word's jpdb `vid` and `sid` if it was retrieved via the jpdb api.
     pub jpdb_vid_sid: Option<(u64, u64)>,
 }
 
+impl Definition {
+    /// The meanings shown to the user: the monolingual ones if requested and there are any.
+    pub fn glosses(&self, monolingual: bool) -> &[String] {
+        match monolingual && !self.monolingual_meanings.is_empty() {
+            true => &self.monolingual_meanings,
+            false => &self.meanings,
+        }
+    }
+}
+
+/// Parts of speech which should be skipped over along with words not marked as relevant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PartOfSpeechFilter {
+    pub skip_particles: bool,
+    pub skip_auxiliaries: bool,
+    pub skip_interjections: bool,
+}
+
+impl Default for PartOfSpeechFilter {
+    fn default() -> Self {
+        Self {
+            skip_particles: true,
+            skip_auxiliaries: true,
+            skip_interjections: true,
+        }
+    }
+}
+
+impl PartOfSpeechFilter {
+    /// Whether the word should be skipped. Words are only skipped if every one of their parts of
+    /// speech is filtered out, so eg. a word which is both a noun and a particle is kept.
+    pub fn skips(&self, word: &Word) -> bool {
+        let Some(definition) = &word.definition else {
+            return false;
+        };
+
+        !definition.part_of_speech.is_empty()
+            && definition.part_of_speech.iter().all(|pos| {
+                (self.skip_particles && pos == "prt")
+                    || (self.skip_auxiliaries && (pos == "aux" || pos.starts_with("aux-")))
+                    || (self.skip_interjections && pos == "int")
+            })
+    }
+}
+
+/// Kana-only words whose definitions are hidden, so they are shown as plain text and can't be
+/// selected. Declutters the OCR window for learners who already know them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DefinitionSuppression {
+    /// Hide the definitions of kana-only particles.
+    pub kana_only_particles: bool,
+    /// Hide the definitions of kana-only words with a frequency rank at or below this. 0 to disable.
+    pub kana_only_max_frequency_rank: u64,
+}
+
+impl DefinitionSuppression {
+    /// Whether the definition should be hidden.
+    pub fn suppresses(&self, definition: &Definition) -> bool {
+        let is_kana = |c: char| matches!(c, '\u{3040}'..='\u{30ff}');
+        if !definition.spelling.chars().all(is_kana) {
+            return false;
+        }
+
+        (self.kana_only_particles && definition.part_of_speech.iter().any(|pos| pos == "prt"))
+            || definition
+                .frequency
+                .is_some_and(|rank| rank <= self.kana_only_max_frequency_rank)
+    }
+
+    /// Remove the suppressed definitions from the given words.
+    pub fn apply(&self, words: &mut [Vec<Word>]) {
+        for word in words.iter_mut().flatten() {
+            if word
+                .definition
+                .as_ref()
+                .is_some_and(|definition| self.suppresses(definition))
+            {
+                word.definition = None;
+            }
+        }
+    }
+}
+
 /// Text with furigana.
-#[derive(Debug, Hash, Clone)]
+#[derive(Debug, Hash, Clone, Serialize, Deserialize)]
 pub struct TextWithRuby(pub Vec<TextFragment>);
 
 /// A fragment of text, optionally with its associated furigana.
-#[derive(Debug, Hash, Clone)]
+#[derive(Debug, Hash, Clone, Serialize, Deserialize)]
 pub struct TextFragment {
     pub text: String,
     pub ruby: Option<String>,
 }
 
+impl TextWithRuby {
+    /// The text without its furigana.
+    pub fn plain_text(&self) -> String {
+        self.0
+            .iter()
+            .map(|fragment| fragment.text.as_str())
+            .collect()
+    }
+}
+
 impl<F: Into<TextFragment>> FromIterator<F> for TextWithRuby {
     fn from_iter<T: IntoIterator<Item = F>>(iter: T) -> Self {
         TextWithRuby(iter.into_iter().map(|f| f.into()).collect())